@@ -1,20 +1,36 @@
 from ._nucypher_core import (
     Conditions,
+    ConditionLingo,
+    Condition,
     Context,
     Address,
     MessageKit,
     HRAC,
+    PolicyValidity,
     EncryptedKeyFrag,
     TreasureMap,
+    TreasureMapBuilder,
     EncryptedTreasureMap,
+    MultiRecipientEncryptedTreasureMap,
     ReencryptionRequest,
+    RefusalReason,
     ReencryptionResponse,
     RetrievalKit,
+    RetrievalPlan,
+    ReencryptionService,
     RevocationOrder,
+    RevocationReceipt,
+    RevocationCertificate,
+    InMemoryRevocationRegistry,
+    RevocationRegistrySnapshot,
+    RevocationKit,
+    PolicyGrant,
+    EncryptingKeyRotation,
     NodeMetadata,
     NodeMetadataPayload,
     FleetStateChecksum,
     MetadataRequest,
     MetadataResponse,
     MetadataResponsePayload,
+    NucypherCoreError,
     )
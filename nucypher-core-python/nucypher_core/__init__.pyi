@@ -1,10 +1,15 @@
-from typing import List, Dict, Sequence, Optional, Mapping, Tuple, Set
+from typing import Callable, List, Dict, Sequence, Optional, Mapping, Tuple, Set, Union
 
 from .umbral import (
     SecretKey, PublicKey, Signer, Capsule, VerifiedKeyFrag, VerifiedCapsuleFrag,
     RecoverableSignature)
 
 
+class NucypherCoreError(ValueError):
+    code: int
+    code_name: str
+
+
 class Address:
 
     def __init__(self, address_bytes: bytes):
@@ -29,10 +34,88 @@ class Conditions:
     def from_string(cls, conditions: str) -> Conditions:
         ...
 
+    def parse(self) -> ConditionLingo:
+        ...
+
+    def canonical(self) -> Conditions:
+        ...
+
+    def hash(self) -> bytes:
+        ...
+
     def __str__(self) -> str:
         ...
 
 
+class ConditionLingo:
+
+    def __init__(self, condition: Condition):
+        ...
+
+    @property
+    def version(self) -> str:
+        ...
+
+    @property
+    def condition(self) -> Condition:
+        ...
+
+    def validate(self) -> None:
+        ...
+
+    def to_conditions(self) -> Conditions:
+        ...
+
+    def __str__(self) -> str:
+        ...
+
+
+class Condition:
+
+    @staticmethod
+    def block_time(chain: int, comparator: str, timestamp: int) -> Condition:
+        ...
+
+    @staticmethod
+    def native_balance(chain: int, comparator: str, amount: int) -> Condition:
+        ...
+
+    @staticmethod
+    def erc20_balance(chain: int, contract_address: str, comparator: str, amount: int) -> Condition:
+        ...
+
+    @staticmethod
+    def erc721_balance(chain: int, contract_address: str, comparator: str, amount: int) -> Condition:
+        ...
+
+    @staticmethod
+    def erc721_ownership(chain: int, contract_address: str, token_id: int) -> Condition:
+        ...
+
+    @staticmethod
+    def contract_call(
+        chain: int,
+        contract_address: str,
+        function_abi: str,
+        parameters: str,
+        comparator: str,
+        value: str,
+    ) -> Condition:
+        ...
+
+    def and_(self, other: Condition) -> Condition:
+        ...
+
+    def or_(self, other: Condition) -> Condition:
+        ...
+
+    def negate(self) -> Condition:
+        ...
+
+    def build(self) -> Conditions:
+        ...
+
+
 class Context:
 
     def __init__(self, context: str):
@@ -94,6 +177,19 @@ class HRAC:
         ...
 
 
+class PolicyValidity:
+
+    def __init__(self, expiration: int, not_before: Optional[int] = None):
+        ...
+
+    expiration: int
+
+    not_before: Optional[int]
+
+    def is_valid_at(self, now: int) -> bool:
+        ...
+
+
 class EncryptedKeyFrag:
 
     def __init__(
@@ -102,6 +198,9 @@ class EncryptedKeyFrag:
         recipient_key: PublicKey,
         hrac: HRAC,
         verified_kfrag: VerifiedKeyFrag,
+        delegating_key: PublicKey,
+        receiving_key: PublicKey,
+        validity: PolicyValidity,
     ):
         ...
 
@@ -110,6 +209,18 @@ class EncryptedKeyFrag:
         sk: SecretKey,
         hrac: HRAC,
         publisher_verifying_key: PublicKey,
+    ) -> Tuple[VerifiedKeyFrag, PolicyValidity]:
+        ...
+
+    def rewrap(self, sk: SecretKey, new_recipient_key: PublicKey) -> EncryptedKeyFrag:
+        ...
+
+    def decrypt_at(
+        self,
+        sk: SecretKey,
+        hrac: HRAC,
+        publisher_verifying_key: PublicKey,
+        now: int,
     ) -> VerifiedKeyFrag:
         ...
 
@@ -128,15 +239,37 @@ class TreasureMap:
         signer: Signer,
         hrac: HRAC,
         policy_encrypting_key: PublicKey,
+        receiving_key: PublicKey,
         assigned_kfrags: Mapping[Address, Tuple[PublicKey, VerifiedKeyFrag]],
         threshold: int,
+        validity: PolicyValidity,
     ):
         ...
 
     def encrypt(self, signer: Signer, recipient_key: PublicKey) -> EncryptedTreasureMap:
         ...
 
-    def make_revocation_orders(self, signer: Signer) -> List[RevocationOrder]:
+    def encrypt_for_many(
+        self,
+        signer: Signer,
+        recipient_keys: Sequence[PublicKey],
+    ) -> MultiRecipientEncryptedTreasureMap:
+        ...
+
+    def amend(
+        self,
+        signer: Signer,
+        receiving_key: PublicKey,
+        replacements: Mapping[Address, Tuple[Address, PublicKey, VerifiedKeyFrag]],
+        validity: PolicyValidity,
+        issued_at: int,
+    ) -> Tuple[TreasureMap, List[RevocationOrder]]:
+        ...
+
+    def make_revocation_orders(self, signer: Signer, issued_at: int) -> List[RevocationOrder]:
+        ...
+
+    def make_revocation_certificate(self, signer: Signer, issued_at: int) -> RevocationCertificate:
         ...
 
     destinations: Dict[Address, EncryptedKeyFrag]
@@ -149,6 +282,8 @@ class TreasureMap:
 
     publisher_verifying_key: PublicKey
 
+    revision: int
+
     @staticmethod
     def from_bytes(data: bytes) -> TreasureMap:
         ...
@@ -157,6 +292,33 @@ class TreasureMap:
         ...
 
 
+class TreasureMapBuilder:
+
+    def __init__(
+        self,
+        hrac: HRAC,
+        policy_encrypting_key: PublicKey,
+        receiving_key: PublicKey,
+        threshold: int,
+        validity: PolicyValidity,
+    ):
+        ...
+
+    def verify_kfrags(self, verify: bool) -> None:
+        ...
+
+    def add_destination(
+        self,
+        address: Address,
+        ursula_encrypting_key: PublicKey,
+        verified_kfrag: VerifiedKeyFrag,
+    ) -> None:
+        ...
+
+    def build(self, signer: Signer) -> TreasureMap:
+        ...
+
+
 class EncryptedTreasureMap:
 
     def decrypt(
@@ -174,6 +336,27 @@ class EncryptedTreasureMap:
         ...
 
 
+class MultiRecipientEncryptedTreasureMap:
+
+    def decrypt(
+        self,
+        sk: SecretKey,
+        publisher_verifying_key: PublicKey,
+    ) -> TreasureMap:
+        ...
+
+    @property
+    def recipients_count(self) -> int:
+        ...
+
+    @staticmethod
+    def from_bytes(data: bytes) -> MultiRecipientEncryptedTreasureMap:
+        ...
+
+    def __bytes__(self) -> bytes:
+        ...
+
+
 class ReencryptionRequest:
 
     def __init__(
@@ -210,19 +393,31 @@ class ReencryptionRequest:
         ...
 
 
+class RefusalReason:
+
+    ConditionsNotSatisfied: RefusalReason
+
+    ConditionEvaluationFailed: RefusalReason
+
+
 class ReencryptionResponse:
 
-    def __init__(self, signer: Signer, capsules_and_vcfrags: Sequence[Tuple[Capsule, VerifiedCapsuleFrag]]):
+    def __init__(
+        self,
+        signer: Signer,
+        request: ReencryptionRequest,
+        results: Sequence[Union[VerifiedCapsuleFrag, RefusalReason]],
+    ):
         ...
 
     def verify(
         self,
-        capsules: Sequence[Capsule],
+        request: ReencryptionRequest,
         alice_verifying_key: PublicKey,
         ursula_verifying_key: PublicKey,
         policy_encrypting_key: PublicKey,
         bob_encrypting_key: PublicKey,
-    ) -> List[VerifiedCapsuleFrag]:
+    ) -> List[Union[VerifiedCapsuleFrag, NucypherCoreError]]:
         ...
 
     @staticmethod
@@ -261,6 +456,60 @@ class RetrievalKit:
         ...
 
 
+class RetrievalPlan:
+
+    def __init__(
+        self,
+        treasure_map: TreasureMap,
+        retrieval_kits: Sequence[RetrievalKit],
+        bob_encrypting_key: PublicKey,
+        bob_verifying_key: PublicKey,
+        context: Optional[Context],
+    ):
+        ...
+
+    def next_requests(self) -> List[Tuple[Address, ReencryptionRequest]]:
+        ...
+
+    def add_response(
+        self,
+        address: Address,
+        ursula_verifying_key: PublicKey,
+        response: ReencryptionResponse,
+    ) -> None:
+        ...
+
+    def report_failure(self, address: Address) -> None:
+        ...
+
+    def is_decryptable(self, index: int) -> bool:
+        ...
+
+    def is_complete(self) -> bool:
+        ...
+
+    def is_exhausted(self) -> bool:
+        ...
+
+    def verified_cfrags(self, index: int) -> List[VerifiedCapsuleFrag]:
+        ...
+
+
+class ReencryptionService:
+
+    def __init__(
+        self,
+        signer: Signer,
+        decrypting_key: SecretKey,
+        revocation_checker: Callable[[HRAC, EncryptedKeyFrag], bool],
+        condition_evaluator: Callable[[Conditions, Optional[Context]], bool],
+    ):
+        ...
+
+    def handle(self, request: ReencryptionRequest, now: int) -> ReencryptionResponse:
+        ...
+
+
 class RevocationOrder:
 
     def __init__(
@@ -268,9 +517,24 @@ class RevocationOrder:
         signer: Signer,
         staking_provider_address: Address,
         encrypted_kfrag: EncryptedKeyFrag,
+        hrac: HRAC,
+        issued_at: int,
+        reason: Optional[int] = None,
     ):
         ...
 
+    @property
+    def hrac(self) -> HRAC:
+        ...
+
+    @property
+    def issued_at(self) -> int:
+        ...
+
+    @property
+    def reason(self) -> Optional[int]:
+        ...
+
     def verify(
         self,
         alice_verifying_key: PublicKey,
@@ -285,6 +549,189 @@ class RevocationOrder:
         ...
 
 
+class RevocationReceipt:
+
+    def __init__(self, signer: Signer, order: RevocationOrder, acknowledged_at: int):
+        ...
+
+    def verify(self, ursula_verifying_key: PublicKey, order: RevocationOrder) -> int:
+        ...
+
+    @staticmethod
+    def from_bytes(data: bytes) -> RevocationReceipt:
+        ...
+
+    def __bytes__(self) -> bytes:
+        ...
+
+
+class RevocationCertificate:
+
+    def __init__(
+        self,
+        signer: Signer,
+        hrac: HRAC,
+        destinations: Mapping[Address, EncryptedKeyFrag],
+        issued_at: int,
+    ):
+        ...
+
+    @property
+    def hrac(self) -> HRAC:
+        ...
+
+    @property
+    def issued_at(self) -> int:
+        ...
+
+    def verify(
+        self,
+        alice_verifying_key: PublicKey,
+    ) -> Tuple[HRAC, Dict[Address, EncryptedKeyFrag]]:
+        ...
+
+    @staticmethod
+    def from_bytes(data: bytes) -> RevocationCertificate:
+        ...
+
+    def __bytes__(self) -> bytes:
+        ...
+
+
+class InMemoryRevocationRegistry:
+
+    def __init__(self):
+        ...
+
+    @staticmethod
+    def from_snapshot(snapshot: RevocationRegistrySnapshot) -> InMemoryRevocationRegistry:
+        ...
+
+    def revoke(
+        self,
+        order: RevocationOrder,
+        publisher_verifying_key: PublicKey,
+        staking_provider_address: Address,
+        decrypting_key: SecretKey,
+        now: int,
+    ) -> None:
+        ...
+
+    def revoke_policy(
+        self,
+        certificate: RevocationCertificate,
+        publisher_verifying_key: PublicKey,
+        staking_provider_address: Address,
+        decrypting_key: SecretKey,
+        now: int,
+    ) -> None:
+        ...
+
+    def is_revoked(self, hrac: HRAC, encrypted_kfrag: EncryptedKeyFrag) -> bool:
+        ...
+
+    def snapshot(self) -> RevocationRegistrySnapshot:
+        ...
+
+    def __len__(self) -> int:
+        ...
+
+
+class RevocationRegistrySnapshot:
+
+    @staticmethod
+    def from_bytes(data: bytes) -> RevocationRegistrySnapshot:
+        ...
+
+    def __bytes__(self) -> bytes:
+        ...
+
+
+class RevocationKit:
+
+    def __init__(self, signer: Signer, treasure_map: TreasureMap, issued_at: int):
+        ...
+
+    @property
+    def hrac(self) -> HRAC:
+        ...
+
+    @property
+    def revocation_orders(self) -> Dict[Address, RevocationOrder]:
+        ...
+
+    @staticmethod
+    def from_bytes(data: bytes) -> RevocationKit:
+        ...
+
+    def __bytes__(self) -> bytes:
+        ...
+
+
+class PolicyGrant:
+
+    def __init__(
+        self,
+        signer: Signer,
+        delegating_sk: SecretKey,
+        bob_encrypting_key: PublicKey,
+        bob_verifying_key: PublicKey,
+        label: bytes,
+        threshold: int,
+        ursulas: Sequence[NodeMetadata],
+        validity: PolicyValidity,
+        now: int,
+    ):
+        ...
+
+    @property
+    def hrac(self) -> HRAC:
+        ...
+
+    @property
+    def policy_encrypting_key(self) -> PublicKey:
+        ...
+
+    @property
+    def encrypted_treasure_map(self) -> EncryptedTreasureMap:
+        ...
+
+    @property
+    def revocation_kit(self) -> RevocationKit:
+        ...
+
+
+class EncryptingKeyRotation:
+
+    def __init__(
+        self,
+        signer: Signer,
+        staking_provider_address: Address,
+        old_encrypting_key: PublicKey,
+        new_encrypting_key: PublicKey,
+        timestamp_epoch: int,
+    ):
+        ...
+
+    def verify(self, verifying_key: PublicKey) -> None:
+        ...
+
+    staking_provider_address: Address
+
+    old_encrypting_key: PublicKey
+
+    new_encrypting_key: PublicKey
+
+    timestamp_epoch: int
+
+    @staticmethod
+    def from_bytes(data: bytes) -> EncryptingKeyRotation:
+        ...
+
+    def __bytes__(self) -> bytes:
+        ...
+
+
 class NodeMetadataPayload:
 
     def __init__(
@@ -6,19 +6,63 @@
 extern crate alloc;
 
 use alloc::collections::{BTreeMap, BTreeSet};
+use std::error::Error as _;
 
 use pyo3::class::basic::CompareOp;
-use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
 use pyo3::pyclass::PyClass;
 use pyo3::types::{PyBytes, PyUnicode};
 
 use nucypher_core::ProtocolObject;
 use umbral_pre::bindings_python::{
-    Capsule, PublicKey, RecoverableSignature, SecretKey, Signer, VerificationError,
-    VerifiedCapsuleFrag, VerifiedKeyFrag,
+    Capsule, PublicKey, RecoverableSignature, SecretKey, Signer, VerifiedCapsuleFrag,
+    VerifiedKeyFrag,
 };
 
+// The expansion of `create_exception!` checks a `cfg` unknown to newer compilers.
+#[allow(unknown_lints, unexpected_cfgs)]
+mod exceptions {
+    use pyo3::create_exception;
+    use pyo3::exceptions::PyValueError;
+
+    create_exception!(
+        _nucypher_core,
+        NucypherCoreError,
+        PyValueError,
+        "An error raised by `nucypher_core`, with a stable `code` and `code_name`."
+    );
+}
+
+use exceptions::NucypherCoreError;
+
+fn with_code(err: PyErr, code: nucypher_core::ErrorCode) -> PyErr {
+    Python::with_gil(|py| {
+        let value = err.value(py);
+        // Setting an attribute on a freshly created exception object cannot fail,
+        // but if it somehow does, the original error is still more useful to the caller.
+        let _ = value.setattr("code", code.number());
+        let _ = value.setattr("code_name", code.name());
+    });
+    err
+}
+
+/// Joins the messages of the error and all its sources.
+fn error_message(err: &nucypher_core::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        message = format!("{}: {}", message, err);
+        source = err.source();
+    }
+    message
+}
+
+fn map_core_err(err: impl Into<nucypher_core::Error>) -> PyErr {
+    let err = err.into();
+    with_code(NucypherCoreError::new_err(error_message(&err)), err.code())
+}
+
 fn to_bytes<'a, T, U>(obj: &T) -> PyObject
 where
     T: AsRef<U>,
@@ -37,9 +81,13 @@ where
     T: From<U>,
     U: ProtocolObject<'a>,
 {
-    U::from_bytes(data)
-        .map(T::from)
-        .map_err(|err| PyValueError::new_err(format!("Failed to deserialize: {}", err)))
+    U::from_bytes(data).map(T::from).map_err(|err| {
+        let code = err.code();
+        with_code(
+            NucypherCoreError::new_err(format!("Failed to deserialize: {}", err)),
+            code,
+        )
+    })
 }
 
 fn richcmp<T>(obj: &T, other: &T, op: CompareOp) -> PyResult<bool>
@@ -118,11 +166,211 @@ impl Conditions {
         }
     }
 
+    pub fn parse(&self) -> PyResult<ConditionLingo> {
+        self.backend
+            .parse()
+            .map(|backend| ConditionLingo { backend })
+            .map_err(map_core_err)
+    }
+
+    pub fn canonical(&self) -> PyResult<Conditions> {
+        self.backend
+            .canonical()
+            .map(|backend| Conditions { backend })
+            .map_err(map_core_err)
+    }
+
+    pub fn hash(&self, py: Python) -> PyResult<PyObject> {
+        let hash = self.backend.hash().map_err(map_core_err)?;
+        Ok(PyBytes::new(py, &hash).into())
+    }
+
     fn __str__(&self) -> &str {
         self.backend.as_ref()
     }
 }
 
+#[pyclass(module = "nucypher_core")]
+pub struct ConditionLingo {
+    backend: nucypher_core::ConditionLingo,
+}
+
+#[pymethods]
+impl ConditionLingo {
+    #[new]
+    pub fn new(condition: &Condition) -> Self {
+        Self {
+            backend: nucypher_core::ConditionLingo::new(condition.backend.clone()),
+        }
+    }
+
+    #[getter]
+    fn version(&self) -> &str {
+        &self.backend.version
+    }
+
+    #[getter]
+    fn condition(&self) -> Condition {
+        Condition {
+            backend: self.backend.condition.clone(),
+        }
+    }
+
+    pub fn validate(&self) -> PyResult<()> {
+        self.backend.validate().map_err(map_core_err)
+    }
+
+    pub fn to_conditions(&self) -> Conditions {
+        Conditions {
+            backend: nucypher_core::Conditions::from(&self.backend),
+        }
+    }
+
+    fn __str__(&self) -> String {
+        nucypher_core::Conditions::from(&self.backend)
+            .as_ref()
+            .into()
+    }
+}
+
+fn parse_comparator(comparator: &str) -> PyResult<nucypher_core::Comparator> {
+    comparator.parse().map_err(map_core_err)
+}
+
+fn parse_json(json: &str) -> PyResult<nucypher_core::serde_json::Value> {
+    nucypher_core::serde_json::from_str(json)
+        .map_err(|err| map_core_err(nucypher_core::ConditionsError::Malformed(err.to_string())))
+}
+
+#[pyclass(module = "nucypher_core")]
+#[derive(Clone)]
+pub struct Condition {
+    backend: nucypher_core::Condition,
+}
+
+#[pymethods]
+impl Condition {
+    #[staticmethod]
+    pub fn block_time(chain: u64, comparator: &str, timestamp: u64) -> PyResult<Self> {
+        Ok(Self {
+            backend: nucypher_core::Condition::block_time(
+                chain,
+                parse_comparator(comparator)?,
+                timestamp,
+            ),
+        })
+    }
+
+    #[staticmethod]
+    pub fn native_balance(chain: u64, comparator: &str, amount: u128) -> PyResult<Self> {
+        Ok(Self {
+            backend: nucypher_core::Condition::native_balance(
+                chain,
+                parse_comparator(comparator)?,
+                amount,
+            ),
+        })
+    }
+
+    #[staticmethod]
+    pub fn erc20_balance(
+        chain: u64,
+        contract_address: &str,
+        comparator: &str,
+        amount: u128,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            backend: nucypher_core::Condition::erc20_balance(
+                chain,
+                contract_address,
+                parse_comparator(comparator)?,
+                amount,
+            ),
+        })
+    }
+
+    #[staticmethod]
+    pub fn erc721_balance(
+        chain: u64,
+        contract_address: &str,
+        comparator: &str,
+        amount: u128,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            backend: nucypher_core::Condition::erc721_balance(
+                chain,
+                contract_address,
+                parse_comparator(comparator)?,
+                amount,
+            ),
+        })
+    }
+
+    #[staticmethod]
+    pub fn erc721_ownership(chain: u64, contract_address: &str, token_id: u128) -> Self {
+        Self {
+            backend: nucypher_core::Condition::erc721_ownership(chain, contract_address, token_id),
+        }
+    }
+
+    #[staticmethod]
+    pub fn contract_call(
+        chain: u64,
+        contract_address: &str,
+        function_abi: &str,
+        parameters: &str,
+        comparator: &str,
+        value: &str,
+    ) -> PyResult<Self> {
+        let parameters = match parse_json(parameters)? {
+            nucypher_core::serde_json::Value::Array(parameters) => parameters,
+            _ => {
+                return Err(map_core_err(nucypher_core::ConditionsError::Malformed(
+                    "parameters must be a JSON array".into(),
+                )))
+            }
+        };
+        Ok(Self {
+            backend: nucypher_core::Condition::contract_call(
+                chain,
+                contract_address,
+                parse_json(function_abi)?,
+                parameters,
+                nucypher_core::ReturnValueTest::new(
+                    parse_comparator(comparator)?,
+                    parse_json(value)?,
+                ),
+            ),
+        })
+    }
+
+    pub fn and_(&self, other: &Condition) -> Self {
+        Self {
+            backend: self.backend.clone().and(other.backend.clone()),
+        }
+    }
+
+    pub fn or_(&self, other: &Condition) -> Self {
+        Self {
+            backend: self.backend.clone().or(other.backend.clone()),
+        }
+    }
+
+    pub fn negate(&self) -> Self {
+        Self {
+            backend: self.backend.clone().negate(),
+        }
+    }
+
+    pub fn build(&self) -> PyResult<Conditions> {
+        self.backend
+            .clone()
+            .build()
+            .map(|backend| Conditions { backend })
+            .map_err(map_core_err)
+    }
+}
+
 #[pyclass(module = "nucypher_core")]
 pub struct Context {
     backend: nucypher_core::Context,
@@ -175,21 +423,21 @@ impl MessageKit {
         policy_encrypting_key: &PublicKey,
         plaintext: &[u8],
         conditions: Option<&Conditions>,
-    ) -> Self {
-        Self {
-            backend: nucypher_core::MessageKit::new(
-                policy_encrypting_key.as_ref(),
-                plaintext,
-                conditions.map(|conditions| &conditions.backend),
-            ),
-        }
+    ) -> PyResult<Self> {
+        let backend = nucypher_core::MessageKit::new(
+            policy_encrypting_key.as_ref(),
+            plaintext,
+            conditions.map(|conditions| &conditions.backend),
+        )
+        .map_err(map_core_err)?;
+        Ok(Self { backend })
     }
 
     pub fn decrypt(&self, py: Python, sk: &SecretKey) -> PyResult<PyObject> {
         let plaintext = self
             .backend
             .decrypt(sk.as_ref())
-            .map_err(|err| PyValueError::new_err(format!("{}", err)))?;
+            .map_err(|err| map_core_err(nucypher_core::DecryptionError::DecryptionFailed(err)))?;
         Ok(PyBytes::new(py, &plaintext).into())
     }
 
@@ -205,7 +453,7 @@ impl MessageKit {
         let plaintext = self
             .backend
             .decrypt_reencrypted(sk.as_ref(), policy_encrypting_key.as_ref(), backend_vcfrags)
-            .map_err(|err| PyValueError::new_err(format!("{}", err)))?;
+            .map_err(|err| map_core_err(nucypher_core::DecryptionError::from(err)))?;
         Ok(PyBytes::new(py, &plaintext).into())
     }
 
@@ -281,6 +529,36 @@ impl HRAC {
 // EncryptedKeyFrag
 //
 
+#[pyclass(module = "nucypher_core")]
+#[derive(Clone, Copy)]
+pub struct PolicyValidity {
+    backend: nucypher_core::PolicyValidity,
+}
+
+#[pymethods]
+impl PolicyValidity {
+    #[new]
+    pub fn new(expiration: u64, not_before: Option<u64>) -> Self {
+        Self {
+            backend: nucypher_core::PolicyValidity::new(expiration, not_before),
+        }
+    }
+
+    #[getter]
+    fn expiration(&self) -> u64 {
+        self.backend.expiration
+    }
+
+    #[getter]
+    fn not_before(&self) -> Option<u64> {
+        self.backend.not_before
+    }
+
+    pub fn is_valid_at(&self, now: u64) -> bool {
+        self.backend.is_valid_at(now)
+    }
+}
+
 #[pyclass(module = "nucypher_core")]
 #[derive(derive_more::From, derive_more::AsRef)]
 pub struct EncryptedKeyFrag {
@@ -295,15 +573,21 @@ impl EncryptedKeyFrag {
         recipient_key: &PublicKey,
         hrac: &HRAC,
         verified_kfrag: &VerifiedKeyFrag,
-    ) -> Self {
-        Self {
-            backend: nucypher_core::EncryptedKeyFrag::new(
-                signer.as_ref(),
-                recipient_key.as_ref(),
-                &hrac.backend,
-                verified_kfrag.as_ref().clone(),
-            ),
-        }
+        delegating_key: &PublicKey,
+        receiving_key: &PublicKey,
+        validity: &PolicyValidity,
+    ) -> PyResult<Self> {
+        nucypher_core::EncryptedKeyFrag::new(
+            signer.as_ref(),
+            recipient_key.as_ref(),
+            &hrac.backend,
+            verified_kfrag.as_ref().clone(),
+            delegating_key.as_ref(),
+            receiving_key.as_ref(),
+            &validity.backend,
+        )
+        .map(|backend| Self { backend })
+        .map_err(map_core_err)
     }
 
     pub fn decrypt(
@@ -311,11 +595,41 @@ impl EncryptedKeyFrag {
         sk: &SecretKey,
         hrac: &HRAC,
         publisher_verifying_key: &PublicKey,
-    ) -> PyResult<VerifiedKeyFrag> {
+    ) -> PyResult<(VerifiedKeyFrag, PolicyValidity)> {
         self.backend
             .decrypt(sk.as_ref(), &hrac.backend, publisher_verifying_key.as_ref())
+            .map(|(verified_kfrag, validity)| {
+                (
+                    VerifiedKeyFrag::from(verified_kfrag),
+                    PolicyValidity { backend: validity },
+                )
+            })
+            .map_err(map_core_err)
+    }
+
+    pub fn rewrap(&self, sk: &SecretKey, new_recipient_key: &PublicKey) -> PyResult<Self> {
+        self.backend
+            .rewrap(sk.as_ref(), new_recipient_key.as_ref())
+            .map(|backend| Self { backend })
+            .map_err(map_core_err)
+    }
+
+    pub fn decrypt_at(
+        &self,
+        sk: &SecretKey,
+        hrac: &HRAC,
+        publisher_verifying_key: &PublicKey,
+        now: u64,
+    ) -> PyResult<VerifiedKeyFrag> {
+        self.backend
+            .decrypt_at(
+                sk.as_ref(),
+                &hrac.backend,
+                publisher_verifying_key.as_ref(),
+                now,
+            )
             .map(VerifiedKeyFrag::from)
-            .map_err(|err| PyValueError::new_err(format!("{}", err)))
+            .map_err(map_core_err)
     }
 
     #[staticmethod]
@@ -345,8 +659,10 @@ impl TreasureMap {
         signer: &Signer,
         hrac: &HRAC,
         policy_encrypting_key: &PublicKey,
+        receiving_key: &PublicKey,
         assigned_kfrags: BTreeMap<Address, (PublicKey, VerifiedKeyFrag)>,
         threshold: u8,
+        validity: &PolicyValidity,
     ) -> Self {
         let assigned_kfrags_backend = assigned_kfrags
             .into_iter()
@@ -357,28 +673,99 @@ impl TreasureMap {
                 signer.as_ref(),
                 &hrac.backend,
                 policy_encrypting_key.as_ref(),
+                receiving_key.as_ref(),
                 assigned_kfrags_backend,
                 threshold,
+                &validity.backend,
             ),
         }
     }
 
-    pub fn encrypt(&self, signer: &Signer, recipient_key: &PublicKey) -> EncryptedTreasureMap {
-        EncryptedTreasureMap {
-            backend: self
-                .backend
-                .encrypt(signer.as_ref(), recipient_key.as_ref()),
-        }
+    pub fn encrypt(
+        &self,
+        signer: &Signer,
+        recipient_key: &PublicKey,
+    ) -> PyResult<EncryptedTreasureMap> {
+        self.backend
+            .encrypt(signer.as_ref(), recipient_key.as_ref())
+            .map(EncryptedTreasureMap::from)
+            .map_err(map_core_err)
+    }
+
+    pub fn encrypt_for_many(
+        &self,
+        signer: &Signer,
+        recipient_keys: Vec<PublicKey>,
+    ) -> PyResult<MultiRecipientEncryptedTreasureMap> {
+        let recipient_keys_backend = recipient_keys
+            .into_iter()
+            .map(umbral_pre::PublicKey::from)
+            .collect::<Vec<_>>();
+        self.backend
+            .encrypt_for_many(signer.as_ref(), &recipient_keys_backend)
+            .map(MultiRecipientEncryptedTreasureMap::from)
+            .map_err(map_core_err)
+    }
+
+    pub fn amend(
+        &self,
+        signer: &Signer,
+        receiving_key: &PublicKey,
+        replacements: BTreeMap<Address, (Address, PublicKey, VerifiedKeyFrag)>,
+        validity: &PolicyValidity,
+        issued_at: u64,
+    ) -> PyResult<(TreasureMap, Vec<RevocationOrder>)> {
+        let replacements_backend = replacements
+            .into_iter()
+            .map(|(removed, (address, key, vkfrag))| {
+                (
+                    removed.backend,
+                    (address.backend, key.into(), vkfrag.into()),
+                )
+            })
+            .collect::<Vec<_>>();
+        let amendment = self
+            .backend
+            .amend(
+                signer.as_ref(),
+                receiving_key.as_ref(),
+                replacements_backend,
+                &validity.backend,
+                issued_at,
+            )
+            .map_err(map_core_err)?;
+        Ok((
+            TreasureMap {
+                backend: amendment.treasure_map,
+            },
+            amendment
+                .revocation_orders
+                .into_iter()
+                .map(|backend| RevocationOrder { backend })
+                .collect(),
+        ))
     }
 
-    pub fn make_revocation_orders(&self, signer: &Signer) -> Vec<RevocationOrder> {
+    pub fn make_revocation_orders(&self, signer: &Signer, issued_at: u64) -> Vec<RevocationOrder> {
         self.backend
-            .make_revocation_orders(signer.as_ref())
+            .make_revocation_orders(signer.as_ref(), issued_at)
             .into_iter()
             .map(|backend| RevocationOrder { backend })
             .collect()
     }
 
+    pub fn make_revocation_certificate(
+        &self,
+        signer: &Signer,
+        issued_at: u64,
+    ) -> RevocationCertificate {
+        RevocationCertificate {
+            backend: self
+                .backend
+                .make_revocation_certificate(signer.as_ref(), issued_at),
+        }
+    }
+
     #[getter]
     fn destinations(&self) -> BTreeMap<Address, EncryptedKeyFrag> {
         let mut result = BTreeMap::new();
@@ -415,6 +802,11 @@ impl TreasureMap {
         self.backend.publisher_verifying_key.into()
     }
 
+    #[getter]
+    fn revision(&self) -> u32 {
+        self.backend.revision
+    }
+
     #[staticmethod]
     pub fn from_bytes(data: &[u8]) -> PyResult<Self> {
         from_bytes::<_, nucypher_core::TreasureMap>(data)
@@ -425,6 +817,61 @@ impl TreasureMap {
     }
 }
 
+//
+// TreasureMapBuilder
+//
+
+#[pyclass(module = "nucypher_core")]
+pub struct TreasureMapBuilder {
+    backend: nucypher_core::TreasureMapBuilder,
+}
+
+#[pymethods]
+impl TreasureMapBuilder {
+    #[new]
+    pub fn new(
+        hrac: &HRAC,
+        policy_encrypting_key: &PublicKey,
+        receiving_key: &PublicKey,
+        threshold: u8,
+        validity: &PolicyValidity,
+    ) -> Self {
+        Self {
+            backend: nucypher_core::TreasureMapBuilder::new(
+                &hrac.backend,
+                policy_encrypting_key.as_ref(),
+                receiving_key.as_ref(),
+                threshold,
+                &validity.backend,
+            ),
+        }
+    }
+
+    pub fn verify_kfrags(&mut self, verify: bool) {
+        self.backend.verify_kfrags(verify);
+    }
+
+    pub fn add_destination(
+        &mut self,
+        address: &Address,
+        ursula_encrypting_key: &PublicKey,
+        verified_kfrag: &VerifiedKeyFrag,
+    ) {
+        self.backend.add_destination(
+            &address.backend,
+            ursula_encrypting_key.as_ref(),
+            verified_kfrag.as_ref().clone(),
+        );
+    }
+
+    pub fn build(&self, signer: &Signer) -> PyResult<TreasureMap> {
+        self.backend
+            .build(signer.as_ref())
+            .map(TreasureMap::from)
+            .map_err(map_core_err)
+    }
+}
+
 //
 // EncryptedTreasureMap
 //
@@ -445,7 +892,7 @@ impl EncryptedTreasureMap {
         self.backend
             .decrypt(sk.as_ref(), publisher_verifying_key.as_ref())
             .map(TreasureMap::from)
-            .map_err(|err| PyValueError::new_err(format!("{}", err)))
+            .map_err(map_core_err)
     }
 
     #[staticmethod]
@@ -458,6 +905,44 @@ impl EncryptedTreasureMap {
     }
 }
 
+//
+// MultiRecipientEncryptedTreasureMap
+//
+
+#[pyclass(module = "nucypher_core")]
+#[derive(derive_more::From, derive_more::AsRef)]
+pub struct MultiRecipientEncryptedTreasureMap {
+    backend: nucypher_core::MultiRecipientEncryptedTreasureMap,
+}
+
+#[pymethods]
+impl MultiRecipientEncryptedTreasureMap {
+    pub fn decrypt(
+        &self,
+        sk: &SecretKey,
+        publisher_verifying_key: &PublicKey,
+    ) -> PyResult<TreasureMap> {
+        self.backend
+            .decrypt(sk.as_ref(), publisher_verifying_key.as_ref())
+            .map(TreasureMap::from)
+            .map_err(map_core_err)
+    }
+
+    #[getter]
+    fn recipients_count(&self) -> usize {
+        self.backend.recipients_count()
+    }
+
+    #[staticmethod]
+    pub fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        from_bytes::<_, nucypher_core::MultiRecipientEncryptedTreasureMap>(data)
+    }
+
+    fn __bytes__(&self) -> PyObject {
+        to_bytes(self)
+    }
+}
+
 //
 // ReencryptionRequest
 //
@@ -479,22 +964,22 @@ impl ReencryptionRequest {
         bob_verifying_key: &PublicKey,
         conditions: Option<&Conditions>,
         context: Option<&Context>,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let capsules_backend = capsules
             .into_iter()
             .map(umbral_pre::Capsule::from)
             .collect::<Vec<_>>();
-        Self {
-            backend: nucypher_core::ReencryptionRequest::new(
-                &capsules_backend,
-                &hrac.backend,
-                &encrypted_kfrag.backend,
-                publisher_verifying_key.as_ref(),
-                bob_verifying_key.as_ref(),
-                conditions.map(|conditions| &conditions.backend),
-                context.map(|context| &context.backend),
-            ),
-        }
+        let backend = nucypher_core::ReencryptionRequest::new(
+            &capsules_backend,
+            &hrac.backend,
+            &encrypted_kfrag.backend,
+            publisher_verifying_key.as_ref(),
+            bob_verifying_key.as_ref(),
+            conditions.map(|conditions| &conditions.backend),
+            context.map(|context| &context.backend),
+        )
+        .map_err(map_core_err)?;
+        Ok(Self { backend })
     }
 
     #[getter]
@@ -563,6 +1048,22 @@ impl ReencryptionRequest {
 // ReencryptionResponse
 //
 
+#[pyclass(module = "nucypher_core")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RefusalReason {
+    ConditionsNotSatisfied,
+    ConditionEvaluationFailed,
+}
+
+impl From<RefusalReason> for nucypher_core::RefusalReason {
+    fn from(reason: RefusalReason) -> Self {
+        match reason {
+            RefusalReason::ConditionsNotSatisfied => Self::ConditionsNotSatisfied,
+            RefusalReason::ConditionEvaluationFailed => Self::ConditionEvaluationFailed,
+        }
+    }
+}
+
 #[pyclass(module = "nucypher_core")]
 #[derive(derive_more::From, derive_more::AsRef)]
 pub struct ReencryptionResponse {
@@ -572,52 +1073,59 @@ pub struct ReencryptionResponse {
 #[pymethods]
 impl ReencryptionResponse {
     #[new]
-    pub fn new(signer: &Signer, capsules_and_vcfrags: Vec<(Capsule, VerifiedCapsuleFrag)>) -> Self {
-        let (capsules_backend, vcfrags_backend): (Vec<_>, Vec<_>) = capsules_and_vcfrags
+    pub fn new(
+        signer: &Signer,
+        request: &ReencryptionRequest,
+        results: Vec<&PyAny>,
+    ) -> PyResult<Self> {
+        let backend_results = results
             .into_iter()
-            .map(|(capsule, vcfrag)| {
-                (
-                    umbral_pre::Capsule::from(capsule),
-                    umbral_pre::VerifiedCapsuleFrag::from(vcfrag),
-                )
+            .map(|result| match result.extract::<RefusalReason>() {
+                Ok(reason) => Ok(Err(reason.into())),
+                Err(_) => result
+                    .extract::<VerifiedCapsuleFrag>()
+                    .map(|vcfrag| Ok(vcfrag.into())),
             })
-            .unzip();
-        ReencryptionResponse {
-            backend: nucypher_core::ReencryptionResponse::new(
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(ReencryptionResponse {
+            backend: nucypher_core::ReencryptionResponse::new_partial(
                 signer.as_ref(),
-                capsules_backend.iter().zip(vcfrags_backend.into_iter()),
-            ),
-        }
+                &request.backend,
+                backend_results,
+            )
+            .map_err(map_core_err)?,
+        })
     }
 
     pub fn verify(
         &self,
-        capsules: Vec<Capsule>,
+        py: Python<'_>,
+        request: &ReencryptionRequest,
         alice_verifying_key: &PublicKey,
         ursula_verifying_key: &PublicKey,
         policy_encrypting_key: &PublicKey,
         bob_encrypting_key: &PublicKey,
-    ) -> PyResult<Vec<VerifiedCapsuleFrag>> {
-        let capsules_backend = capsules
-            .into_iter()
-            .map(umbral_pre::Capsule::from)
-            .collect::<Vec<_>>();
-        let vcfrags_backend = self
+    ) -> PyResult<Vec<PyObject>> {
+        let results_backend = self
             .backend
             .clone()
             .verify(
-                &capsules_backend,
+                &request.backend,
                 alice_verifying_key.as_ref(),
                 ursula_verifying_key.as_ref(),
                 policy_encrypting_key.as_ref(),
                 bob_encrypting_key.as_ref(),
             )
-            .map_err(|_err| PyValueError::new_err("ReencryptionResponse verification failed"))?;
-        Ok(vcfrags_backend
-            .iter()
-            .cloned()
-            .map(VerifiedCapsuleFrag::from)
-            .collect::<Vec<_>>())
+            .map_err(map_core_err)?;
+        // Capsules for which no capsule frag was obtained are represented by the error objects.
+        Ok(results_backend
+            .into_vec()
+            .into_iter()
+            .map(|result| match result {
+                Ok(vcfrag) => VerifiedCapsuleFrag::from(vcfrag).into_py(py),
+                Err(err) => map_core_err(err).into_value(py).into_py(py),
+            })
+            .collect())
     }
 
     #[staticmethod]
@@ -654,18 +1162,18 @@ impl RetrievalKit {
         capsule: &Capsule,
         queried_addresses: BTreeSet<Address>,
         conditions: Option<&Conditions>,
-    ) -> Self {
+    ) -> PyResult<Self> {
         let addresses_backend = queried_addresses
             .iter()
             .map(|address| address.backend)
             .collect::<Vec<_>>();
-        Self {
-            backend: nucypher_core::RetrievalKit::new(
-                capsule.as_ref(),
-                addresses_backend,
-                conditions.map(|conditions| &conditions.backend),
-            ),
-        }
+        let backend = nucypher_core::RetrievalKit::new(
+            capsule.as_ref(),
+            addresses_backend,
+            conditions.map(|conditions| &conditions.backend),
+        )
+        .map_err(map_core_err)?;
+        Ok(Self { backend })
     }
 
     #[getter]
@@ -702,6 +1210,178 @@ impl RetrievalKit {
     }
 }
 
+//
+// RetrievalPlan
+//
+
+#[pyclass(module = "nucypher_core")]
+#[derive(derive_more::From, derive_more::AsRef)]
+pub struct RetrievalPlan {
+    backend: nucypher_core::RetrievalPlan,
+}
+
+#[pymethods]
+impl RetrievalPlan {
+    #[new]
+    pub fn new(
+        treasure_map: &TreasureMap,
+        retrieval_kits: Vec<PyRef<'_, RetrievalKit>>,
+        bob_encrypting_key: &PublicKey,
+        bob_verifying_key: &PublicKey,
+        context: Option<&Context>,
+    ) -> PyResult<Self> {
+        nucypher_core::RetrievalPlan::new(
+            &treasure_map.backend,
+            retrieval_kits.iter().map(|kit| &kit.backend),
+            bob_encrypting_key.as_ref(),
+            bob_verifying_key.as_ref(),
+            context.map(|context| &context.backend),
+        )
+        .map(Self::from)
+        .map_err(map_core_err)
+    }
+
+    pub fn next_requests(&mut self) -> Vec<(Address, ReencryptionRequest)> {
+        self.backend
+            .next_requests()
+            .into_iter()
+            .map(|(address, request)| {
+                (
+                    Address { backend: address },
+                    ReencryptionRequest { backend: request },
+                )
+            })
+            .collect()
+    }
+
+    pub fn add_response(
+        &mut self,
+        address: &Address,
+        ursula_verifying_key: &PublicKey,
+        response: &ReencryptionResponse,
+    ) -> PyResult<()> {
+        self.backend
+            .add_response(
+                &address.backend,
+                ursula_verifying_key.as_ref(),
+                response.backend.clone(),
+            )
+            .map_err(map_core_err)
+    }
+
+    pub fn report_failure(&mut self, address: &Address) {
+        self.backend.report_failure(&address.backend)
+    }
+
+    pub fn is_decryptable(&self, index: usize) -> bool {
+        self.backend.is_decryptable(index)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.backend.is_complete()
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.backend.is_exhausted()
+    }
+
+    pub fn verified_cfrags(&self, index: usize) -> Vec<VerifiedCapsuleFrag> {
+        self.backend
+            .verified_cfrags(index)
+            .into_iter()
+            .map(VerifiedCapsuleFrag::from)
+            .collect()
+    }
+}
+
+//
+// ReencryptionService
+//
+
+struct PyRevocationChecker(PyObject);
+
+impl nucypher_core::RevocationChecker for PyRevocationChecker {
+    fn is_revoked(
+        &self,
+        hrac: &nucypher_core::HRAC,
+        encrypted_kfrag: &nucypher_core::EncryptedKeyFrag,
+    ) -> Result<bool, String> {
+        Python::with_gil(|py| {
+            let args = (
+                HRAC { backend: *hrac },
+                EncryptedKeyFrag {
+                    backend: encrypted_kfrag.clone(),
+                },
+            );
+            self.0
+                .call1(py, args)
+                .and_then(|result| result.extract::<bool>(py))
+                .map_err(|err| err.to_string())
+        })
+    }
+}
+
+struct PyConditionEvaluator(PyObject);
+
+impl nucypher_core::ConditionEvaluator for PyConditionEvaluator {
+    fn evaluate(
+        &self,
+        conditions: &nucypher_core::Conditions,
+        context: Option<&nucypher_core::Context>,
+    ) -> Result<bool, String> {
+        Python::with_gil(|py| {
+            let args = (
+                Conditions {
+                    backend: conditions.clone(),
+                },
+                context.map(|context| Context {
+                    backend: context.clone(),
+                }),
+            );
+            self.0
+                .call1(py, args)
+                .and_then(|result| result.extract::<bool>(py))
+                .map_err(|err| err.to_string())
+        })
+    }
+}
+
+#[pyclass(module = "nucypher_core")]
+pub struct ReencryptionService {
+    backend: nucypher_core::ReencryptionService<PyRevocationChecker, PyConditionEvaluator>,
+}
+
+#[pymethods]
+impl ReencryptionService {
+    #[new]
+    pub fn new(
+        signer: &Signer,
+        decrypting_key: &SecretKey,
+        revocation_checker: PyObject,
+        condition_evaluator: PyObject,
+    ) -> Self {
+        Self {
+            backend: nucypher_core::ReencryptionService::new(
+                signer.as_ref().clone(),
+                decrypting_key.as_ref().clone(),
+                PyRevocationChecker(revocation_checker),
+                PyConditionEvaluator(condition_evaluator),
+            ),
+        }
+    }
+
+    pub fn handle(
+        &self,
+        request: &ReencryptionRequest,
+        now: u64,
+    ) -> PyResult<ReencryptionResponse> {
+        self.backend
+            .handle(&request.backend, now)
+            .map(ReencryptionResponse::from)
+            .map_err(map_core_err)
+    }
+}
+
 //
 // RevocationOrder
 //
@@ -719,16 +1399,39 @@ impl RevocationOrder {
         signer: &Signer,
         staking_provider_address: &Address,
         encrypted_kfrag: &EncryptedKeyFrag,
+        hrac: &HRAC,
+        issued_at: u64,
+        reason: Option<u16>,
     ) -> Self {
         Self {
             backend: nucypher_core::RevocationOrder::new(
                 signer.as_ref(),
                 &staking_provider_address.backend,
                 &encrypted_kfrag.backend,
+                &hrac.backend,
+                issued_at,
+                reason,
             ),
         }
     }
 
+    #[getter]
+    fn hrac(&self) -> HRAC {
+        HRAC {
+            backend: self.backend.hrac(),
+        }
+    }
+
+    #[getter]
+    fn issued_at(&self) -> u64 {
+        self.backend.issued_at()
+    }
+
+    #[getter]
+    fn reason(&self) -> Option<u16> {
+        self.backend.reason()
+    }
+
     pub fn verify(&self, alice_verifying_key: &PublicKey) -> PyResult<(Address, EncryptedKeyFrag)> {
         self.backend
             .clone()
@@ -739,7 +1442,7 @@ impl RevocationOrder {
                     EncryptedKeyFrag { backend: ekfrag },
                 )
             })
-            .map_err(|_err| VerificationError::new_err("RevocationOrder verification failed"))
+            .map_err(map_core_err)
     }
 
     #[staticmethod]
@@ -752,6 +1455,418 @@ impl RevocationOrder {
     }
 }
 
+//
+// RevocationKit
+//
+
+#[pyclass(module = "nucypher_core")]
+#[derive(derive_more::From, derive_more::AsRef)]
+pub struct RevocationKit {
+    backend: nucypher_core::RevocationKit,
+}
+
+#[pymethods]
+impl RevocationKit {
+    #[new]
+    pub fn new(signer: &Signer, treasure_map: &TreasureMap, issued_at: u64) -> Self {
+        Self {
+            backend: nucypher_core::RevocationKit::new(
+                signer.as_ref(),
+                &treasure_map.backend,
+                issued_at,
+            ),
+        }
+    }
+
+    #[getter]
+    fn hrac(&self) -> HRAC {
+        HRAC {
+            backend: self.backend.hrac,
+        }
+    }
+
+    #[getter]
+    fn revocation_orders(&self) -> BTreeMap<Address, RevocationOrder> {
+        let mut result = BTreeMap::new();
+        for (address, order) in &self.backend.revocation_orders {
+            result.insert(
+                Address { backend: *address },
+                RevocationOrder {
+                    backend: order.clone(),
+                },
+            );
+        }
+        result
+    }
+
+    #[staticmethod]
+    pub fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        from_bytes::<_, nucypher_core::RevocationKit>(data)
+    }
+
+    fn __bytes__(&self) -> PyObject {
+        to_bytes(self)
+    }
+}
+
+//
+// PolicyGrant
+//
+
+#[pyclass(module = "nucypher_core")]
+#[derive(derive_more::From, derive_more::AsRef)]
+pub struct PolicyGrant {
+    backend: nucypher_core::PolicyGrant,
+}
+
+#[pymethods]
+impl PolicyGrant {
+    #[allow(clippy::too_many_arguments)]
+    #[new]
+    pub fn new(
+        signer: &Signer,
+        delegating_sk: &SecretKey,
+        bob_encrypting_key: &PublicKey,
+        bob_verifying_key: &PublicKey,
+        label: &[u8],
+        threshold: u8,
+        ursulas: Vec<NodeMetadata>,
+        validity: &PolicyValidity,
+        now: u64,
+    ) -> PyResult<Self> {
+        let ursulas_backend = ursulas
+            .into_iter()
+            .map(|node| node.backend)
+            .collect::<Vec<_>>();
+        nucypher_core::PolicyGrant::new(
+            signer.as_ref(),
+            delegating_sk.as_ref(),
+            bob_encrypting_key.as_ref(),
+            bob_verifying_key.as_ref(),
+            label,
+            threshold,
+            &ursulas_backend,
+            &validity.backend,
+            now,
+        )
+        .map(Self::from)
+        .map_err(map_core_err)
+    }
+
+    #[getter]
+    fn hrac(&self) -> HRAC {
+        HRAC {
+            backend: self.backend.hrac,
+        }
+    }
+
+    #[getter]
+    fn policy_encrypting_key(&self) -> PublicKey {
+        self.backend.policy_encrypting_key.into()
+    }
+
+    #[getter]
+    fn encrypted_treasure_map(&self) -> EncryptedTreasureMap {
+        EncryptedTreasureMap {
+            backend: self.backend.encrypted_treasure_map.clone(),
+        }
+    }
+
+    #[getter]
+    fn revocation_kit(&self) -> RevocationKit {
+        RevocationKit {
+            backend: self.backend.revocation_kit.clone(),
+        }
+    }
+}
+
+//
+// RevocationReceipt
+//
+
+#[pyclass(module = "nucypher_core")]
+#[derive(derive_more::From, derive_more::AsRef)]
+pub struct RevocationReceipt {
+    backend: nucypher_core::RevocationReceipt,
+}
+
+#[pymethods]
+impl RevocationReceipt {
+    #[new]
+    pub fn new(signer: &Signer, order: &RevocationOrder, acknowledged_at: u64) -> Self {
+        Self {
+            backend: nucypher_core::RevocationReceipt::new(
+                signer.as_ref(),
+                &order.backend,
+                acknowledged_at,
+            ),
+        }
+    }
+
+    pub fn verify(
+        &self,
+        ursula_verifying_key: &PublicKey,
+        order: &RevocationOrder,
+    ) -> PyResult<u64> {
+        self.backend
+            .clone()
+            .verify(ursula_verifying_key.as_ref(), &order.backend)
+            .map_err(map_core_err)
+    }
+
+    #[staticmethod]
+    pub fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        from_bytes::<_, nucypher_core::RevocationReceipt>(data)
+    }
+
+    fn __bytes__(&self) -> PyObject {
+        to_bytes(self)
+    }
+}
+
+//
+// RevocationCertificate
+//
+
+#[pyclass(module = "nucypher_core")]
+#[derive(derive_more::From, derive_more::AsRef)]
+pub struct RevocationCertificate {
+    backend: nucypher_core::RevocationCertificate,
+}
+
+#[pymethods]
+impl RevocationCertificate {
+    #[new]
+    pub fn new(
+        signer: &Signer,
+        hrac: &HRAC,
+        destinations: BTreeMap<Address, PyRef<'_, EncryptedKeyFrag>>,
+        issued_at: u64,
+    ) -> Self {
+        let destinations_backend = destinations
+            .into_iter()
+            .map(|(address, ekfrag)| (address.backend, ekfrag.backend.clone()))
+            .collect();
+        Self {
+            backend: nucypher_core::RevocationCertificate::new(
+                signer.as_ref(),
+                &hrac.backend,
+                &destinations_backend,
+                issued_at,
+            ),
+        }
+    }
+
+    #[getter]
+    fn hrac(&self) -> HRAC {
+        HRAC {
+            backend: self.backend.hrac(),
+        }
+    }
+
+    #[getter]
+    fn issued_at(&self) -> u64 {
+        self.backend.issued_at()
+    }
+
+    pub fn verify(
+        &self,
+        alice_verifying_key: &PublicKey,
+    ) -> PyResult<(HRAC, BTreeMap<Address, EncryptedKeyFrag>)> {
+        self.backend
+            .clone()
+            .verify(alice_verifying_key.as_ref())
+            .map(|(hrac, destinations)| {
+                (
+                    HRAC { backend: hrac },
+                    destinations
+                        .into_iter()
+                        .map(|(address, ekfrag)| {
+                            (
+                                Address { backend: address },
+                                EncryptedKeyFrag { backend: ekfrag },
+                            )
+                        })
+                        .collect(),
+                )
+            })
+            .map_err(map_core_err)
+    }
+
+    #[staticmethod]
+    pub fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        from_bytes::<_, nucypher_core::RevocationCertificate>(data)
+    }
+
+    fn __bytes__(&self) -> PyObject {
+        to_bytes(self)
+    }
+}
+
+//
+// RevocationRegistry
+//
+
+#[pyclass(module = "nucypher_core")]
+#[derive(Default, derive_more::From, derive_more::AsRef)]
+pub struct InMemoryRevocationRegistry {
+    backend: nucypher_core::InMemoryRevocationRegistry,
+}
+
+#[pymethods]
+impl InMemoryRevocationRegistry {
+    #[new]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[staticmethod]
+    pub fn from_snapshot(snapshot: &RevocationRegistrySnapshot) -> Self {
+        Self {
+            backend: snapshot.backend.clone().into(),
+        }
+    }
+
+    pub fn revoke(
+        &mut self,
+        order: &RevocationOrder,
+        publisher_verifying_key: &PublicKey,
+        staking_provider_address: &Address,
+        decrypting_key: &SecretKey,
+        now: u64,
+    ) -> PyResult<()> {
+        nucypher_core::revoke(
+            &mut self.backend,
+            &order.backend,
+            publisher_verifying_key.as_ref(),
+            &staking_provider_address.backend,
+            decrypting_key.as_ref(),
+            now,
+        )
+        .map_err(map_core_err)
+    }
+
+    pub fn revoke_policy(
+        &mut self,
+        certificate: &RevocationCertificate,
+        publisher_verifying_key: &PublicKey,
+        staking_provider_address: &Address,
+        decrypting_key: &SecretKey,
+        now: u64,
+    ) -> PyResult<()> {
+        nucypher_core::revoke_policy(
+            &mut self.backend,
+            &certificate.backend,
+            publisher_verifying_key.as_ref(),
+            &staking_provider_address.backend,
+            decrypting_key.as_ref(),
+            now,
+        )
+        .map_err(map_core_err)
+    }
+
+    pub fn is_revoked(&self, hrac: &HRAC, encrypted_kfrag: &EncryptedKeyFrag) -> bool {
+        nucypher_core::is_kfrag_revoked(&self.backend, &hrac.backend, &encrypted_kfrag.backend)
+    }
+
+    pub fn snapshot(&self) -> RevocationRegistrySnapshot {
+        RevocationRegistrySnapshot {
+            backend: self.backend.snapshot(),
+        }
+    }
+
+    fn __len__(&self) -> usize {
+        self.backend.len()
+    }
+}
+
+#[pyclass(module = "nucypher_core")]
+#[derive(derive_more::From, derive_more::AsRef)]
+pub struct RevocationRegistrySnapshot {
+    backend: nucypher_core::RevocationRegistrySnapshot,
+}
+
+#[pymethods]
+impl RevocationRegistrySnapshot {
+    #[staticmethod]
+    pub fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        from_bytes::<_, nucypher_core::RevocationRegistrySnapshot>(data)
+    }
+
+    fn __bytes__(&self) -> PyObject {
+        to_bytes(self)
+    }
+}
+
+//
+// EncryptingKeyRotation
+//
+
+#[pyclass(module = "nucypher_core")]
+#[derive(derive_more::From, derive_more::AsRef)]
+pub struct EncryptingKeyRotation {
+    backend: nucypher_core::EncryptingKeyRotation,
+}
+
+#[pymethods]
+impl EncryptingKeyRotation {
+    #[new]
+    pub fn new(
+        signer: &Signer,
+        staking_provider_address: &Address,
+        old_encrypting_key: &PublicKey,
+        new_encrypting_key: &PublicKey,
+        timestamp_epoch: u64,
+    ) -> Self {
+        Self {
+            backend: nucypher_core::EncryptingKeyRotation::new(
+                signer.as_ref(),
+                &staking_provider_address.backend,
+                old_encrypting_key.as_ref(),
+                new_encrypting_key.as_ref(),
+                timestamp_epoch,
+            ),
+        }
+    }
+
+    pub fn verify(&self, verifying_key: &PublicKey) -> PyResult<()> {
+        self.backend
+            .verify(verifying_key.as_ref())
+            .map_err(map_core_err)
+    }
+
+    #[getter]
+    fn staking_provider_address(&self) -> Address {
+        Address {
+            backend: self.backend.staking_provider_address,
+        }
+    }
+
+    #[getter]
+    fn old_encrypting_key(&self) -> PublicKey {
+        self.backend.old_encrypting_key.into()
+    }
+
+    #[getter]
+    fn new_encrypting_key(&self) -> PublicKey {
+        self.backend.new_encrypting_key.into()
+    }
+
+    #[getter]
+    fn timestamp_epoch(&self) -> u64 {
+        self.backend.timestamp_epoch
+    }
+
+    #[staticmethod]
+    pub fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        from_bytes::<_, nucypher_core::EncryptingKeyRotation>(data)
+    }
+
+    fn __bytes__(&self) -> PyObject {
+        to_bytes(self)
+    }
+}
+
 //
 // NodeMetadataPayload
 //
@@ -842,7 +1957,7 @@ impl NodeMetadataPayload {
         let address = self
             .backend
             .derive_operator_address()
-            .map_err(|err| PyValueError::new_err(format!("{}", err)))?;
+            .map_err(map_core_err)?;
         Ok(Python::with_gil(|py| -> PyObject {
             PyBytes::new(py, address.as_ref()).into()
         }))
@@ -1054,7 +2169,7 @@ impl MetadataResponse {
             .map(|backend_payload| MetadataResponsePayload {
                 backend: backend_payload,
             })
-            .map_err(|_err| VerificationError::new_err("MetadataResponse verification failed"))
+            .map_err(map_core_err)
     }
 
     #[staticmethod]
@@ -1072,22 +2187,38 @@ impl MetadataResponse {
 fn _nucypher_core(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<Address>()?;
     m.add_class::<Conditions>()?;
+    m.add_class::<ConditionLingo>()?;
+    m.add_class::<Condition>()?;
     m.add_class::<Context>()?;
     m.add_class::<MessageKit>()?;
     m.add_class::<HRAC>()?;
+    m.add_class::<PolicyValidity>()?;
     m.add_class::<EncryptedKeyFrag>()?;
     m.add_class::<TreasureMap>()?;
+    m.add_class::<TreasureMapBuilder>()?;
     m.add_class::<EncryptedTreasureMap>()?;
+    m.add_class::<MultiRecipientEncryptedTreasureMap>()?;
     m.add_class::<ReencryptionRequest>()?;
+    m.add_class::<RefusalReason>()?;
     m.add_class::<ReencryptionResponse>()?;
     m.add_class::<RetrievalKit>()?;
+    m.add_class::<RetrievalPlan>()?;
+    m.add_class::<ReencryptionService>()?;
     m.add_class::<RevocationOrder>()?;
+    m.add_class::<RevocationReceipt>()?;
+    m.add_class::<RevocationCertificate>()?;
+    m.add_class::<InMemoryRevocationRegistry>()?;
+    m.add_class::<RevocationRegistrySnapshot>()?;
+    m.add_class::<RevocationKit>()?;
+    m.add_class::<PolicyGrant>()?;
+    m.add_class::<EncryptingKeyRotation>()?;
     m.add_class::<NodeMetadata>()?;
     m.add_class::<NodeMetadataPayload>()?;
     m.add_class::<FleetStateChecksum>()?;
     m.add_class::<MetadataRequest>()?;
     m.add_class::<MetadataResponsePayload>()?;
     m.add_class::<MetadataResponse>()?;
+    m.add("NucypherCoreError", py.get_type::<NucypherCoreError>())?;
 
     let umbral_module = PyModule::new(py, "umbral")?;
 
@@ -1111,7 +2242,7 @@ fn _nucypher_core(py: Python, m: &PyModule) -> PyResult<()> {
     umbral_module.add(
         "VerificationError",
         py.get_type::<umbral_pre::bindings_python::VerificationError>(),
-    )?; // depends on what `reencryption_response.verify()` returns
+    )?; // raised by `KeyFrag.verify()` and `CapsuleFrag.verify()`
     m.add_submodule(umbral_module)?;
 
     Ok(())
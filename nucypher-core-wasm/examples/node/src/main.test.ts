@@ -4,7 +4,6 @@ import {
   Context,
   Capsule,
   VerifiedKeyFrag,
-  VerifiedCapsuleFrag,
   EncryptedKeyFrag,
   generateKFrags,
   HRAC,
@@ -13,11 +12,22 @@ import {
   reencrypt,
   ReencryptionRequest,
   ReencryptionResponse,
+  RefusalReason,
+  ReencryptionService,
   RevocationOrder,
+  RevocationReceipt,
+  RevocationCertificate,
+  InMemoryRevocationRegistry,
+  RevocationRegistrySnapshot,
   SecretKey,
   Signer,
   TreasureMap,
   NodeMetadataPayload,
+  PolicyValidity,
+  PolicyGrant,
+  RevocationKit,
+  RetrievalKit,
+  RetrievalPlan,
   NodeMetadata,
   MetadataRequest,
   MetadataResponse,
@@ -66,7 +76,15 @@ const makeTreasureMap = (publisherSk: SecretKey, recipientSk: SecretKey) => {
     [new Address(Buffer.from("00000000000000000003")),
       [SecretKey.random().publicKey(), vkfrags[2]]]];
 
-  return new TreasureMap(signer, hrac, recipientPk, assigned_kfrags, threshold);
+  return new TreasureMap(
+    signer,
+    hrac,
+    publisherSk.publicKey(),
+    recipientPk,
+    assigned_kfrags,
+    threshold,
+    new PolicyValidity(2000000000)
+  );
 };
 
 const makeKFrags = (delegatingSk: SecretKey, recipientSk: SecretKey) =>
@@ -80,9 +98,12 @@ const makeKFrags = (delegatingSk: SecretKey, recipientSk: SecretKey) =>
     false
   );
 
-const makeNodeMetadata = (sk: SecretKey) => {
+const makeNodeMetadata = (
+  sk: SecretKey,
+  address: string = "00000000000000000000"
+) => {
   const payload = new NodeMetadataPayload(
-    new Address(Buffer.from("00000000000000000000")),
+    new Address(Buffer.from(address)),
     "fake-domain",
     (Date.now() / 1000) | 0,
     sk.publicKey(),
@@ -168,6 +189,23 @@ describe("MessageKit", () => {
     const asBytes = messageKit.toBytes();
     expect(MessageKit.fromBytes(asBytes).toBytes()).toEqual(asBytes);
   });
+
+  it("reports error codes", () => {
+    const delegatingSk = SecretKey.random();
+    const delegatingPk = delegatingSk.publicKey();
+    const message = new Uint8Array(Buffer.from("Hello, world!"));
+    const asBytes = new MessageKit(delegatingPk, message, null).toBytes();
+    asBytes[0] ^= 1;
+
+    let error: any = null;
+    try {
+      MessageKit.fromBytes(asBytes);
+    } catch (e) {
+      error = e;
+    }
+    expect(error.code).toEqual(302);
+    expect(error.codeName).toEqual("DESERIALIZATION_INCORRECT_HEADER");
+  });
 });
 
 describe("HRAC", () => {
@@ -194,7 +232,10 @@ describe("EncryptedKeyFrag", () => {
       signer,
       recipientPk,
       hrac,
-      vkfrags[0]
+      vkfrags[0],
+      delegatingSk.publicKey(),
+      recipientPk,
+      new PolicyValidity(2000000000)
     );
     const asBytes = encryptedKeyFrag.toBytes();
 
@@ -231,6 +272,53 @@ describe("TreasureMap", () => {
 
     expect(decrypted.toBytes()).toEqual(treasureMap.toBytes());
   });
+
+  it("rejects mismatched publishers", () => {
+    const publisherSk = SecretKey.random();
+    const recipientSk = SecretKey.random();
+    const otherSigner = new Signer(SecretKey.random());
+    const treasureMap = makeTreasureMap(publisherSk, recipientSk);
+
+    expect(() =>
+      treasureMap.encrypt(otherSigner, recipientSk.publicKey())
+    ).toThrow();
+
+    const encryptedTreasureMap = treasureMap.encrypt(
+      new Signer(publisherSk),
+      recipientSk.publicKey()
+    );
+    let error: any = null;
+    try {
+      encryptedTreasureMap.decrypt(recipientSk, otherSigner.verifyingKey());
+    } catch (e) {
+      error = e;
+    }
+    expect(error.code).toEqual(208);
+    expect(error.codeName).toEqual("DECRYPTION_PUBLISHER_MISMATCH");
+  });
+
+  it("encrypts for many recipients", () => {
+    const publisherSk = SecretKey.random();
+    const signer = new Signer(publisherSk);
+
+    const recipientSks = [SecretKey.random(), SecretKey.random()];
+    const recipientPks = recipientSks.map((sk) => sk.publicKey());
+
+    const treasureMap = makeTreasureMap(publisherSk, recipientSks[0]);
+    const encryptedTreasureMap = treasureMap.encryptForMany(
+      signer,
+      recipientPks
+    );
+    expect(encryptedTreasureMap.recipientsCount).toEqual(2);
+
+    for (const recipientSk of recipientSks) {
+      const decrypted = encryptedTreasureMap.decrypt(
+        recipientSk,
+        publisherSk.publicKey()
+      );
+      expect(decrypted.toBytes()).toEqual(treasureMap.toBytes());
+    }
+  });
 });
 
 describe("RevocationOrder", () => {
@@ -251,16 +339,234 @@ describe("RevocationOrder", () => {
       signer,
       recipientPk,
       hrac,
-      vkfrags[0]
+      vkfrags[0],
+      delegatingSk.publicKey(),
+      recipientPk,
+      new PolicyValidity(2000000000)
     );
 
     const revocationOrder = new RevocationOrder(
       signer,
       ursulaAddress,
-      encryptedKeyFrag
+      encryptedKeyFrag,
+      hrac,
+      1000000000,
+      3
     );
     const asBytes = revocationOrder.toBytes();
     expect(RevocationOrder.fromBytes(asBytes).toBytes()).toEqual(asBytes);
+    expect(revocationOrder.hrac.toBytes()).toEqual(hrac.toBytes());
+    expect(revocationOrder.issuedAt).toEqual(1000000000);
+    expect(revocationOrder.reason).toEqual(3);
+  });
+
+  it("is acknowledged with a receipt", () => {
+    const delegatingSk = SecretKey.random();
+    const signer = new Signer(delegatingSk);
+    const recipientSk = SecretKey.random();
+    const hrac = makeHrac(delegatingSk, recipientSk);
+    const encryptedKeyFrag = new EncryptedKeyFrag(
+      signer,
+      recipientSk.publicKey(),
+      hrac,
+      makeKFrags(delegatingSk, recipientSk)[0],
+      delegatingSk.publicKey(),
+      recipientSk.publicKey(),
+      new PolicyValidity(2000000000)
+    );
+    const revocationOrder = new RevocationOrder(
+      signer,
+      new Address(Buffer.from("00000000000000000000")),
+      encryptedKeyFrag,
+      hrac,
+      1000000000
+    );
+
+    const ursulaSigner = new Signer(SecretKey.random());
+    const receipt = RevocationReceipt.fromBytes(
+      new RevocationReceipt(ursulaSigner, revocationOrder, 1000000100).toBytes()
+    );
+    expect(receipt.verify(ursulaSigner.verifyingKey(), revocationOrder)).toEqual(
+      1000000100
+    );
+    expect(() =>
+      receipt.verify(SecretKey.random().publicKey(), revocationOrder)
+    ).toThrow();
+  });
+});
+
+describe("RevocationRegistry", () => {
+  it("records verified revocation orders", () => {
+    const publisherSk = SecretKey.random();
+    const signer = new Signer(publisherSk);
+    const ursulaSk = SecretKey.random();
+    const recipientSk = SecretKey.random();
+    const hrac = makeHrac(publisherSk, recipientSk);
+    const encryptedKeyFrag = new EncryptedKeyFrag(
+      signer,
+      ursulaSk.publicKey(),
+      hrac,
+      makeKFrags(publisherSk, recipientSk)[0],
+      publisherSk.publicKey(),
+      recipientSk.publicKey(),
+      new PolicyValidity(2000000000)
+    );
+    const ursulaAddress = new Address(Buffer.from("00000000000000000001"));
+    const otherAddress = new Address(Buffer.from("00000000000000000002"));
+
+    const registry = new InMemoryRevocationRegistry();
+
+    let error: any = null;
+    try {
+      registry.revoke(
+        new RevocationOrder(
+          signer,
+          otherAddress,
+          encryptedKeyFrag,
+          hrac,
+          1000000000
+        ),
+        publisherSk.publicKey(),
+        ursulaAddress,
+        ursulaSk,
+        1000000000
+      );
+    } catch (e) {
+      error = e;
+    }
+    expect(error.codeName).toEqual("REVOCATION_WRONG_URSULA");
+
+    registry.revoke(
+      new RevocationOrder(
+        signer,
+        ursulaAddress,
+        encryptedKeyFrag,
+        hrac,
+        1000000000
+      ),
+      publisherSk.publicKey(),
+      ursulaAddress,
+      ursulaSk,
+      1000000000
+    );
+    expect(registry.isRevoked(hrac, encryptedKeyFrag)).toBe(true);
+    expect(registry.length).toEqual(1);
+
+    const snapshot = RevocationRegistrySnapshot.fromBytes(
+      registry.snapshot().toBytes()
+    );
+    expect(
+      InMemoryRevocationRegistry.fromSnapshot(snapshot).isRevoked(
+        hrac,
+        encryptedKeyFrag
+      )
+    ).toBe(true);
+  });
+
+  it("records verified revocation certificates", () => {
+    const publisherSk = SecretKey.random();
+    const signer = new Signer(publisherSk);
+    const ursulaSk = SecretKey.random();
+    const recipientSk = SecretKey.random();
+    const hrac = makeHrac(publisherSk, recipientSk);
+    const encryptedKeyFrag = new EncryptedKeyFrag(
+      signer,
+      ursulaSk.publicKey(),
+      hrac,
+      makeKFrags(publisherSk, recipientSk)[0],
+      publisherSk.publicKey(),
+      recipientSk.publicKey(),
+      new PolicyValidity(2000000000)
+    );
+    const ursulaAddress = new Address(Buffer.from("00000000000000000001"));
+
+    const certificate = RevocationCertificate.fromBytes(
+      new RevocationCertificate(
+        signer,
+        hrac,
+        [[ursulaAddress, encryptedKeyFrag]],
+        1000000000
+      ).toBytes()
+    );
+    expect(certificate.issuedAt).toEqual(1000000000);
+    const [verifiedHrac, destinations] = certificate.verify(
+      publisherSk.publicKey()
+    );
+    expect(verifiedHrac.toBytes()).toEqual(hrac.toBytes());
+    expect(destinations.length).toEqual(1);
+    expect(() => certificate.verify(SecretKey.random().publicKey())).toThrow();
+
+    const registry = new InMemoryRevocationRegistry();
+    registry.revokePolicy(
+      certificate,
+      publisherSk.publicKey(),
+      ursulaAddress,
+      ursulaSk,
+      1000000000
+    );
+    expect(registry.isRevoked(hrac, encryptedKeyFrag)).toBe(true);
+  });
+});
+
+describe("PolicyGrant", () => {
+  it("grants a policy", () => {
+    const publisherSk = SecretKey.random();
+    const delegatingSk = SecretKey.random();
+    const bobSk = SecretKey.random();
+    const ursulas = ["1", "2", "3"].map((index) =>
+      makeNodeMetadata(SecretKey.random(), index.padStart(20, "0"))
+    );
+
+    const grant = new PolicyGrant(
+      new Signer(publisherSk),
+      delegatingSk,
+      bobSk.publicKey(),
+      SecretKey.random().publicKey(),
+      Buffer.from("label"),
+      2,
+      ursulas,
+      new PolicyValidity(2000000000),
+      1000000000
+    );
+
+    const treasureMap = grant.encryptedTreasureMap.decrypt(
+      bobSk,
+      publisherSk.publicKey()
+    );
+    expect(treasureMap.destinations.length).toEqual(3);
+    expect(treasureMap.hrac.toBytes()).toEqual(grant.hrac.toBytes());
+
+    const revocationKit = grant.revocationKit;
+    expect(revocationKit.revocationOrders.length).toEqual(3);
+    const asBytes = revocationKit.toBytes();
+    expect(asBytes).toEqual(RevocationKit.fromBytes(asBytes).toBytes());
+  });
+});
+
+describe("RetrievalPlan", () => {
+  it("plans requests", () => {
+    const publisherSk = SecretKey.random();
+    const recipientSk = SecretKey.random();
+    const treasureMap = makeTreasureMap(publisherSk, recipientSk);
+    const message = new Uint8Array(Buffer.from("Hello, world!"));
+    const messageKit = new MessageKit(publisherSk.publicKey(), message, null);
+
+    const plan = new RetrievalPlan(
+      treasureMap,
+      [RetrievalKit.fromMessageKit(messageKit)],
+      recipientSk.publicKey(),
+      SecretKey.random().publicKey(),
+      null
+    );
+
+    const requests = plan.nextRequests();
+    expect(requests.length).toEqual(2);
+    expect(plan.nextRequests().length).toEqual(0);
+
+    plan.reportFailure(requests[0][0]);
+    expect(plan.nextRequests().length).toEqual(1);
+    expect(plan.isDecryptable(0)).toBeFalsy();
+    expect(plan.isComplete()).toBeFalsy();
   });
 });
 
@@ -283,7 +589,10 @@ describe("ReencryptionRequest", () => {
       signer,
       recipientPk,
       hrac,
-      vkfrags[0]
+      vkfrags[0],
+      delegatingSk.publicKey(),
+      recipientPk,
+      new PolicyValidity(2000000000)
     );
 
     const reencryptionRequest = new ReencryptionRequest(
@@ -332,28 +641,146 @@ describe("ReencryptionResponse", () => {
     // Perform the reencryption
     const vcfrags = vkfrags.map((vkfrag) => reencrypt(messageKit.capsule, vkfrag));
 
+    // Make the request the response is for
+    const capsules = vkfrags.map((_) => messageKit.capsule);
+    const encryptedKeyFrag = new EncryptedKeyFrag(
+      new Signer(aliceSk),
+      bobSk.publicKey(),
+      makeHrac(aliceSk, bobSk),
+      vkfrags[0],
+      policyEncryptingKey,
+      bobSk.publicKey(),
+      new PolicyValidity(2000000000)
+    );
+    const makeRequest = (conditions: Conditions | null) =>
+      new ReencryptionRequest(
+        capsules,
+        makeHrac(aliceSk, bobSk),
+        encryptedKeyFrag,
+        aliceSk.publicKey(),
+        bobSk.publicKey(),
+        conditions,
+        null
+      );
+    const request = makeRequest(null);
+
     // Make the reencryption response
     const ursulaSk = SecretKey.random();
-    const capsules_and_vcfrags: [Capsule, VerifiedCapsuleFrag][] =
-      vcfrags.map((vcfrag) => [messageKit.capsule, vcfrag]);
     const reencryptionResponse = new ReencryptionResponse(
-      new Signer(ursulaSk), capsules_and_vcfrags
+      new Signer(ursulaSk), request, vcfrags
     );
 
     // Test serialization
     const asBytes = reencryptionResponse.toBytes();
     expect(ReencryptionResponse.fromBytes(asBytes).toBytes()).toEqual(asBytes);
 
+    // The response is bound to the request it was made for
+    expect(() =>
+      reencryptionResponse.verify(
+        makeRequest(new Conditions("other conditions")),
+        aliceSk.publicKey(),
+        ursulaSk.publicKey(),
+        policyEncryptingKey,
+        bobSk.publicKey()
+      )
+    ).toThrow();
+
     // Verify the reencryption response
-    const capsules = vkfrags.map((_) => messageKit.capsule);
     const verified = reencryptionResponse.verify(
-      capsules,
+      request,
       aliceSk.publicKey(),
       ursulaSk.publicKey(),
       policyEncryptingKey,
       bobSk.publicKey()
     );
     expect(verified.length).toEqual(vkfrags.length);
+
+    // A response may refuse to reencrypt some of the capsules
+    const partialResponse = new ReencryptionResponse(
+      new Signer(ursulaSk),
+      request,
+      [RefusalReason.ConditionsNotSatisfied, ...vcfrags.slice(1)]
+    );
+    const results = partialResponse.verify(
+      request,
+      aliceSk.publicKey(),
+      ursulaSk.publicKey(),
+      policyEncryptingKey,
+      bobSk.publicKey()
+    );
+    expect(results.length).toEqual(vkfrags.length);
+    const refusal: any = results[0];
+    expect(refusal).toBeInstanceOf(Error);
+    expect(refusal.code).toEqual(1401);
+    expect(refusal.codeName).toEqual("CAPSULE_FRAG_CONDITIONS_NOT_SATISFIED");
+    expect(results.slice(1).every((result) => !(result instanceof Error))).toBeTruthy();
+  });
+});
+
+describe("ReencryptionService", () => {
+  it("reencrypts valid requests and rejects the rest", () => {
+    const delegatingSk = SecretKey.random();
+    const delegatingPk = delegatingSk.publicKey();
+    const recipientSk = SecretKey.random();
+    const ursulaSk = SecretKey.random();
+    const ursulaSigner = new Signer(SecretKey.random());
+
+    const capsules = makeCapsules(delegatingPk);
+    const hrac = makeHrac(delegatingSk, recipientSk);
+    const encryptedKeyFrag = new EncryptedKeyFrag(
+      new Signer(delegatingSk),
+      ursulaSk.publicKey(),
+      hrac,
+      makeKFrags(delegatingSk, recipientSk)[0],
+      delegatingPk,
+      recipientSk.publicKey(),
+      new PolicyValidity(2000000000)
+    );
+    const makeRequest = (conditions: string) =>
+      new ReencryptionRequest(
+        capsules,
+        hrac,
+        encryptedKeyFrag,
+        delegatingPk,
+        recipientSk.publicKey(),
+        new Conditions(conditions),
+        null
+      );
+
+    const revoked: Uint8Array[] = [];
+    const service = new ReencryptionService(
+      ursulaSigner,
+      ursulaSk,
+      (_hrac, ekfrag) =>
+        revoked.some((bytes) => Buffer.from(bytes).equals(ekfrag.toBytes())),
+      (conditions, _context) => conditions.toString() === "allow"
+    );
+
+    const response = service.handle(makeRequest("allow"), 1000000000);
+    const verified = response.verify(
+      capsules,
+      delegatingPk,
+      ursulaSigner.verifyingKey(),
+      delegatingPk,
+      recipientSk.publicKey()
+    );
+    expect(verified.length).toEqual(capsules.length);
+
+    let error: any = null;
+    try {
+      service.handle(makeRequest("deny"), 1000000000);
+    } catch (e) {
+      error = e;
+    }
+    expect(error.codeName).toEqual("REENCRYPTION_CONDITIONS_NOT_SATISFIED");
+
+    revoked.push(encryptedKeyFrag.toBytes());
+    try {
+      service.handle(makeRequest("allow"), 1000000000);
+    } catch (e) {
+      error = e;
+    }
+    expect(error.codeName).toEqual("REENCRYPTION_REVOKED");
   });
 });
 
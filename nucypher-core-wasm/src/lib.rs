@@ -3,14 +3,18 @@
 #![allow(clippy::unused_unit)]
 
 extern crate alloc;
+// Only for `std::error::Error`, to walk the chain of error sources.
+extern crate std;
 
 use alloc::{
     boxed::Box,
+    collections::BTreeMap,
     format,
     string::{String, ToString},
     vec::Vec,
 };
 use core::fmt;
+use std::error::Error as _;
 
 use js_sys::Error;
 use umbral_pre::bindings_wasm::{
@@ -27,6 +31,30 @@ fn map_js_err<T: fmt::Display>(err: T) -> Error {
     Error::new(&format!("{}", err))
 }
 
+/// Attaches the stable error code to the error object as `code` and `codeName` properties.
+fn with_code(err: Error, code: nucypher_core::ErrorCode) -> Error {
+    // Setting a property on a plain `Error` object cannot fail.
+    let _ = js_sys::Reflect::set(err.as_ref(), &"code".into(), &code.number().into());
+    let _ = js_sys::Reflect::set(err.as_ref(), &"codeName".into(), &code.name().into());
+    err
+}
+
+/// Joins the messages of the error and all its sources.
+fn error_message(err: &nucypher_core::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        message = format!("{}: {}", message, err);
+        source = err.source();
+    }
+    message
+}
+
+fn map_core_err<T: Into<nucypher_core::Error>>(err: T) -> Error {
+    let err = err.into();
+    with_code(Error::new(&error_message(&err)), err.code())
+}
+
 fn to_bytes<'a, T, U>(obj: &T) -> Box<[u8]>
 where
     T: AsRef<U>,
@@ -44,7 +72,7 @@ where
     T: From<U>,
     U: ProtocolObject<'a>,
 {
-    U::from_bytes(data).map(T::from).map_err(map_js_err)
+    U::from_bytes(data).map(T::from).map_err(map_core_err)
 }
 
 /// Tries to convert an optional value (either `null` or a `#[wasm_bindgen]` marked structure)
@@ -105,8 +133,11 @@ extern "C" {
     #[wasm_bindgen(typescript_type = "VerifiedCapsuleFrag[]")]
     pub type VerifiedCapsuleFragArray;
 
-    #[wasm_bindgen(typescript_type = "[Capsule, VerifiedCapsuleFrag][]")]
-    pub type CapsuleAndVerifiedCapsuleFragArray;
+    #[wasm_bindgen(typescript_type = "(VerifiedCapsuleFrag | RefusalReason)[]")]
+    pub type CapsuleResultArray;
+
+    #[wasm_bindgen(typescript_type = "(VerifiedCapsuleFrag | Error)[]")]
+    pub type VerifiedCapsuleResultArray;
 
     #[wasm_bindgen(typescript_type = "Conditions | null")]
     pub type OptionConditions;
@@ -126,6 +157,9 @@ extern "C" {
     #[wasm_bindgen(typescript_type = "Address[]")]
     pub type AddressArray;
 
+    #[wasm_bindgen(typescript_type = "PublicKey[]")]
+    pub type PublicKeyArray;
+
     #[wasm_bindgen(typescript_type = "NodeMetadata[]")]
     pub type NodeMetadataArray;
 
@@ -140,6 +174,39 @@ extern "C" {
 
     #[wasm_bindgen(typescript_type = "[Address, EncryptedKeyFrag]")]
     pub type VerifiedRevocationOrder;
+
+    #[wasm_bindgen(typescript_type = "[HRAC, [Address, EncryptedKeyFrag][]]")]
+    pub type VerifiedRevocationCertificate;
+
+    #[wasm_bindgen(typescript_type = "[VerifiedKeyFrag, PolicyValidity]")]
+    pub type DecryptedKeyFrag;
+
+    #[wasm_bindgen(typescript_type = "[Address, [Address, PublicKey, VerifiedKeyFrag]][]")]
+    pub type ReplacementsArray;
+
+    #[wasm_bindgen(typescript_type = "[TreasureMap, RevocationOrder[]]")]
+    pub type TreasureMapAmendment;
+
+    #[wasm_bindgen(typescript_type = "[Address, RevocationOrder][]")]
+    pub type RevocationOrdersArray;
+
+    #[wasm_bindgen(typescript_type = "RetrievalKit[]")]
+    pub type RetrievalKitArray;
+
+    #[wasm_bindgen(typescript_type = "[Address, ReencryptionRequest][]")]
+    pub type AddressedReencryptionRequestArray;
+
+    #[wasm_bindgen(
+        extends = js_sys::Function,
+        typescript_type = "(hrac: HRAC, encryptedKfrag: EncryptedKeyFrag) => boolean"
+    )]
+    pub type RevocationCheckerFunction;
+
+    #[wasm_bindgen(
+        extends = js_sys::Function,
+        typescript_type = "(conditions: Conditions, context: Context | null) => boolean"
+    )]
+    pub type ConditionEvaluatorFunction;
 }
 
 //
@@ -163,6 +230,21 @@ impl Conditions {
         Self(nucypher_core::Conditions::from(data_owned))
     }
 
+    pub fn parse(&self) -> Result<ConditionLingo, Error> {
+        self.0.parse().map(ConditionLingo).map_err(map_core_err)
+    }
+
+    pub fn canonical(&self) -> Result<Conditions, Error> {
+        self.0.canonical().map(Conditions).map_err(map_core_err)
+    }
+
+    pub fn hash(&self) -> Result<Box<[u8]>, Error> {
+        self.0
+            .hash()
+            .map(|hash| hash.to_vec().into_boxed_slice())
+            .map_err(map_core_err)
+    }
+
     #[allow(clippy::inherent_to_string)]
     #[wasm_bindgen(js_name = toString)]
     pub fn to_string(&self) -> String {
@@ -170,6 +252,171 @@ impl Conditions {
     }
 }
 
+#[wasm_bindgen]
+pub struct ConditionLingo(nucypher_core::ConditionLingo);
+
+#[wasm_bindgen]
+impl ConditionLingo {
+    #[wasm_bindgen(constructor)]
+    pub fn new(condition: &Condition) -> Self {
+        Self(nucypher_core::ConditionLingo::new(condition.0.clone()))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn version(&self) -> String {
+        self.0.version.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn condition(&self) -> Condition {
+        Condition(self.0.condition.clone())
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        self.0.validate().map_err(map_core_err)
+    }
+
+    #[wasm_bindgen(js_name = toConditions)]
+    pub fn to_conditions(&self) -> Conditions {
+        Conditions(nucypher_core::Conditions::from(&self.0))
+    }
+
+    #[allow(clippy::inherent_to_string)]
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_string(&self) -> String {
+        nucypher_core::Conditions::from(&self.0).as_ref().into()
+    }
+}
+
+fn parse_comparator(comparator: &str) -> Result<nucypher_core::Comparator, Error> {
+    comparator.parse().map_err(map_core_err)
+}
+
+fn parse_json(json: &str) -> Result<nucypher_core::serde_json::Value, Error> {
+    nucypher_core::serde_json::from_str(json)
+        .map_err(|err| map_core_err(nucypher_core::ConditionsError::Malformed(err.to_string())))
+}
+
+// Amounts can exceed the safe integer range of JS numbers, so they are passed as decimal strings.
+fn parse_amount(amount: &str) -> Result<u128, Error> {
+    amount.parse().map_err(|err| {
+        map_core_err(nucypher_core::ConditionsError::Malformed(format!(
+            "invalid amount {:?}: {}",
+            amount, err
+        )))
+    })
+}
+
+#[derive(Clone)]
+#[wasm_bindgen]
+pub struct Condition(nucypher_core::Condition);
+
+#[wasm_bindgen]
+impl Condition {
+    #[wasm_bindgen(js_name = blockTime)]
+    pub fn block_time(chain: u32, comparator: &str, timestamp: u32) -> Result<Condition, Error> {
+        Ok(Self(nucypher_core::Condition::block_time(
+            chain.into(),
+            parse_comparator(comparator)?,
+            timestamp.into(),
+        )))
+    }
+
+    #[wasm_bindgen(js_name = nativeBalance)]
+    pub fn native_balance(chain: u32, comparator: &str, amount: &str) -> Result<Condition, Error> {
+        Ok(Self(nucypher_core::Condition::native_balance(
+            chain.into(),
+            parse_comparator(comparator)?,
+            parse_amount(amount)?,
+        )))
+    }
+
+    #[wasm_bindgen(js_name = erc20Balance)]
+    pub fn erc20_balance(
+        chain: u32,
+        contract_address: &str,
+        comparator: &str,
+        amount: &str,
+    ) -> Result<Condition, Error> {
+        Ok(Self(nucypher_core::Condition::erc20_balance(
+            chain.into(),
+            contract_address,
+            parse_comparator(comparator)?,
+            parse_amount(amount)?,
+        )))
+    }
+
+    #[wasm_bindgen(js_name = erc721Balance)]
+    pub fn erc721_balance(
+        chain: u32,
+        contract_address: &str,
+        comparator: &str,
+        amount: &str,
+    ) -> Result<Condition, Error> {
+        Ok(Self(nucypher_core::Condition::erc721_balance(
+            chain.into(),
+            contract_address,
+            parse_comparator(comparator)?,
+            parse_amount(amount)?,
+        )))
+    }
+
+    #[wasm_bindgen(js_name = erc721Ownership)]
+    pub fn erc721_ownership(
+        chain: u32,
+        contract_address: &str,
+        token_id: &str,
+    ) -> Result<Condition, Error> {
+        Ok(Self(nucypher_core::Condition::erc721_ownership(
+            chain.into(),
+            contract_address,
+            parse_amount(token_id)?,
+        )))
+    }
+
+    #[wasm_bindgen(js_name = contractCall)]
+    pub fn contract_call(
+        chain: u32,
+        contract_address: &str,
+        function_abi: &str,
+        parameters: &str,
+        comparator: &str,
+        value: &str,
+    ) -> Result<Condition, Error> {
+        let parameters = match parse_json(parameters)? {
+            nucypher_core::serde_json::Value::Array(parameters) => parameters,
+            _ => {
+                return Err(map_core_err(nucypher_core::ConditionsError::Malformed(
+                    "parameters must be a JSON array".into(),
+                )))
+            }
+        };
+        Ok(Self(nucypher_core::Condition::contract_call(
+            chain.into(),
+            contract_address,
+            parse_json(function_abi)?,
+            parameters,
+            nucypher_core::ReturnValueTest::new(parse_comparator(comparator)?, parse_json(value)?),
+        )))
+    }
+
+    pub fn and(&self, other: &Condition) -> Condition {
+        Self(self.0.clone().and(other.0.clone()))
+    }
+
+    pub fn or(&self, other: &Condition) -> Condition {
+        Self(self.0.clone().or(other.0.clone()))
+    }
+
+    pub fn negate(&self) -> Condition {
+        Self(self.0.clone().negate())
+    }
+
+    pub fn build(&self) -> Result<Conditions, Error> {
+        self.0.clone().build().map(Conditions).map_err(map_core_err)
+    }
+}
+
 #[derive(TryFromJsValue)]
 #[wasm_bindgen]
 #[derive(Clone)]
@@ -248,15 +495,19 @@ impl MessageKit {
         conditions: &OptionConditions,
     ) -> Result<MessageKit, Error> {
         let typed_conditions = try_from_js_option::<Conditions>(conditions)?;
-        Ok(MessageKit(nucypher_core::MessageKit::new(
+        nucypher_core::MessageKit::new(
             policy_encrypting_key.as_ref(),
             plaintext,
             typed_conditions.as_ref().map(|c| &c.0),
-        )))
+        )
+        .map(MessageKit)
+        .map_err(map_core_err)
     }
 
     pub fn decrypt(&self, sk: &SecretKey) -> Result<Box<[u8]>, Error> {
-        self.0.decrypt(sk.as_ref()).map_err(map_js_err)
+        self.0
+            .decrypt(sk.as_ref())
+            .map_err(|err| map_core_err(nucypher_core::DecryptionError::DecryptionFailed(err)))
     }
 
     #[wasm_bindgen(getter)]
@@ -295,7 +546,7 @@ impl MessageKit {
                     .into_iter()
                     .map(umbral_pre::VerifiedCapsuleFrag::from),
             )
-            .map_err(map_js_err)
+            .map_err(|err| map_core_err(nucypher_core::DecryptionError::from(err)))
     }
 }
 
@@ -340,6 +591,41 @@ impl HRAC {
     }
 }
 
+//
+// PolicyValidity
+//
+
+#[derive(Clone, Copy)]
+#[wasm_bindgen]
+pub struct PolicyValidity(nucypher_core::PolicyValidity);
+
+#[wasm_bindgen]
+impl PolicyValidity {
+    #[wasm_bindgen(constructor)]
+    pub fn new(expiration: u32, not_before: Option<u32>) -> Self {
+        Self(nucypher_core::PolicyValidity::new(
+            expiration.into(),
+            not_before.map(u64::from),
+        ))
+    }
+
+    // Timestamps are returned as JS numbers, which represent them exactly.
+    #[wasm_bindgen(getter)]
+    pub fn expiration(&self) -> f64 {
+        self.0.expiration as f64
+    }
+
+    #[wasm_bindgen(getter, js_name = notBefore)]
+    pub fn not_before(&self) -> Option<f64> {
+        self.0.not_before.map(|not_before| not_before as f64)
+    }
+
+    #[wasm_bindgen(js_name = isValidAt)]
+    pub fn is_valid_at(&self, now: u32) -> bool {
+        self.0.is_valid_at(now.into())
+    }
+}
+
 //
 // EncryptedKeyFrag
 //
@@ -357,13 +643,21 @@ impl EncryptedKeyFrag {
         recipient_key: &PublicKey,
         hrac: &HRAC,
         verified_kfrag: &VerifiedKeyFrag,
-    ) -> Self {
-        Self(nucypher_core::EncryptedKeyFrag::new(
+        delegating_key: &PublicKey,
+        receiving_key: &PublicKey,
+        validity: &PolicyValidity,
+    ) -> Result<EncryptedKeyFrag, Error> {
+        nucypher_core::EncryptedKeyFrag::new(
             signer.as_ref(),
             recipient_key.as_ref(),
             &hrac.0,
             verified_kfrag.as_ref().clone(),
-        ))
+            delegating_key.as_ref(),
+            receiving_key.as_ref(),
+            &validity.0,
+        )
+        .map(Self)
+        .map_err(map_core_err)
     }
 
     pub fn decrypt(
@@ -371,10 +665,44 @@ impl EncryptedKeyFrag {
         sk: &SecretKey,
         hrac: &HRAC,
         publisher_verifying_key: &PublicKey,
+    ) -> Result<DecryptedKeyFrag, Error> {
+        let (verified_kfrag, validity) = self
+            .0
+            .decrypt(sk.as_ref(), &hrac.0, publisher_verifying_key.as_ref())
+            .map_err(map_core_err)?;
+        Ok(into_js_array([
+            JsValue::from(VerifiedKeyFrag::from(verified_kfrag)),
+            JsValue::from(PolicyValidity(validity)),
+        ]))
+    }
+
+    pub fn rewrap(
+        &self,
+        sk: &SecretKey,
+        new_recipient_key: &PublicKey,
+    ) -> Result<EncryptedKeyFrag, Error> {
+        self.0
+            .rewrap(sk.as_ref(), new_recipient_key.as_ref())
+            .map(Self)
+            .map_err(map_core_err)
+    }
+
+    #[wasm_bindgen(js_name = decryptAt)]
+    pub fn decrypt_at(
+        &self,
+        sk: &SecretKey,
+        hrac: &HRAC,
+        publisher_verifying_key: &PublicKey,
+        now: u32,
     ) -> Result<VerifiedKeyFrag, Error> {
         self.0
-            .decrypt(sk.as_ref(), &hrac.0, publisher_verifying_key.as_ref())
-            .map_err(map_js_err)
+            .decrypt_at(
+                sk.as_ref(),
+                &hrac.0,
+                publisher_verifying_key.as_ref(),
+                now.into(),
+            )
+            .map_err(map_core_err)
             .map(VerifiedKeyFrag::from)
     }
 
@@ -393,6 +721,7 @@ impl EncryptedKeyFrag {
 // TreasureMap
 //
 
+#[derive(TryFromJsValue)]
 #[wasm_bindgen]
 #[derive(Clone, PartialEq, Debug, derive_more::From, derive_more::AsRef)]
 pub struct TreasureMap(nucypher_core::TreasureMap);
@@ -404,8 +733,10 @@ impl TreasureMap {
         signer: &Signer,
         hrac: &HRAC,
         policy_encrypting_key: &PublicKey,
+        receiving_key: &PublicKey,
         assigned_kfrags: &AssignedKeyFragsArray,
         threshold: u8,
+        validity: &PolicyValidity,
     ) -> Result<TreasureMap, Error> {
         let js_kfrags: &JsValue = assigned_kfrags.as_ref();
         let kfrags_array: &js_sys::Array = js_kfrags
@@ -442,13 +773,39 @@ impl TreasureMap {
             signer.as_ref(),
             &hrac.0,
             policy_encrypting_key.as_ref(),
+            receiving_key.as_ref(),
             typed_assigned_kfrags,
             threshold,
+            &validity.0,
         )))
     }
 
-    pub fn encrypt(&self, signer: &Signer, recipient_key: &PublicKey) -> EncryptedTreasureMap {
-        EncryptedTreasureMap(self.0.encrypt(signer.as_ref(), recipient_key.as_ref()))
+    pub fn encrypt(
+        &self,
+        signer: &Signer,
+        recipient_key: &PublicKey,
+    ) -> Result<EncryptedTreasureMap, Error> {
+        self.0
+            .encrypt(signer.as_ref(), recipient_key.as_ref())
+            .map(EncryptedTreasureMap)
+            .map_err(map_core_err)
+    }
+
+    #[wasm_bindgen(js_name = encryptForMany)]
+    pub fn encrypt_for_many(
+        &self,
+        signer: &Signer,
+        recipient_keys: &PublicKeyArray,
+    ) -> Result<MultiRecipientEncryptedTreasureMap, Error> {
+        let typed_recipient_keys = try_from_js_array::<PublicKey>(recipient_keys)?;
+        let backend_recipient_keys = typed_recipient_keys
+            .into_iter()
+            .map(umbral_pre::PublicKey::from)
+            .collect::<Vec<_>>();
+        self.0
+            .encrypt_for_many(signer.as_ref(), &backend_recipient_keys)
+            .map(MultiRecipientEncryptedTreasureMap)
+            .map_err(map_core_err)
     }
 
     #[wasm_bindgen(getter)]
@@ -463,16 +820,85 @@ impl TreasureMap {
         }))
     }
 
+    pub fn amend(
+        &self,
+        signer: &Signer,
+        receiving_key: &PublicKey,
+        replacements: &ReplacementsArray,
+        validity: &PolicyValidity,
+        issued_at: u32,
+    ) -> Result<TreasureMapAmendment, Error> {
+        let js_replacements: &JsValue = replacements.as_ref();
+        let replacements_array: &js_sys::Array = js_replacements
+            .dyn_ref()
+            .ok_or_else(|| Error::new("`replacements` must be an array"))?;
+
+        let mut typed_replacements = Vec::new();
+        for entry in replacements_array.iter() {
+            let key_value: js_sys::Array = entry.dyn_into()?;
+            if key_value.length() != 2 {
+                return Err(Error::new(
+                    "A tuple of an incorrect size received when iterating through map's entries",
+                ));
+            }
+            let value_tuple: js_sys::Array = key_value.get(1).dyn_into()?;
+            if value_tuple.length() != 3 {
+                return Err(Error::new(
+                    "A tuple of an incorrect size received when iterating through map's entries",
+                ));
+            }
+
+            let removed = Address::try_from(&key_value.get(0)).map_err(map_js_err)?;
+            let address = Address::try_from(&value_tuple.get(0)).map_err(map_js_err)?;
+            let pk = PublicKey::try_from(&value_tuple.get(1)).map_err(map_js_err)?;
+            let kfrag = VerifiedKeyFrag::try_from(&value_tuple.get(2)).map_err(map_js_err)?;
+
+            typed_replacements.push((removed.0, (address.0, pk.into(), kfrag.into())));
+        }
+
+        let amendment = self
+            .0
+            .amend(
+                signer.as_ref(),
+                receiving_key.as_ref(),
+                typed_replacements,
+                &validity.0,
+                issued_at.into(),
+            )
+            .map_err(map_core_err)?;
+        let revocation_orders: js_sys::Array =
+            into_js_array(amendment.revocation_orders.into_iter().map(RevocationOrder));
+        Ok([
+            JsValue::from(TreasureMap(amendment.treasure_map)),
+            revocation_orders.into(),
+        ]
+        .iter()
+        .collect::<js_sys::Array>()
+        .unchecked_into::<TreasureMapAmendment>())
+    }
+
     #[wasm_bindgen(js_name = makeRevocationOrders)]
-    pub fn make_revocation_orders(&self, signer: &Signer) -> RevocationOrderArray {
+    pub fn make_revocation_orders(&self, signer: &Signer, issued_at: u32) -> RevocationOrderArray {
         into_js_array(
             self.0
-                .make_revocation_orders(signer.as_ref())
+                .make_revocation_orders(signer.as_ref(), issued_at.into())
                 .into_iter()
                 .map(RevocationOrder),
         )
     }
 
+    #[wasm_bindgen(js_name = makeRevocationCertificate)]
+    pub fn make_revocation_certificate(
+        &self,
+        signer: &Signer,
+        issued_at: u32,
+    ) -> RevocationCertificate {
+        RevocationCertificate(
+            self.0
+                .make_revocation_certificate(signer.as_ref(), issued_at.into()),
+        )
+    }
+
     #[wasm_bindgen(getter)]
     pub fn hrac(&self) -> HRAC {
         HRAC(self.0.hrac)
@@ -493,6 +919,11 @@ impl TreasureMap {
         PublicKey::from(self.0.publisher_verifying_key)
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn revision(&self) -> u32 {
+        self.0.revision
+    }
+
     #[wasm_bindgen(js_name = fromBytes)]
     pub fn from_bytes(data: &[u8]) -> Result<TreasureMap, Error> {
         from_bytes::<_, nucypher_core::TreasureMap>(data)
@@ -504,6 +935,59 @@ impl TreasureMap {
     }
 }
 
+//
+// TreasureMapBuilder
+//
+
+#[wasm_bindgen]
+pub struct TreasureMapBuilder(nucypher_core::TreasureMapBuilder);
+
+#[wasm_bindgen]
+impl TreasureMapBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        hrac: &HRAC,
+        policy_encrypting_key: &PublicKey,
+        receiving_key: &PublicKey,
+        threshold: u8,
+        validity: &PolicyValidity,
+    ) -> Self {
+        Self(nucypher_core::TreasureMapBuilder::new(
+            &hrac.0,
+            policy_encrypting_key.as_ref(),
+            receiving_key.as_ref(),
+            threshold,
+            &validity.0,
+        ))
+    }
+
+    #[wasm_bindgen(js_name = verifyKfrags)]
+    pub fn verify_kfrags(&mut self, verify: bool) {
+        self.0.verify_kfrags(verify);
+    }
+
+    #[wasm_bindgen(js_name = addDestination)]
+    pub fn add_destination(
+        &mut self,
+        address: &Address,
+        ursula_encrypting_key: &PublicKey,
+        verified_kfrag: &VerifiedKeyFrag,
+    ) {
+        self.0.add_destination(
+            &address.0,
+            ursula_encrypting_key.as_ref(),
+            verified_kfrag.as_ref().clone(),
+        );
+    }
+
+    pub fn build(&self, signer: &Signer) -> Result<TreasureMap, Error> {
+        self.0
+            .build(signer.as_ref())
+            .map(TreasureMap)
+            .map_err(map_core_err)
+    }
+}
+
 //
 // EncryptedTreasureMap
 //
@@ -521,7 +1005,7 @@ impl EncryptedTreasureMap {
     ) -> Result<TreasureMap, Error> {
         self.0
             .decrypt(sk.as_ref(), publisher_verifying_key.as_ref())
-            .map_err(map_js_err)
+            .map_err(map_core_err)
             .map(TreasureMap)
     }
 
@@ -536,6 +1020,43 @@ impl EncryptedTreasureMap {
     }
 }
 
+//
+// MultiRecipientEncryptedTreasureMap
+//
+
+#[wasm_bindgen]
+#[derive(PartialEq, Debug, derive_more::From, derive_more::AsRef)]
+pub struct MultiRecipientEncryptedTreasureMap(nucypher_core::MultiRecipientEncryptedTreasureMap);
+
+#[wasm_bindgen]
+impl MultiRecipientEncryptedTreasureMap {
+    pub fn decrypt(
+        &self,
+        sk: &SecretKey,
+        publisher_verifying_key: &PublicKey,
+    ) -> Result<TreasureMap, Error> {
+        self.0
+            .decrypt(sk.as_ref(), publisher_verifying_key.as_ref())
+            .map_err(map_core_err)
+            .map(TreasureMap)
+    }
+
+    #[wasm_bindgen(getter, js_name = recipientsCount)]
+    pub fn recipients_count(&self) -> usize {
+        self.0.recipients_count()
+    }
+
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(data: &[u8]) -> Result<MultiRecipientEncryptedTreasureMap, Error> {
+        from_bytes::<_, nucypher_core::MultiRecipientEncryptedTreasureMap>(data)
+    }
+
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        to_bytes(self)
+    }
+}
+
 //
 // ReencryptionRequest
 //
@@ -563,7 +1084,7 @@ impl ReencryptionRequest {
             .into_iter()
             .map(umbral_pre::Capsule::from)
             .collect::<Vec<_>>();
-        Ok(Self(nucypher_core::ReencryptionRequest::new(
+        nucypher_core::ReencryptionRequest::new(
             &backend_capules,
             &hrac.0,
             &encrypted_kfrag.0,
@@ -571,7 +1092,9 @@ impl ReencryptionRequest {
             bob_verifying_key.as_ref(),
             typed_conditions.as_ref().map(|conditions| &conditions.0),
             typed_context.as_ref().map(|context| &context.0),
-        )))
+        )
+        .map(Self)
+        .map_err(map_core_err)
     }
 
     #[wasm_bindgen(getter)]
@@ -624,6 +1147,32 @@ impl ReencryptionRequest {
 // ReencryptionResponse
 //
 
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RefusalReason {
+    ConditionsNotSatisfied,
+    ConditionEvaluationFailed,
+}
+
+impl From<RefusalReason> for nucypher_core::RefusalReason {
+    fn from(reason: RefusalReason) -> Self {
+        match reason {
+            RefusalReason::ConditionsNotSatisfied => Self::ConditionsNotSatisfied,
+            RefusalReason::ConditionEvaluationFailed => Self::ConditionEvaluationFailed,
+        }
+    }
+}
+
+fn try_from_js_refusal_reason(value: &JsValue) -> Option<RefusalReason> {
+    let number = value.as_f64()?;
+    [
+        RefusalReason::ConditionsNotSatisfied,
+        RefusalReason::ConditionEvaluationFailed,
+    ]
+    .into_iter()
+    .find(|reason| *reason as u32 as f64 == number)
+}
+
 #[wasm_bindgen]
 #[derive(derive_more::From, derive_more::AsRef)]
 pub struct ReencryptionResponse(nucypher_core::ReencryptionResponse);
@@ -633,39 +1182,32 @@ impl ReencryptionResponse {
     #[wasm_bindgen(constructor)]
     pub fn new(
         signer: &Signer,
-        capsules_and_vcfrags: &CapsuleAndVerifiedCapsuleFragArray,
+        request: &ReencryptionRequest,
+        results: &CapsuleResultArray,
     ) -> Result<ReencryptionResponse, Error> {
-        let js_capsules_and_vcfrags: &JsValue = capsules_and_vcfrags.as_ref();
-        let capsules_and_vcfrags_array: &js_sys::Array = js_capsules_and_vcfrags
+        let js_results: &JsValue = results.as_ref();
+        let results_array: &js_sys::Array = js_results
             .dyn_ref()
-            .ok_or_else(|| Error::new("`capsules_and_vcfrags` must be an array"))?;
-
-        let mut backend_capsules = Vec::new();
-        let mut backend_vcfrags = Vec::new();
-
-        for entry in capsules_and_vcfrags_array.iter() {
-            let entry_tuple: js_sys::Array = entry.dyn_into()?;
-            if entry_tuple.length() != 2 {
-                return Err(Error::new(
-                    "A tuple of an incorrect size received when iterating through list's entries",
-                ));
-            }
-
-            let capsule = umbral_pre::Capsule::from(
-                Capsule::try_from(&entry_tuple.get(0)).map_err(map_js_err)?,
-            );
-            let vcfrag = umbral_pre::VerifiedCapsuleFrag::from(
-                VerifiedCapsuleFrag::try_from(&entry_tuple.get(1)).map_err(map_js_err)?,
-            );
-
-            backend_capsules.push(capsule);
-            backend_vcfrags.push(vcfrag);
+            .ok_or_else(|| Error::new("`results` must be an array"))?;
+
+        let mut backend_results = Vec::new();
+        for entry in results_array.iter() {
+            let result = match try_from_js_refusal_reason(&entry) {
+                Some(reason) => Err(reason.into()),
+                None => Ok(umbral_pre::VerifiedCapsuleFrag::from(
+                    VerifiedCapsuleFrag::try_from(&entry).map_err(map_js_err)?,
+                )),
+            };
+            backend_results.push(result);
         }
 
-        Ok(Self(nucypher_core::ReencryptionResponse::new(
+        nucypher_core::ReencryptionResponse::new_partial(
             signer.as_ref(),
-            backend_capsules.iter().zip(backend_vcfrags.into_iter()),
-        )))
+            request.as_ref(),
+            backend_results,
+        )
+        .map(Self)
+        .map_err(map_core_err)
     }
 
     #[wasm_bindgen(js_name = fromBytes)]
@@ -681,37 +1223,31 @@ impl ReencryptionResponse {
     #[wasm_bindgen]
     pub fn verify(
         &self,
-        capsules: &CapsuleArray,
+        request: &ReencryptionRequest,
         alice_verifying_key: &PublicKey,
         ursula_verifying_key: &PublicKey,
         policy_encrypting_key: &PublicKey,
         bob_encrypting_key: &PublicKey,
-    ) -> Result<VerifiedCapsuleFragArray, Error> {
-        let typed_capsules = try_from_js_array::<Capsule>(capsules)?;
-        let backend_capsules = typed_capsules
-            .into_iter()
-            .map(|capsule| capsule.as_ref().clone())
-            .collect::<Vec<_>>();
-        let backend_vcfrags = self
+    ) -> Result<VerifiedCapsuleResultArray, Error> {
+        let backend_results = self
             .0
             .clone()
             .verify(
-                &backend_capsules,
+                request.as_ref(),
                 alice_verifying_key.as_ref(),
                 ursula_verifying_key.as_ref(),
                 policy_encrypting_key.as_ref(),
                 bob_encrypting_key.as_ref(),
             )
-            .map_err(|_err| {
-                JsValue::from(Error::new("ReencryptionResponse verification failed"))
-            })?;
-
-        Ok(into_js_array(
-            backend_vcfrags
-                .into_vec()
-                .into_iter()
-                .map(VerifiedCapsuleFrag::from),
-        ))
+            .map_err(|err| JsValue::from(map_core_err(err)))?;
+
+        // Capsules for which no capsule frag was obtained are represented by the error objects.
+        Ok(into_js_array(backend_results.into_vec().into_iter().map(
+            |result| match result {
+                Ok(vcfrag) => JsValue::from(VerifiedCapsuleFrag::from(vcfrag)),
+                Err(err) => JsValue::from(map_core_err(err)),
+            },
+        )))
     }
 }
 
@@ -719,8 +1255,9 @@ impl ReencryptionResponse {
 // RetrievalKit
 //
 
+#[derive(TryFromJsValue)]
 #[wasm_bindgen]
-#[derive(derive_more::From, derive_more::AsRef)]
+#[derive(Clone, derive_more::From, derive_more::AsRef)]
 pub struct RetrievalKit(nucypher_core::RetrievalKit);
 
 #[wasm_bindgen]
@@ -737,11 +1274,13 @@ impl RetrievalKit {
             .into_iter()
             .map(|address| address.0)
             .collect::<Vec<_>>();
-        Ok(Self(nucypher_core::RetrievalKit::new(
+        nucypher_core::RetrievalKit::new(
             capsule.as_ref(),
             backend_addresses,
             typed_conditions.as_ref().map(|conditions| &conditions.0),
-        )))
+        )
+        .map(Self)
+        .map_err(map_core_err)
     }
 
     #[wasm_bindgen(js_name = fromMessageKit)]
@@ -777,12 +1316,192 @@ impl RetrievalKit {
     }
 }
 
+//
+// RetrievalPlan
+//
+
+#[wasm_bindgen]
+#[derive(derive_more::From, derive_more::AsRef)]
+pub struct RetrievalPlan(nucypher_core::RetrievalPlan);
+
+#[wasm_bindgen]
+impl RetrievalPlan {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        treasure_map: &TreasureMap,
+        retrieval_kits: &RetrievalKitArray,
+        bob_encrypting_key: &PublicKey,
+        bob_verifying_key: &PublicKey,
+        context: &OptionContext,
+    ) -> Result<RetrievalPlan, Error> {
+        let typed_retrieval_kits = try_from_js_array::<RetrievalKit>(retrieval_kits)?;
+        let typed_context = try_from_js_option::<Context>(context)?;
+        nucypher_core::RetrievalPlan::new(
+            &treasure_map.0,
+            typed_retrieval_kits.iter().map(|kit| &kit.0),
+            bob_encrypting_key.as_ref(),
+            bob_verifying_key.as_ref(),
+            typed_context.as_ref().map(|context| &context.0),
+        )
+        .map(Self)
+        .map_err(map_core_err)
+    }
+
+    #[wasm_bindgen(js_name = nextRequests)]
+    pub fn next_requests(&mut self) -> AddressedReencryptionRequestArray {
+        into_js_array(
+            self.0
+                .next_requests()
+                .into_iter()
+                .map(|(address, request)| {
+                    [
+                        JsValue::from(Address(address)),
+                        JsValue::from(ReencryptionRequest(request)),
+                    ]
+                    .iter()
+                    .collect::<js_sys::Array>()
+                }),
+        )
+    }
+
+    #[wasm_bindgen(js_name = addResponse)]
+    pub fn add_response(
+        &mut self,
+        address: &Address,
+        ursula_verifying_key: &PublicKey,
+        response: &ReencryptionResponse,
+    ) -> Result<(), Error> {
+        self.0
+            .add_response(
+                &address.0,
+                ursula_verifying_key.as_ref(),
+                response.0.clone(),
+            )
+            .map_err(map_core_err)
+    }
+
+    #[wasm_bindgen(js_name = reportFailure)]
+    pub fn report_failure(&mut self, address: &Address) {
+        self.0.report_failure(&address.0)
+    }
+
+    #[wasm_bindgen(js_name = isDecryptable)]
+    pub fn is_decryptable(&self, index: usize) -> bool {
+        self.0.is_decryptable(index)
+    }
+
+    #[wasm_bindgen(js_name = isComplete)]
+    pub fn is_complete(&self) -> bool {
+        self.0.is_complete()
+    }
+
+    #[wasm_bindgen(js_name = isExhausted)]
+    pub fn is_exhausted(&self) -> bool {
+        self.0.is_exhausted()
+    }
+
+    #[wasm_bindgen(js_name = verifiedCfrags)]
+    pub fn verified_cfrags(&self, index: usize) -> VerifiedCapsuleFragArray {
+        into_js_array(
+            self.0
+                .verified_cfrags(index)
+                .into_iter()
+                .map(VerifiedCapsuleFrag::from),
+        )
+    }
+}
+
+//
+// ReencryptionService
+//
+
+fn callback_result(result: Result<JsValue, JsValue>) -> Result<bool, String> {
+    let value = result.map_err(|err| match err.dyn_ref::<Error>() {
+        Some(err) => String::from(err.message()),
+        None => format!("{:?}", err),
+    })?;
+    value
+        .as_bool()
+        .ok_or_else(|| "the callback must return a boolean".to_string())
+}
+
+struct JsRevocationChecker(RevocationCheckerFunction);
+
+impl nucypher_core::RevocationChecker for JsRevocationChecker {
+    fn is_revoked(
+        &self,
+        hrac: &nucypher_core::HRAC,
+        encrypted_kfrag: &nucypher_core::EncryptedKeyFrag,
+    ) -> Result<bool, String> {
+        callback_result(self.0.call2(
+            &JsValue::NULL,
+            &HRAC(*hrac).into(),
+            &EncryptedKeyFrag(encrypted_kfrag.clone()).into(),
+        ))
+    }
+}
+
+struct JsConditionEvaluator(ConditionEvaluatorFunction);
+
+impl nucypher_core::ConditionEvaluator for JsConditionEvaluator {
+    fn evaluate(
+        &self,
+        conditions: &nucypher_core::Conditions,
+        context: Option<&nucypher_core::Context>,
+    ) -> Result<bool, String> {
+        let js_context = match context {
+            Some(context) => Context(context.clone()).into(),
+            None => JsValue::NULL,
+        };
+        callback_result(self.0.call2(
+            &JsValue::NULL,
+            &Conditions(conditions.clone()).into(),
+            &js_context,
+        ))
+    }
+}
+
+#[wasm_bindgen]
+pub struct ReencryptionService(
+    nucypher_core::ReencryptionService<JsRevocationChecker, JsConditionEvaluator>,
+);
+
+#[wasm_bindgen]
+impl ReencryptionService {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        signer: &Signer,
+        decrypting_key: &SecretKey,
+        revocation_checker: RevocationCheckerFunction,
+        condition_evaluator: ConditionEvaluatorFunction,
+    ) -> Self {
+        Self(nucypher_core::ReencryptionService::new(
+            signer.as_ref().clone(),
+            decrypting_key.as_ref().clone(),
+            JsRevocationChecker(revocation_checker),
+            JsConditionEvaluator(condition_evaluator),
+        ))
+    }
+
+    pub fn handle(
+        &self,
+        request: &ReencryptionRequest,
+        now: u32,
+    ) -> Result<ReencryptionResponse, Error> {
+        self.0
+            .handle(&request.0, now.into())
+            .map(ReencryptionResponse)
+            .map_err(map_core_err)
+    }
+}
+
 //
 // RevocationOrder
 //
 
+#[derive(TryFromJsValue)]
 #[wasm_bindgen]
-#[derive(PartialEq, Debug, derive_more::From, derive_more::AsRef)]
+#[derive(Clone, PartialEq, Debug, derive_more::From, derive_more::AsRef)]
 pub struct RevocationOrder(nucypher_core::RevocationOrder);
 
 #[wasm_bindgen]
@@ -792,14 +1511,35 @@ impl RevocationOrder {
         signer: &Signer,
         staking_provider_address: &Address,
         encrypted_kfrag: &EncryptedKeyFrag,
+        hrac: &HRAC,
+        issued_at: u32,
+        reason: Option<u16>,
     ) -> Result<RevocationOrder, Error> {
         Ok(Self(nucypher_core::RevocationOrder::new(
             signer.as_ref(),
             &staking_provider_address.0,
             &encrypted_kfrag.0,
+            &hrac.0,
+            issued_at.into(),
+            reason,
         )))
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn hrac(&self) -> HRAC {
+        HRAC(self.0.hrac())
+    }
+
+    #[wasm_bindgen(getter, js_name = issuedAt)]
+    pub fn issued_at(&self) -> f64 {
+        self.0.issued_at() as f64
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn reason(&self) -> Option<u16> {
+        self.0.reason()
+    }
+
     #[wasm_bindgen]
     pub fn verify(
         &self,
@@ -809,7 +1549,7 @@ impl RevocationOrder {
             .0
             .clone()
             .verify(alice_verifying_key.as_ref())
-            .map_err(|_err| Error::new("Failed to verify RevocationOrder"))?;
+            .map_err(map_core_err)?;
         Ok(into_js_array([
             JsValue::from(Address(address)),
             JsValue::from(EncryptedKeyFrag(ekfrag)),
@@ -827,6 +1567,402 @@ impl RevocationOrder {
     }
 }
 
+//
+// RevocationKit
+//
+
+#[wasm_bindgen]
+#[derive(Clone, PartialEq, Debug, derive_more::From, derive_more::AsRef)]
+pub struct RevocationKit(nucypher_core::RevocationKit);
+
+#[wasm_bindgen]
+impl RevocationKit {
+    #[wasm_bindgen(constructor)]
+    pub fn new(signer: &Signer, treasure_map: &TreasureMap, issued_at: u32) -> Self {
+        Self(nucypher_core::RevocationKit::new(
+            signer.as_ref(),
+            &treasure_map.0,
+            issued_at.into(),
+        ))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn hrac(&self) -> HRAC {
+        HRAC(self.0.hrac)
+    }
+
+    #[wasm_bindgen(getter, js_name = revocationOrders)]
+    pub fn revocation_orders(&self) -> RevocationOrdersArray {
+        into_js_array(self.0.revocation_orders.iter().map(|(address, order)| {
+            [
+                JsValue::from(Address(*address)),
+                JsValue::from(RevocationOrder(order.clone())),
+            ]
+            .iter()
+            .collect::<js_sys::Array>()
+        }))
+    }
+
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(data: &[u8]) -> Result<RevocationKit, Error> {
+        from_bytes::<_, nucypher_core::RevocationKit>(data)
+    }
+
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        to_bytes(self)
+    }
+}
+
+//
+// PolicyGrant
+//
+
+#[wasm_bindgen]
+#[derive(Clone, PartialEq, Debug, derive_more::From, derive_more::AsRef)]
+pub struct PolicyGrant(nucypher_core::PolicyGrant);
+
+#[wasm_bindgen]
+impl PolicyGrant {
+    #[allow(clippy::too_many_arguments)]
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        signer: &Signer,
+        delegating_sk: &SecretKey,
+        bob_encrypting_key: &PublicKey,
+        bob_verifying_key: &PublicKey,
+        label: &[u8],
+        threshold: u8,
+        ursulas: &NodeMetadataArray,
+        validity: &PolicyValidity,
+        now: u32,
+    ) -> Result<PolicyGrant, Error> {
+        let typed_ursulas = try_from_js_array::<NodeMetadata>(ursulas)?;
+        let backend_ursulas = typed_ursulas
+            .into_iter()
+            .map(|node| node.0)
+            .collect::<Vec<_>>();
+        nucypher_core::PolicyGrant::new(
+            signer.as_ref(),
+            delegating_sk.as_ref(),
+            bob_encrypting_key.as_ref(),
+            bob_verifying_key.as_ref(),
+            label,
+            threshold,
+            &backend_ursulas,
+            &validity.0,
+            now.into(),
+        )
+        .map(Self)
+        .map_err(map_core_err)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn hrac(&self) -> HRAC {
+        HRAC(self.0.hrac)
+    }
+
+    #[wasm_bindgen(getter, js_name = policyEncryptingKey)]
+    pub fn policy_encrypting_key(&self) -> PublicKey {
+        PublicKey::from(self.0.policy_encrypting_key)
+    }
+
+    #[wasm_bindgen(getter, js_name = encryptedTreasureMap)]
+    pub fn encrypted_treasure_map(&self) -> EncryptedTreasureMap {
+        EncryptedTreasureMap(self.0.encrypted_treasure_map.clone())
+    }
+
+    #[wasm_bindgen(getter, js_name = revocationKit)]
+    pub fn revocation_kit(&self) -> RevocationKit {
+        RevocationKit(self.0.revocation_kit.clone())
+    }
+}
+
+//
+// RevocationReceipt
+//
+
+#[wasm_bindgen]
+#[derive(derive_more::From, derive_more::AsRef)]
+pub struct RevocationReceipt(nucypher_core::RevocationReceipt);
+
+#[wasm_bindgen]
+impl RevocationReceipt {
+    #[wasm_bindgen(constructor)]
+    pub fn new(signer: &Signer, order: &RevocationOrder, acknowledged_at: u32) -> Self {
+        Self(nucypher_core::RevocationReceipt::new(
+            signer.as_ref(),
+            &order.0,
+            acknowledged_at.into(),
+        ))
+    }
+
+    pub fn verify(
+        &self,
+        ursula_verifying_key: &PublicKey,
+        order: &RevocationOrder,
+    ) -> Result<f64, Error> {
+        self.0
+            .clone()
+            .verify(ursula_verifying_key.as_ref(), &order.0)
+            .map(|acknowledged_at| acknowledged_at as f64)
+            .map_err(map_core_err)
+    }
+
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(data: &[u8]) -> Result<RevocationReceipt, Error> {
+        from_bytes::<_, nucypher_core::RevocationReceipt>(data)
+    }
+
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        to_bytes(self)
+    }
+}
+
+//
+// RevocationCertificate
+//
+
+#[wasm_bindgen]
+#[derive(derive_more::From, derive_more::AsRef)]
+pub struct RevocationCertificate(nucypher_core::RevocationCertificate);
+
+#[wasm_bindgen]
+impl RevocationCertificate {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        signer: &Signer,
+        hrac: &HRAC,
+        destinations: &DestinationsArray,
+        issued_at: u32,
+    ) -> Result<RevocationCertificate, Error> {
+        let js_destinations: &JsValue = destinations.as_ref();
+        let destinations_array: &js_sys::Array = js_destinations
+            .dyn_ref()
+            .ok_or_else(|| Error::new("`destinations` must be an array"))?;
+
+        let mut typed_destinations = BTreeMap::new();
+        for entry in destinations_array.iter() {
+            let key_value: js_sys::Array = entry.dyn_into()?;
+            if key_value.length() != 2 {
+                return Err(Error::new(
+                    "A tuple of an incorrect size received when iterating through map's entries",
+                ));
+            }
+            let address = Address::try_from(&key_value.get(0)).map_err(map_js_err)?;
+            let ekfrag = EncryptedKeyFrag::try_from(&key_value.get(1)).map_err(map_js_err)?;
+            typed_destinations.insert(address.0, ekfrag.0);
+        }
+
+        Ok(Self(nucypher_core::RevocationCertificate::new(
+            signer.as_ref(),
+            &hrac.0,
+            &typed_destinations,
+            issued_at.into(),
+        )))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn hrac(&self) -> HRAC {
+        HRAC(self.0.hrac())
+    }
+
+    #[wasm_bindgen(getter, js_name = issuedAt)]
+    pub fn issued_at(&self) -> f64 {
+        self.0.issued_at() as f64
+    }
+
+    pub fn verify(
+        &self,
+        alice_verifying_key: &PublicKey,
+    ) -> Result<VerifiedRevocationCertificate, Error> {
+        let (hrac, destinations) = self
+            .0
+            .clone()
+            .verify(alice_verifying_key.as_ref())
+            .map_err(map_core_err)?;
+        let js_destinations: js_sys::Array = destinations
+            .into_iter()
+            .map(|(address, ekfrag)| {
+                [
+                    JsValue::from(Address(address)),
+                    JsValue::from(EncryptedKeyFrag(ekfrag)),
+                ]
+                .iter()
+                .collect::<js_sys::Array>()
+            })
+            .collect();
+        Ok(into_js_array([
+            JsValue::from(HRAC(hrac)),
+            JsValue::from(js_destinations),
+        ]))
+    }
+
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(data: &[u8]) -> Result<RevocationCertificate, Error> {
+        from_bytes::<_, nucypher_core::RevocationCertificate>(data)
+    }
+
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        to_bytes(self)
+    }
+}
+
+//
+// RevocationRegistry
+//
+
+#[wasm_bindgen]
+#[derive(Default, derive_more::From, derive_more::AsRef)]
+pub struct InMemoryRevocationRegistry(nucypher_core::InMemoryRevocationRegistry);
+
+#[wasm_bindgen]
+impl InMemoryRevocationRegistry {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[wasm_bindgen(js_name = fromSnapshot)]
+    pub fn from_snapshot(snapshot: &RevocationRegistrySnapshot) -> Self {
+        Self(snapshot.0.clone().into())
+    }
+
+    pub fn revoke(
+        &mut self,
+        order: &RevocationOrder,
+        publisher_verifying_key: &PublicKey,
+        staking_provider_address: &Address,
+        decrypting_key: &SecretKey,
+        now: u32,
+    ) -> Result<(), Error> {
+        nucypher_core::revoke(
+            &mut self.0,
+            &order.0,
+            publisher_verifying_key.as_ref(),
+            &staking_provider_address.0,
+            decrypting_key.as_ref(),
+            now.into(),
+        )
+        .map_err(map_core_err)
+    }
+
+    #[wasm_bindgen(js_name = revokePolicy)]
+    pub fn revoke_policy(
+        &mut self,
+        certificate: &RevocationCertificate,
+        publisher_verifying_key: &PublicKey,
+        staking_provider_address: &Address,
+        decrypting_key: &SecretKey,
+        now: u32,
+    ) -> Result<(), Error> {
+        nucypher_core::revoke_policy(
+            &mut self.0,
+            &certificate.0,
+            publisher_verifying_key.as_ref(),
+            &staking_provider_address.0,
+            decrypting_key.as_ref(),
+            now.into(),
+        )
+        .map_err(map_core_err)
+    }
+
+    #[wasm_bindgen(js_name = isRevoked)]
+    pub fn is_revoked(&self, hrac: &HRAC, encrypted_kfrag: &EncryptedKeyFrag) -> bool {
+        nucypher_core::is_kfrag_revoked(&self.0, &hrac.0, &encrypted_kfrag.0)
+    }
+
+    pub fn snapshot(&self) -> RevocationRegistrySnapshot {
+        RevocationRegistrySnapshot(self.0.snapshot())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[wasm_bindgen]
+#[derive(derive_more::From, derive_more::AsRef)]
+pub struct RevocationRegistrySnapshot(nucypher_core::RevocationRegistrySnapshot);
+
+#[wasm_bindgen]
+impl RevocationRegistrySnapshot {
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(data: &[u8]) -> Result<RevocationRegistrySnapshot, Error> {
+        from_bytes::<_, nucypher_core::RevocationRegistrySnapshot>(data)
+    }
+
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        to_bytes(self)
+    }
+}
+
+//
+// EncryptingKeyRotation
+//
+
+#[wasm_bindgen]
+#[derive(PartialEq, Debug, derive_more::From, derive_more::AsRef)]
+pub struct EncryptingKeyRotation(nucypher_core::EncryptingKeyRotation);
+
+#[wasm_bindgen]
+impl EncryptingKeyRotation {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        signer: &Signer,
+        staking_provider_address: &Address,
+        old_encrypting_key: &PublicKey,
+        new_encrypting_key: &PublicKey,
+        timestamp_epoch: u32,
+    ) -> Self {
+        Self(nucypher_core::EncryptingKeyRotation::new(
+            signer.as_ref(),
+            &staking_provider_address.0,
+            old_encrypting_key.as_ref(),
+            new_encrypting_key.as_ref(),
+            timestamp_epoch.into(),
+        ))
+    }
+
+    pub fn verify(&self, verifying_key: &PublicKey) -> Result<(), Error> {
+        self.0.verify(verifying_key.as_ref()).map_err(map_core_err)
+    }
+
+    #[wasm_bindgen(getter, js_name = stakingProviderAddress)]
+    pub fn staking_provider_address(&self) -> Address {
+        Address(self.0.staking_provider_address)
+    }
+
+    #[wasm_bindgen(getter, js_name = oldEncryptingKey)]
+    pub fn old_encrypting_key(&self) -> PublicKey {
+        PublicKey::from(self.0.old_encrypting_key)
+    }
+
+    #[wasm_bindgen(getter, js_name = newEncryptingKey)]
+    pub fn new_encrypting_key(&self) -> PublicKey {
+        PublicKey::from(self.0.new_encrypting_key)
+    }
+
+    #[wasm_bindgen(getter, js_name = timestampEpoch)]
+    pub fn timestamp_epoch(&self) -> f64 {
+        self.0.timestamp_epoch as f64
+    }
+
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(data: &[u8]) -> Result<EncryptingKeyRotation, Error> {
+        from_bytes::<_, nucypher_core::EncryptingKeyRotation>(data)
+    }
+
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        to_bytes(self)
+    }
+}
+
 //
 // NodeMetadataPayload
 //
@@ -912,7 +2048,7 @@ impl NodeMetadataPayload {
         self.0
             .derive_operator_address()
             .map(Address)
-            .map_err(map_js_err)
+            .map_err(map_core_err)
     }
 }
 
@@ -1100,7 +2236,7 @@ impl MetadataResponse {
         self.0
             .clone()
             .verify(verifying_pk.as_ref())
-            .map_err(|_err| Error::new("Failed to verify MetadataResponse"))
+            .map_err(map_core_err)
             .map(MetadataResponsePayload)
     }
 
@@ -68,7 +68,7 @@ fn make_hrac() -> HRAC {
 fn make_kfrags(delegating_sk: &SecretKey, receiving_sk: &SecretKey) -> Vec<VerifiedKeyFrag> {
     let receiving_pk = receiving_sk.public_key();
     let signer = Signer::new(delegating_sk);
-    let js_kfrags = generate_kfrags(delegating_sk, &receiving_pk, &signer, 2, 3, false, false);
+    let js_kfrags = generate_kfrags(delegating_sk, &receiving_pk, &signer, 2, 3, true, true);
     try_from_js_array::<VerifiedKeyFrag>(js_kfrags)
 }
 
@@ -79,11 +79,15 @@ fn make_fleet_state_checksum() -> FleetStateChecksum {
 }
 
 fn make_node_metadata() -> NodeMetadata {
+    make_node_metadata_at(b"00000000000000000001")
+}
+
+fn make_node_metadata_at(address: &[u8]) -> NodeMetadata {
     // Just a random valid key.
     // Need to fix it to check the operator address derivation.
     let signing_key = SecretKey::from_be_bytes(b"01234567890123456789012345678901").unwrap();
 
-    let staking_provider_address = Address::new(b"00000000000000000001").unwrap();
+    let staking_provider_address = Address::new(address).unwrap();
     let domain = "localhost";
     let timestamp_epoch = 1546300800;
     let verifying_key = signing_key.public_key();
@@ -121,6 +125,67 @@ fn make_metadata_response_payload() -> (MetadataResponsePayload, Vec<NodeMetadat
     (payload, announce_nodes)
 }
 
+//
+// Conditions
+//
+
+#[wasm_bindgen_test]
+fn conditions_parse() {
+    let conditions = Conditions::new(
+        r#"{"version":"1.0.0","condition":{"conditionType":"time","chain":1,"method":"blocktime","returnValueTest":{"comparator":">","value":100}}}"#,
+    );
+    let lingo = conditions.parse().unwrap();
+    assert_eq!(lingo.version(), "1.0.0");
+    assert_eq!(
+        lingo.to_conditions().to_string(),
+        conditions.to_string(),
+        "Conditions do not roundtrip"
+    );
+
+    let rebuilt = ConditionLingo::new(&lingo.condition());
+    assert!(rebuilt.validate().is_ok());
+    assert_eq!(rebuilt.to_string(), lingo.to_string());
+
+    assert!(Conditions::new("{'llamas': 'yes'}").parse().is_err());
+    assert!(Conditions::new(
+        r#"{"version":"1.0.0","extra":1,"condition":{"conditionType":"time","chain":1,"method":"blocktime","returnValueTest":{"comparator":">","value":100}}}"#,
+    )
+    .parse()
+    .is_err());
+}
+
+#[wasm_bindgen_test]
+fn condition_builder() {
+    let contract = "0x1e6F4Bd5E3f8A2f1aFbF4E6D6B3bA1c0D9e2F3a4";
+    let conditions = Condition::block_time(1, ">=", 1700000000)
+        .unwrap()
+        .and(&Condition::erc721_ownership(137, contract, "5").unwrap())
+        .negate()
+        .build()
+        .unwrap();
+    assert_eq!(
+        conditions.to_string(),
+        nucypher_core::Condition::block_time(1, nucypher_core::Comparator::Ge, 1700000000)
+            .and(nucypher_core::Condition::erc721_ownership(137, contract, 5))
+            .negate()
+            .build()
+            .unwrap()
+            .as_ref()
+    );
+
+    assert_eq!(
+        conditions.hash().unwrap(),
+        Conditions::new(&format!(" {} ", conditions.to_string()))
+            .hash()
+            .unwrap()
+    );
+    assert!(Condition::block_time(1, "~", 0).is_err());
+    assert!(Condition::erc20_balance(1, "0x1234", ">", "1")
+        .unwrap()
+        .build()
+        .is_err());
+}
+
 //
 // MessageKit
 //
@@ -139,6 +204,38 @@ fn message_kit_decrypts() {
     );
 }
 
+#[wasm_bindgen_test]
+fn message_kit_error_code() {
+    let sk = SecretKey::random();
+    let message_kit = make_message_kit(&sk, b"Hello, world!", None::<&str>);
+    let mut bytes = message_kit.to_bytes().to_vec();
+    bytes[0] ^= 1;
+
+    let err = MessageKit::from_bytes(&bytes).unwrap_err();
+    let code = js_sys::Reflect::get(err.as_ref(), &"code".into()).unwrap();
+    let code_name = js_sys::Reflect::get(err.as_ref(), &"codeName".into()).unwrap();
+    assert_eq!(code.as_f64(), Some(302.0));
+    assert_eq!(
+        code_name.as_string().as_deref(),
+        Some("DESERIALIZATION_INCORRECT_HEADER")
+    );
+}
+
+#[wasm_bindgen_test]
+fn message_kit_decryption_error_code() {
+    let message_kit = make_message_kit(&SecretKey::random(), b"Hello, world!", None::<&str>);
+    let err = message_kit.decrypt(&SecretKey::random()).unwrap_err();
+    let code = js_sys::Reflect::get(err.as_ref(), &"code".into()).unwrap();
+    assert_eq!(code.as_f64(), Some(201.0));
+
+    let err = match Condition::erc20_balance(1, "0x1234", ">", "one") {
+        Err(err) => err,
+        Ok(_) => panic!("invalid amount accepted"),
+    };
+    let code = js_sys::Reflect::get(err.as_ref(), &"code".into()).unwrap();
+    assert_eq!(code.as_f64(), Some(501.0));
+}
+
 #[wasm_bindgen_test]
 fn message_kit_decrypt_reencrypted() {
     // Create a message kit
@@ -226,16 +323,82 @@ fn encrypted_kfrag_decrypt() {
 
     let verified_kfrags = make_kfrags(&delegating_sk, &receiving_sk);
 
-    let encrypted_kfrag = EncryptedKeyFrag::new(&signer, &receiving_pk, &hrac, &verified_kfrags[0]);
+    let encrypted_kfrag = EncryptedKeyFrag::new(
+        &signer,
+        &receiving_pk,
+        &hrac,
+        &verified_kfrags[0],
+        &delegating_sk.public_key(),
+        &receiving_pk,
+        &PolicyValidity::new(2000, None),
+    )
+    .unwrap();
 
     let decrypted = encrypted_kfrag
-        .decrypt(&receiving_sk, &hrac, &delegating_pk)
+        .decrypt_at(&receiving_sk, &hrac, &delegating_pk, 1000)
         .unwrap();
     assert_eq!(
         decrypted.to_bytes(),
         verified_kfrags[0].to_bytes(),
         "Decrypted KFrag does not match"
     );
+    assert!(
+        encrypted_kfrag
+            .decrypt_at(&receiving_sk, &hrac, &delegating_pk, 2000)
+            .is_err(),
+        "Expired KFrag was accepted"
+    );
+}
+
+#[wasm_bindgen_test]
+fn encrypted_kfrag_rewrap() {
+    let hrac = make_hrac();
+    let delegating_sk = SecretKey::random();
+    let delegating_pk = delegating_sk.public_key();
+    let receiving_sk = SecretKey::random();
+    let receiving_pk = receiving_sk.public_key();
+    let signer = Signer::new(&delegating_sk);
+
+    let verified_kfrags = make_kfrags(&delegating_sk, &receiving_sk);
+    let encrypted_kfrag = EncryptedKeyFrag::new(
+        &signer,
+        &receiving_pk,
+        &hrac,
+        &verified_kfrags[0],
+        &delegating_sk.public_key(),
+        &receiving_pk,
+        &PolicyValidity::new(2000, None),
+    )
+    .unwrap();
+
+    let new_sk = SecretKey::random();
+    let rewrapped = encrypted_kfrag
+        .rewrap(&receiving_sk, &new_sk.public_key())
+        .unwrap();
+    let decrypted = rewrapped
+        .decrypt_at(&new_sk, &hrac, &delegating_pk, 1000)
+        .unwrap();
+    assert_eq!(
+        decrypted.to_bytes(),
+        verified_kfrags[0].to_bytes(),
+        "Re-wrapped KFrag does not match"
+    );
+
+    let ursula_signer = Signer::new(&SecretKey::random());
+    let rotation = EncryptingKeyRotation::new(
+        &ursula_signer,
+        &Address::new(b"00000000000000000001").unwrap(),
+        &receiving_pk,
+        &new_sk.public_key(),
+        1000,
+    );
+    assert!(rotation.verify(&ursula_signer.verifying_key()).is_ok());
+    assert!(rotation.verify(&SecretKey::random().public_key()).is_err());
+    assert_eq!(
+        rotation,
+        EncryptingKeyRotation::from_bytes(&rotation.to_bytes()).unwrap(),
+        "EncryptingKeyRotation does not roundtrip"
+    );
 }
 
 #[wasm_bindgen_test]
@@ -247,7 +410,16 @@ fn encrypted_to_bytes_from_bytes() {
     let signer = Signer::new(&delegating_sk);
 
     let verified_kfrags = make_kfrags(&delegating_sk, &receiving_sk);
-    let encrypted_kfrag = EncryptedKeyFrag::new(&signer, &receiving_pk, &hrac, &verified_kfrags[0]);
+    let encrypted_kfrag = EncryptedKeyFrag::new(
+        &signer,
+        &receiving_pk,
+        &hrac,
+        &verified_kfrags[0],
+        &delegating_sk.public_key(),
+        &receiving_pk,
+        &PolicyValidity::new(2000, None),
+    )
+    .unwrap();
 
     assert_eq!(
         encrypted_kfrag,
@@ -291,9 +463,11 @@ fn make_treasure_map(publisher_sk: &SecretKey, receiving_sk: &SecretKey) -> Trea
     TreasureMap::new(
         &Signer::new(publisher_sk),
         &hrac,
-        &SecretKey::random().public_key(),
+        &publisher_sk.public_key(),
+        &receiving_sk.public_key(),
         &assigned_kfrags_js,
         2,
+        &PolicyValidity::new(2000, None),
     )
     .unwrap()
 }
@@ -308,7 +482,7 @@ fn treasure_map_encrypt_decrypt() {
     let publisher_pk = publisher_sk.public_key();
     let recipient_pk = receiving_sk.public_key();
     let signer = Signer::new(&publisher_sk);
-    let encrypted = treasure_map.encrypt(&signer, &recipient_pk);
+    let encrypted = treasure_map.encrypt(&signer, &recipient_pk).unwrap();
 
     let decrypted = encrypted.decrypt(&receiving_sk, &publisher_pk).unwrap();
 
@@ -316,6 +490,93 @@ fn treasure_map_encrypt_decrypt() {
         decrypted, treasure_map,
         "Decrypted TreasureMap does not match"
     );
+
+    let other_signer = Signer::new(&SecretKey::random());
+    assert!(
+        treasure_map.encrypt(&other_signer, &recipient_pk).is_err(),
+        "Encryption by someone other than the publisher should fail"
+    );
+    assert!(
+        encrypted
+            .decrypt(&receiving_sk, &other_signer.verifying_key())
+            .is_err(),
+        "Decryption with a wrong publisher key should fail"
+    );
+}
+
+#[wasm_bindgen_test]
+fn treasure_map_builder() {
+    let publisher_sk = SecretKey::random();
+    let receiving_sk = SecretKey::random();
+    let vkfrags = make_kfrags(&publisher_sk, &receiving_sk);
+
+    let mut builder = TreasureMapBuilder::new(
+        &make_hrac(),
+        &publisher_sk.public_key(),
+        &receiving_sk.public_key(),
+        3,
+        &PolicyValidity::new(2000, None),
+    );
+    builder.verify_kfrags(true);
+    builder.add_destination(
+        &Address::new(b"00000000000000000001").unwrap(),
+        &SecretKey::random().public_key(),
+        &vkfrags[0],
+    );
+    builder.add_destination(
+        &Address::new(b"00000000000000000002").unwrap(),
+        &SecretKey::random().public_key(),
+        &vkfrags[1],
+    );
+    let signer = Signer::new(&publisher_sk);
+    assert!(builder.build(&signer).is_err(), "Threshold exceeds shares");
+
+    builder.add_destination(
+        &Address::new(b"00000000000000000003").unwrap(),
+        &SecretKey::random().public_key(),
+        &vkfrags[2],
+    );
+    let treasure_map = builder.build(&signer).unwrap();
+    assert_eq!(treasure_map.threshold(), 3);
+}
+
+#[wasm_bindgen_test]
+fn treasure_map_amend() {
+    let publisher_sk = SecretKey::random();
+    let receiving_sk = SecretKey::random();
+    let treasure_map = make_treasure_map(&publisher_sk, &receiving_sk);
+    let vkfrags = make_kfrags(&publisher_sk, &receiving_sk);
+
+    let replacement = [
+        JsValue::from(Address::new(b"00000000000000000004").unwrap()),
+        JsValue::from(SecretKey::random().public_key()),
+        JsValue::from(vkfrags[0].clone()),
+    ]
+    .into_iter()
+    .collect::<js_sys::Array>();
+    let replacements = into_js_array([[
+        JsValue::from(Address::new(b"00000000000000000001").unwrap()),
+        JsValue::from(replacement),
+    ]
+    .into_iter()
+    .collect::<js_sys::Array>()]);
+
+    let amendment = treasure_map
+        .amend(
+            &Signer::new(&publisher_sk),
+            &receiving_sk.public_key(),
+            &replacements,
+            &PolicyValidity::new(2000, None),
+            1000,
+        )
+        .unwrap();
+    let amendment: js_sys::Array = JsValue::from(amendment).dyn_into().unwrap();
+    let amended = TreasureMap::try_from(&amendment.get(0)).unwrap();
+    assert_eq!(amended.revision(), 1);
+    assert_eq!(
+        try_from_js_array::<RevocationOrder>(amendment.get(1)).len(),
+        1
+    );
 }
 
 #[wasm_bindgen_test]
@@ -356,7 +617,9 @@ fn encrypted_treasure_map_from_bytes_to_bytes() {
     let receiving_sk = SecretKey::random();
     let treasure_map = make_treasure_map(&publisher_sk, &receiving_sk);
 
-    let encrypted = treasure_map.encrypt(&Signer::new(&publisher_sk), &receiving_sk.public_key());
+    let encrypted = treasure_map
+        .encrypt(&Signer::new(&publisher_sk), &receiving_sk.public_key())
+        .unwrap();
 
     assert_eq!(
         encrypted,
@@ -365,6 +628,33 @@ fn encrypted_treasure_map_from_bytes_to_bytes() {
     );
 }
 
+#[wasm_bindgen_test]
+fn multi_recipient_encrypted_treasure_map() {
+    let publisher_sk = SecretKey::random();
+    let receiving_sk = SecretKey::random();
+    let other_sk = SecretKey::random();
+    let treasure_map = make_treasure_map(&publisher_sk, &receiving_sk);
+
+    let recipient_keys = into_js_array([receiving_sk.public_key(), other_sk.public_key()]);
+    let encrypted = treasure_map
+        .encrypt_for_many(&Signer::new(&publisher_sk), &recipient_keys)
+        .unwrap();
+    assert_eq!(encrypted.recipients_count(), 2);
+    assert_eq!(
+        encrypted,
+        MultiRecipientEncryptedTreasureMap::from_bytes(&encrypted.to_bytes()).unwrap(),
+        "MultiRecipientEncryptedTreasureMap does not roundtrip"
+    );
+
+    for sk in [&receiving_sk, &other_sk] {
+        let decrypted = encrypted.decrypt(sk, &publisher_sk.public_key()).unwrap();
+        assert_eq!(
+            decrypted, treasure_map,
+            "Decrypted treasure map does not match"
+        );
+    }
+}
+
 //
 // ReencryptionRequest
 //
@@ -376,7 +666,7 @@ fn reencryption_request_from_bytes_to_bytes() {
     let plaintext = b"Hello, world!";
     let conditions = Some(&"{'hello': 'world'}");
     let message_kit = make_message_kit(&publisher_sk, plaintext, conditions);
-    let capsules = vec![message_kit.capsule()];
+    let capsules = [message_kit.capsule()];
 
     let hrac = make_hrac();
 
@@ -385,7 +675,16 @@ fn reencryption_request_from_bytes_to_bytes() {
     let receiving_pk = receiving_sk.public_key();
     let signer = Signer::new(&publisher_sk);
     let verified_kfrags = make_kfrags(&publisher_sk, &receiving_sk);
-    let encrypted_kfrag = EncryptedKeyFrag::new(&signer, &receiving_pk, &hrac, &verified_kfrags[0]);
+    let encrypted_kfrag = EncryptedKeyFrag::new(
+        &signer,
+        &receiving_pk,
+        &hrac,
+        &verified_kfrags[0],
+        &publisher_sk.public_key(),
+        &receiving_pk,
+        &PolicyValidity::new(2000, None),
+    )
+    .unwrap();
     let conditions: JsValue = Some(Conditions::new("{'some': 'condition'}")).into();
     let context: JsValue = Some(Context::new("{'user': 'context'}")).into();
 
@@ -441,28 +740,57 @@ fn reencryption_response_verify() {
         .map(|kfrag| reencrypt(&capsules[0], kfrag))
         .collect();
 
+    // Make the request the response is for
+    let encrypted_kfrag = EncryptedKeyFrag::new(
+        &Signer::new(&alice_sk),
+        &bob_sk.public_key(),
+        &make_hrac(),
+        &kfrags[0],
+        &policy_encrypting_key,
+        &bob_sk.public_key(),
+        &PolicyValidity::new(2000, None),
+    )
+    .unwrap();
+    let make_request = |conditions: JsValue| {
+        ReencryptionRequest::new(
+            &into_js_array(capsules.iter().cloned()),
+            &make_hrac(),
+            &encrypted_kfrag,
+            &alice_sk.public_key(),
+            &bob_sk.public_key(),
+            &conditions.unchecked_into::<OptionConditions>(),
+            &JsValue::NULL.unchecked_into::<OptionContext>(),
+        )
+        .unwrap()
+    };
+    let request = make_request(JsValue::NULL);
+
     // Make the reencryption response
     let ursula_sk = SecretKey::random();
     let signer = Signer::new(&ursula_sk);
 
-    let capsules_and_vcfrags_js =
-        into_js_array(capsules.iter().cloned().zip(vcfrags.iter().cloned()).map(
-            |(capsule, vcfrag)| {
-                [JsValue::from(capsule), JsValue::from(vcfrag)]
-                    .into_iter()
-                    .collect::<js_sys::Array>()
-            },
-        ));
     let reencryption_response =
-        ReencryptionResponse::new(&signer, &capsules_and_vcfrags_js).unwrap();
+        ReencryptionResponse::new(&signer, &request, &into_js_array(vcfrags.iter().cloned()))
+            .unwrap();
 
     // Now that the response is created, we're going to "send it" to the client and verify it
 
+    // The response does not verify for another request with the same capsules
+    let other_request = make_request(Some(Conditions::new("{'other': 'condition'}")).into());
+    assert!(reencryption_response
+        .verify(
+            &other_request,
+            &alice_sk.public_key(),
+            &ursula_sk.public_key(),
+            &policy_encrypting_key,
+            &bob_sk.public_key(),
+        )
+        .is_err());
+
     // Verify reencryption response
-    let capsules_js = into_js_array(capsules);
     let verified_array = reencryption_response
         .verify(
-            &capsules_js,
+            &request,
             &alice_sk.public_key(),
             &ursula_sk.public_key(),
             &policy_encrypting_key,
@@ -481,6 +809,42 @@ fn reencryption_response_verify() {
             .to_bytes(),
         "ReencryptionResponse does not roundtrip"
     );
+
+    // There must be exactly one result per capsule
+    assert!(ReencryptionResponse::new(
+        &signer,
+        &request,
+        &into_js_array(vcfrags[1..].iter().cloned())
+    )
+    .is_err());
+
+    // A response may refuse to reencrypt some of the capsules
+    let results = core::iter::once(JsValue::from(RefusalReason::ConditionsNotSatisfied))
+        .chain(vcfrags[1..].iter().cloned().map(JsValue::from));
+    let partial_response =
+        ReencryptionResponse::new(&signer, &request, &into_js_array(results)).unwrap();
+    let results_array: js_sys::Array = partial_response
+        .verify(
+            &request,
+            &alice_sk.public_key(),
+            &ursula_sk.public_key(),
+            &policy_encrypting_key,
+            &bob_sk.public_key(),
+        )
+        .unwrap()
+        .unchecked_into();
+    let refusal = results_array.get(0);
+    assert_eq!(
+        js_sys::Reflect::get(&refusal, &"code".into()).unwrap(),
+        JsValue::from(1401),
+        "Refusal does not carry its code"
+    );
+    let verified = results_array
+        .iter()
+        .skip(1)
+        .map(|js| VerifiedCapsuleFrag::try_from(&js).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(vcfrags[1..], verified, "Capsule fragments do not match");
 }
 
 //
@@ -535,6 +899,99 @@ fn retrieval_kit() {
     );
 }
 
+//
+// RetrievalPlan
+//
+
+#[wasm_bindgen_test]
+fn retrieval_plan() {
+    let publisher_sk = SecretKey::random();
+    let receiving_sk = SecretKey::random();
+    let treasure_map = make_treasure_map(&publisher_sk, &receiving_sk);
+    let message_kit = make_message_kit(&publisher_sk, b"Hello, world!", None::<&str>);
+    let retrieval_kits = into_js_array([RetrievalKit::from_message_kit(&message_kit)]);
+
+    let mut plan = RetrievalPlan::new(
+        &treasure_map,
+        &retrieval_kits,
+        &receiving_sk.public_key(),
+        &SecretKey::random().public_key(),
+        &JsValue::NULL.unchecked_into::<OptionContext>(),
+    )
+    .unwrap();
+
+    // Two Ursulas are asked, since the threshold is 2.
+    let requests: Vec<js_sys::Array> = js_sys::Array::from(&plan.next_requests())
+        .iter()
+        .map(|entry| entry.dyn_into().unwrap())
+        .collect();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(js_sys::Array::from(&plan.next_requests()).length(), 0);
+
+    // After a failure, the remaining Ursula is asked.
+    let failed_address = Address::try_from(&requests[0].get(0)).unwrap();
+    plan.report_failure(&failed_address);
+    assert_eq!(js_sys::Array::from(&plan.next_requests()).length(), 1);
+
+    assert!(!plan.is_decryptable(0));
+    assert!(!plan.is_complete());
+    assert!(!plan.is_exhausted());
+    assert_eq!(js_sys::Array::from(&plan.verified_cfrags(0)).length(), 0);
+}
+
+//
+// ReencryptionService
+//
+
+#[wasm_bindgen_test]
+fn reencryption_service() {
+    let publisher_sk = SecretKey::random();
+    let ursula_sk = SecretKey::random();
+    let ursula_signer = Signer::new(&SecretKey::random());
+    let message_kit = make_message_kit(&publisher_sk, b"Hello, world!", None::<&str>);
+    let hrac = make_hrac();
+    let encrypted_kfrag = EncryptedKeyFrag::new(
+        &Signer::new(&publisher_sk),
+        &ursula_sk.public_key(),
+        &hrac,
+        &make_kfrags(&publisher_sk, &SecretKey::random())[0],
+        &publisher_sk.public_key(),
+        &SecretKey::random().public_key(),
+        &PolicyValidity::new(2000, None),
+    )
+    .unwrap();
+    let make_request = |conditions: &str| {
+        let conditions: JsValue = Some(Conditions::new(conditions)).into();
+        ReencryptionRequest::new(
+            &into_js_array([message_kit.capsule()]),
+            &hrac,
+            &encrypted_kfrag,
+            &publisher_sk.public_key(),
+            &SecretKey::random().public_key(),
+            &conditions.unchecked_into::<OptionConditions>(),
+            &JsValue::NULL.unchecked_into::<OptionContext>(),
+        )
+        .unwrap()
+    };
+
+    let revocation_checker = js_sys::Function::new_with_args("hrac, ekfrag", "return false;");
+    let condition_evaluator = js_sys::Function::new_with_args(
+        "conditions, context",
+        "return conditions.toString() === 'allow';",
+    );
+    let service = ReencryptionService::new(
+        &ursula_signer,
+        &ursula_sk,
+        revocation_checker.unchecked_into::<RevocationCheckerFunction>(),
+        condition_evaluator.unchecked_into::<ConditionEvaluatorFunction>(),
+    );
+
+    assert!(service.handle(&make_request("allow"), 1000).is_ok());
+    assert!(service.handle(&make_request("deny"), 1000).is_err());
+    // The policy has expired.
+    assert!(service.handle(&make_request("allow"), 3000).is_err());
+}
+
 //
 // RevocationOrder
 //
@@ -548,13 +1005,43 @@ fn revocation_order() {
     let hrac = make_hrac();
     let receiving_pk = receiving_sk.public_key();
     let signer = Signer::new(&delegating_sk);
-    let encrypted_kfrag = EncryptedKeyFrag::new(&signer, &receiving_pk, &hrac, &verified_kfrags[0]);
+    let encrypted_kfrag = EncryptedKeyFrag::new(
+        &signer,
+        &receiving_pk,
+        &hrac,
+        &verified_kfrags[0],
+        &delegating_sk.public_key(),
+        &receiving_pk,
+        &PolicyValidity::new(2000, None),
+    )
+    .unwrap();
 
     let ursula_address = Address::new(b"00000000000000000001").unwrap();
-    let revocation_order =
-        RevocationOrder::new(&signer, &ursula_address, &encrypted_kfrag).unwrap();
+    let revocation_order = RevocationOrder::new(
+        &signer,
+        &ursula_address,
+        &encrypted_kfrag,
+        &hrac,
+        1000,
+        Some(1),
+    )
+    .unwrap();
 
     assert!(revocation_order.verify(&delegating_sk.public_key()).is_ok());
+    assert_eq!(revocation_order.hrac().to_bytes(), hrac.to_bytes());
+    assert_eq!(revocation_order.issued_at(), 1000.0);
+    assert_eq!(revocation_order.reason(), Some(1));
+
+    // Ursula acknowledges the order
+    let ursula_signer = Signer::new(&SecretKey::random());
+    let receipt = RevocationReceipt::new(&ursula_signer, &revocation_order, 1100);
+    let receipt = RevocationReceipt::from_bytes(&receipt.to_bytes()).unwrap();
+    assert_eq!(
+        receipt
+            .verify(&ursula_signer.verifying_key(), &revocation_order)
+            .unwrap(),
+        1100.0
+    );
 
     let as_bytes = revocation_order.to_bytes();
     assert_eq!(
@@ -564,6 +1051,162 @@ fn revocation_order() {
     );
 }
 
+//
+// RevocationRegistry
+//
+
+#[wasm_bindgen_test]
+fn revocation_registry() {
+    let publisher_sk = SecretKey::random();
+    let ursula_sk = SecretKey::random();
+    let verified_kfrags = make_kfrags(&publisher_sk, &SecretKey::random());
+
+    let hrac = make_hrac();
+    let signer = Signer::new(&publisher_sk);
+    let encrypted_kfrag = EncryptedKeyFrag::new(
+        &signer,
+        &ursula_sk.public_key(),
+        &hrac,
+        &verified_kfrags[0],
+        &publisher_sk.public_key(),
+        &SecretKey::random().public_key(),
+        &PolicyValidity::new(2000, None),
+    )
+    .unwrap();
+    let ursula_address = Address::new(b"00000000000000000001").unwrap();
+    let other_address = Address::new(b"00000000000000000002").unwrap();
+
+    let mut registry = InMemoryRevocationRegistry::new();
+
+    let order =
+        RevocationOrder::new(&signer, &other_address, &encrypted_kfrag, &hrac, 1000, None).unwrap();
+    assert!(registry
+        .revoke(
+            &order,
+            &publisher_sk.public_key(),
+            &ursula_address,
+            &ursula_sk,
+            1000
+        )
+        .is_err());
+    assert!(!registry.is_revoked(&hrac, &encrypted_kfrag));
+
+    let order = RevocationOrder::new(
+        &signer,
+        &ursula_address,
+        &encrypted_kfrag,
+        &hrac,
+        1000,
+        None,
+    )
+    .unwrap();
+    registry
+        .revoke(
+            &order,
+            &publisher_sk.public_key(),
+            &ursula_address,
+            &ursula_sk,
+            1000,
+        )
+        .unwrap();
+    assert!(registry.is_revoked(&hrac, &encrypted_kfrag));
+    assert_eq!(registry.length(), 1);
+
+    // A replayed order is rejected
+    assert!(registry
+        .revoke(
+            &order,
+            &publisher_sk.public_key(),
+            &ursula_address,
+            &ursula_sk,
+            1100
+        )
+        .is_err());
+
+    let snapshot = RevocationRegistrySnapshot::from_bytes(&registry.snapshot().to_bytes()).unwrap();
+    let restored = InMemoryRevocationRegistry::from_snapshot(&snapshot);
+    assert!(restored.is_revoked(&hrac, &encrypted_kfrag));
+
+    // A policy-wide certificate
+    let destinations: DestinationsArray = into_js_array([into_js_array::<js_sys::Array, _>([
+        JsValue::from(ursula_address.clone()),
+        JsValue::from(encrypted_kfrag.clone()),
+    ])]);
+    let certificate = RevocationCertificate::new(&signer, &hrac, &destinations, 1000).unwrap();
+    let certificate = RevocationCertificate::from_bytes(&certificate.to_bytes()).unwrap();
+    assert_eq!(certificate.hrac().to_bytes(), hrac.to_bytes());
+    assert_eq!(certificate.issued_at(), 1000.0);
+    assert!(certificate.verify(&publisher_sk.public_key()).is_ok());
+    assert!(certificate
+        .verify(&SecretKey::random().public_key())
+        .is_err());
+
+    let mut registry = InMemoryRevocationRegistry::new();
+    registry
+        .revoke_policy(
+            &certificate,
+            &publisher_sk.public_key(),
+            &ursula_address,
+            &ursula_sk,
+            1000,
+        )
+        .unwrap();
+    assert!(registry.is_revoked(&hrac, &encrypted_kfrag));
+}
+
+//
+// PolicyGrant
+//
+
+#[wasm_bindgen_test]
+fn policy_grant() {
+    let publisher_sk = SecretKey::random();
+    let delegating_sk = SecretKey::random();
+    let bob_sk = SecretKey::random();
+    let signer = Signer::new(&publisher_sk);
+    let ursulas = into_js_array([
+        make_node_metadata_at(b"00000000000000000001"),
+        make_node_metadata_at(b"00000000000000000002"),
+        make_node_metadata_at(b"00000000000000000003"),
+    ]);
+
+    let grant = PolicyGrant::new(
+        &signer,
+        &delegating_sk,
+        &bob_sk.public_key(),
+        &SecretKey::random().public_key(),
+        b"label",
+        2,
+        &ursulas,
+        &PolicyValidity::new(2000, None),
+        1000,
+    )
+    .unwrap();
+    assert_eq!(
+        grant.policy_encrypting_key().to_compressed_bytes(),
+        delegating_sk.public_key().to_compressed_bytes()
+    );
+
+    let treasure_map = grant
+        .encrypted_treasure_map()
+        .decrypt(&bob_sk, &publisher_sk.public_key())
+        .unwrap();
+    assert_eq!(treasure_map.hrac().to_bytes(), grant.hrac().to_bytes());
+
+    let revocation_kit = grant.revocation_kit();
+    let revocation_orders: Vec<js_sys::Array> =
+        js_sys::Array::from(&revocation_kit.revocation_orders())
+            .iter()
+            .map(|entry| entry.dyn_into().unwrap())
+            .collect();
+    assert_eq!(revocation_orders.len(), 3);
+    assert_eq!(
+        revocation_kit,
+        RevocationKit::from_bytes(&revocation_kit.to_bytes()).unwrap(),
+        "RevocationKit does not roundtrip"
+    );
+}
+
 //
 // NodeMetadataPayload
 //
@@ -608,7 +1251,7 @@ fn fleet_state_checksum_to_bytes() {
     let fleet_state_checksum = make_fleet_state_checksum();
 
     assert!(
-        fleet_state_checksum.to_bytes().len() > 0,
+        !fleet_state_checksum.to_bytes().is_empty(),
         "FleetStateChecksum does not serialize to bytes"
     );
 }
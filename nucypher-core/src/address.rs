@@ -10,7 +10,8 @@ use umbral_pre::{serde_bytes, PublicKey};
 // We could use the third-party `ethereum_types::Address` here,
 // but it has an inefficient `serde` implementation (serializes as hex instead of bytes).
 // So for simplicity we just use our own type since we only need the size check.
-// Later a conversion method can be easily defined to/from `ethereum_types::Address`.
+// Conversions to/from `ethereum_types::Address` and `alloy_primitives::Address`
+// are available with the corresponding features enabled (see `eth_interop`).
 
 /// Represents an Ethereum address (20 bytes).
 #[derive(PartialEq, Debug, Serialize, Deserialize, Copy, Clone, PartialOrd, Eq, Ord)]
@@ -0,0 +1,329 @@
+//! Canonical form of conditions and complexity limits.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde_json::{Map, Number, Value};
+use sha3::{Digest, Keccak256};
+
+use crate::condition_evaluation::Uint256;
+use crate::conditions::{Conditions, ConditionsError};
+
+/// Limits on the complexity of conditions accepted by protocol objects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConditionsLimits {
+    /// Maximum nesting depth of compound conditions.
+    pub max_depth: usize,
+    /// Maximum number of operands of a single compound condition.
+    pub max_operands: usize,
+    /// Maximum size of the conditions string in bytes.
+    pub max_size: usize,
+}
+
+impl ConditionsLimits {
+    /// The limits enforced when conditions enter
+    /// `MessageKit`, `RetrievalKit` and `ReencryptionRequest`.
+    pub const DEFAULT: Self = Self {
+        max_depth: 4,
+        max_operands: 16,
+        max_size: 16 * 1024,
+    };
+}
+
+impl Default for ConditionsLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+fn canonical_number(number: &Number) -> Number {
+    // Floats with no fractional part are equivalent to integers.
+    match number.as_f64() {
+        Some(float) if number.is_f64() && float.fract() == 0.0 => {
+            if float >= 0.0 && float < u64::MAX as f64 {
+                Number::from(float as u64)
+            } else if float < 0.0 && float >= i64::MIN as f64 {
+                Number::from(float as i64)
+            } else {
+                number.clone()
+            }
+        }
+        _ => number.clone(),
+    }
+}
+
+fn canonical_value(value: Value) -> Value {
+    match value {
+        Value::Number(number) => Value::Number(canonical_number(&number)),
+        Value::Array(items) => Value::Array(items.into_iter().map(canonical_value).collect()),
+        Value::Object(object) => {
+            // Sort explicitly, in case `serde_json` is built with `preserve_order`.
+            let mut entries = object.into_iter().collect::<Vec<_>>();
+            entries.sort_by(|(key1, _), (key2, _)| key1.cmp(key2));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let value = if key == "returnValueTest" {
+                            canonical_return_value_test(value)
+                        } else {
+                            canonical_value(value)
+                        };
+                        (key, value)
+                    })
+                    .collect::<Map<String, Value>>(),
+            )
+        }
+        value => value,
+    }
+}
+
+/// Normalizes the expected value of a return value test, which the evaluator compares
+/// as an unsigned integer whether it is given as a number, a decimal or a hex string.
+fn canonical_return_value_test(test: Value) -> Value {
+    match canonical_value(test) {
+        Value::Object(mut object) => {
+            if let Some(value) = object.get_mut("value") {
+                if let Some(uint) = Uint256::from_value(value) {
+                    *value = uint.to_canonical_value();
+                }
+            }
+            Value::Object(object)
+        }
+        test => test,
+    }
+}
+
+/// Returns the nesting depth of compound conditions and the largest number of operands.
+fn complexity(value: &Value) -> (usize, usize) {
+    let operands = match value
+        .get("operands")
+        .and_then(|operands| operands.as_array())
+    {
+        Some(operands) => operands,
+        None => {
+            return match value.get("condition") {
+                Some(condition) => complexity(condition),
+                None => (0, 0),
+            }
+        }
+    };
+    operands.iter().map(complexity).fold(
+        (1, operands.len()),
+        |(depth, width), (op_depth, op_width)| (depth.max(op_depth + 1), width.max(op_width)),
+    )
+}
+
+impl Conditions {
+    /// Returns the canonical form of the conditions:
+    /// sorted keys, integral numbers without a fractional part, and no whitespace.
+    ///
+    /// Expected values of return value tests that are unsigned integers
+    /// (numbers, decimal or hex strings) are written as numbers if they fit into `u64`,
+    /// and as decimal strings otherwise.
+    pub fn canonical(&self) -> Result<Self, ConditionsError> {
+        let value: Value = serde_json::from_str(self.as_ref())
+            .map_err(|err| ConditionsError::Malformed(err.to_string()))?;
+        Ok(Self::new(&canonical_value(value).to_string()))
+    }
+
+    /// Returns the Keccak-256 hash of the canonical form of the conditions.
+    ///
+    /// Semantically identical conditions have the same hash.
+    pub fn hash(&self) -> Result<[u8; 32], ConditionsError> {
+        let canonical = self.canonical()?;
+        Ok(Keccak256::digest(canonical.as_ref().as_bytes()).into())
+    }
+
+    /// Checks that the conditions do not exceed the given limits.
+    ///
+    /// The structural limits are only checked if the conditions are valid JSON.
+    pub fn check_limits(&self, limits: &ConditionsLimits) -> Result<(), ConditionsError> {
+        let size = self.as_ref().len();
+        if size > limits.max_size {
+            return Err(ConditionsError::LimitExceeded {
+                limit: "size",
+                value: size,
+                max: limits.max_size,
+            });
+        }
+
+        let value: Value = match serde_json::from_str(self.as_ref()) {
+            Ok(value) => value,
+            Err(_) => return Ok(()),
+        };
+        let (depth, operands) = complexity(&value);
+        if depth > limits.max_depth {
+            return Err(ConditionsError::LimitExceeded {
+                limit: "depth",
+                value: depth,
+                max: limits.max_depth,
+            });
+        }
+        if operands > limits.max_operands {
+            return Err(ConditionsError::LimitExceeded {
+                limit: "operands",
+                value: operands,
+                max: limits.max_operands,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Checks optional conditions against the default limits.
+pub(crate) fn check_default_limits(conditions: Option<&Conditions>) -> Result<(), ConditionsError> {
+    conditions.map_or(Ok(()), |conditions| {
+        conditions.check_limits(&ConditionsLimits::DEFAULT)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use serde_json::json;
+
+    use super::ConditionsLimits;
+    use crate::{Comparator, Condition, Conditions, ConditionsError};
+
+    #[test]
+    fn canonical_form() {
+        let conditions1 = Conditions::new(
+            r#"{ "version": "1.0.0", "condition": { "method": "blocktime", "chain": 1.0,
+                "returnValueTest": { "value": 100, "comparator": ">" },
+                "conditionType": "time" } }"#,
+        );
+        let conditions2 = Condition::block_time(1, Comparator::Gt, 100)
+            .build()
+            .unwrap();
+        assert_ne!(conditions1, conditions2);
+        assert_eq!(conditions1.canonical().unwrap(), conditions2);
+
+        let canonical = conditions1.canonical().unwrap();
+        assert_eq!(canonical, conditions2.canonical().unwrap());
+        assert_eq!(
+            canonical.as_ref(),
+            r#"{"condition":{"chain":1,"conditionType":"time","method":"blocktime","returnValueTest":{"comparator":">","value":100}},"version":"1.0.0"}"#
+        );
+        assert_eq!(conditions1.hash(), conditions2.hash());
+        assert!(Conditions::new("{'llamas': 'yes'}").hash().is_err());
+    }
+
+    #[test]
+    fn canonical_number_bounds() {
+        // 2^64 is not representable as `u64` and must not saturate to `u64::MAX`.
+        let conditions = Conditions::new(r#"{"value":18446744073709551616.0}"#);
+        assert_ne!(
+            conditions.canonical().unwrap().as_ref(),
+            r#"{"value":18446744073709551615}"#
+        );
+        let conditions = Conditions::new(r#"{"value":9007199254740992.0}"#);
+        assert_eq!(
+            conditions.canonical().unwrap().as_ref(),
+            r#"{"value":9007199254740992}"#
+        );
+    }
+
+    #[test]
+    fn canonical_return_values() {
+        let with_value = |value: serde_json::Value| {
+            Conditions::new(
+                &json!({"version": "1.0.0", "condition": {
+                    "conditionType": "time",
+                    "chain": 1,
+                    "method": "blocktime",
+                    "returnValueTest": {"comparator": ">", "value": value}
+                }})
+                .to_string(),
+            )
+        };
+
+        // Literals the evaluator compares as the same integer have the same hash
+        let number = with_value(json!(100));
+        assert_eq!(number.hash(), with_value(json!("100")).hash());
+        assert_eq!(number.hash(), with_value(json!("0x64")).hash());
+        assert_eq!(number.hash(), with_value(json!("0100")).hash());
+
+        let large = with_value(json!("1000000000000000000000"));
+        assert_eq!(
+            large.hash(),
+            with_value(json!("0x3635C9ADC5DEA00000")).hash()
+        );
+        assert!(large
+            .canonical()
+            .unwrap()
+            .as_ref()
+            .contains(r#""value":"1000000000000000000000""#));
+
+        // Other strings are kept as they are
+        assert_ne!(
+            with_value(json!("llama")).hash(),
+            with_value(json!("Llama")).hash()
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let leaf = Condition::block_time(1, Comparator::Gt, 100);
+        let mut condition = leaf.clone();
+        for _ in 0..4 {
+            condition = condition.and(leaf.clone()).negate();
+        }
+        let conditions = condition.build().unwrap();
+
+        let limits = ConditionsLimits {
+            max_depth: 8,
+            ..ConditionsLimits::DEFAULT
+        };
+        assert_eq!(conditions.check_limits(&limits), Ok(()));
+        assert_eq!(
+            conditions.check_limits(&ConditionsLimits::DEFAULT),
+            Err(ConditionsError::LimitExceeded {
+                limit: "depth",
+                value: 8,
+                max: 4
+            })
+        );
+
+        let wide = Conditions::new(
+            &json!({"version": "1.0.0", "condition": {
+                "conditionType": "compound",
+                "operator": "or",
+                "operands": [leaf_json(), leaf_json(), leaf_json()]
+            }})
+            .to_string(),
+        );
+        let limits = ConditionsLimits {
+            max_operands: 2,
+            ..ConditionsLimits::DEFAULT
+        };
+        assert!(matches!(
+            wide.check_limits(&limits),
+            Err(ConditionsError::LimitExceeded {
+                limit: "operands",
+                ..
+            })
+        ));
+
+        let limits = ConditionsLimits {
+            max_size: 8,
+            ..ConditionsLimits::DEFAULT
+        };
+        assert!(matches!(
+            Conditions::new("{'llamas': 'yes'}").check_limits(&limits),
+            Err(ConditionsError::LimitExceeded { limit: "size", .. })
+        ));
+    }
+
+    fn leaf_json() -> serde_json::Value {
+        serde_json::from_str(
+            Condition::block_time(1, Comparator::Gt, 100)
+                .build()
+                .unwrap()
+                .as_ref(),
+        )
+        .map(|lingo: serde_json::Value| lingo["condition"].clone())
+        .unwrap()
+    }
+}
@@ -0,0 +1,316 @@
+//! Constructors producing canonical condition trees.
+//!
+//! All the language bindings go through these methods, so the same conditions
+//! built in Rust, Python or JS serialize into byte-identical strings.
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use serde_json::Value;
+
+use crate::conditions::{
+    Comparator, CompoundCondition, CompoundOperator, Condition, ConditionLingo, Conditions,
+    ConditionsError, ContractCondition, ReturnValueTest, RpcCondition, StandardContractType,
+    TimeCondition,
+};
+use crate::context_variables::USER_ADDRESS_CONTEXT_VARIABLE;
+
+impl FromStr for Comparator {
+    type Err = ConditionsError;
+
+    fn from_str(symbol: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(Value::from(symbol))
+            .map_err(|_err| ConditionsError::Malformed(["unknown comparator ", symbol].concat()))
+    }
+}
+
+/// Integers that do not fit into a JSON number without loss of precision
+/// are represented as decimal strings.
+fn uint_value(value: u128) -> Value {
+    match u64::try_from(value) {
+        Ok(value) => Value::from(value),
+        Err(_) => Value::from(value.to_string()),
+    }
+}
+
+impl ReturnValueTest {
+    /// Creates a test comparing the whole returned value.
+    pub fn new(comparator: Comparator, value: Value) -> Self {
+        Self {
+            comparator,
+            value,
+            index: None,
+        }
+    }
+}
+
+fn user_address() -> Value {
+    Value::from(USER_ADDRESS_CONTEXT_VARIABLE)
+}
+
+impl Condition {
+    /// Compares the timestamp of the latest block on the given chain with `timestamp`.
+    pub fn block_time(chain: u64, comparator: Comparator, timestamp: u64) -> Self {
+        Self::Time(TimeCondition {
+            chain,
+            method: "blocktime".into(),
+            return_value_test: ReturnValueTest::new(comparator, Value::from(timestamp)),
+        })
+    }
+
+    /// Compares the requester's native token balance with `amount`.
+    pub fn native_balance(chain: u64, comparator: Comparator, amount: u128) -> Self {
+        Self::Rpc(RpcCondition {
+            chain,
+            method: "eth_getBalance".into(),
+            parameters: vec![user_address(), Value::from("latest")],
+            return_value_test: ReturnValueTest::new(comparator, uint_value(amount)),
+        })
+    }
+
+    fn standard_contract(
+        chain: u64,
+        contract_address: &str,
+        contract_type: StandardContractType,
+        method: &str,
+        parameters: Vec<Value>,
+        return_value_test: ReturnValueTest,
+    ) -> Self {
+        Self::Contract(ContractCondition {
+            chain,
+            contract_address: contract_address.into(),
+            standard_contract_type: Some(contract_type),
+            function_abi: None,
+            method: method.into(),
+            parameters,
+            return_value_test,
+        })
+    }
+
+    /// Compares the requester's balance of an ERC20 token with `amount`.
+    pub fn erc20_balance(
+        chain: u64,
+        contract_address: &str,
+        comparator: Comparator,
+        amount: u128,
+    ) -> Self {
+        Self::standard_contract(
+            chain,
+            contract_address,
+            StandardContractType::ERC20,
+            "balanceOf",
+            vec![user_address()],
+            ReturnValueTest::new(comparator, uint_value(amount)),
+        )
+    }
+
+    /// Compares the number of ERC721 tokens owned by the requester with `amount`.
+    pub fn erc721_balance(
+        chain: u64,
+        contract_address: &str,
+        comparator: Comparator,
+        amount: u128,
+    ) -> Self {
+        Self::standard_contract(
+            chain,
+            contract_address,
+            StandardContractType::ERC721,
+            "balanceOf",
+            vec![user_address()],
+            ReturnValueTest::new(comparator, uint_value(amount)),
+        )
+    }
+
+    /// Requires the requester to own the ERC721 token with the given ID.
+    pub fn erc721_ownership(chain: u64, contract_address: &str, token_id: u128) -> Self {
+        Self::standard_contract(
+            chain,
+            contract_address,
+            StandardContractType::ERC721,
+            "ownerOf",
+            vec![uint_value(token_id)],
+            ReturnValueTest::new(Comparator::Eq, user_address()),
+        )
+    }
+
+    /// Tests the result of an arbitrary contract method described by `function_abi`.
+    pub fn contract_call(
+        chain: u64,
+        contract_address: &str,
+        function_abi: Value,
+        parameters: Vec<Value>,
+        return_value_test: ReturnValueTest,
+    ) -> Self {
+        let method = function_abi
+            .get("name")
+            .and_then(|name| name.as_str())
+            .map(String::from)
+            .unwrap_or_default();
+        Self::Contract(ContractCondition {
+            chain,
+            contract_address: contract_address.into(),
+            standard_contract_type: None,
+            function_abi: Some(function_abi),
+            method,
+            parameters,
+            return_value_test,
+        })
+    }
+
+    fn compound(self, operator: CompoundOperator, other: Self) -> Self {
+        // Nested operations of the same kind are flattened,
+        // so that the result does not depend on the order of composition.
+        let mut operands = Vec::new();
+        for condition in [self, other] {
+            match condition {
+                Self::Compound(compound) if compound.operator == operator => {
+                    operands.extend(compound.operands)
+                }
+                condition => operands.push(condition),
+            }
+        }
+        Self::Compound(CompoundCondition { operator, operands })
+    }
+
+    /// Requires both this and the `other` condition to be satisfied.
+    pub fn and(self, other: Self) -> Self {
+        self.compound(CompoundOperator::And, other)
+    }
+
+    /// Requires either this or the `other` condition to be satisfied.
+    pub fn or(self, other: Self) -> Self {
+        self.compound(CompoundOperator::Or, other)
+    }
+
+    /// Requires this condition not to be satisfied.
+    pub fn negate(self) -> Self {
+        match self {
+            // Double negation cancels out.
+            Self::Compound(CompoundCondition {
+                operator: CompoundOperator::Not,
+                mut operands,
+            }) if operands.len() == 1 => operands.remove(0),
+            condition => Self::Compound(CompoundCondition {
+                operator: CompoundOperator::Not,
+                operands: vec![condition],
+            }),
+        }
+    }
+
+    /// Validates the condition tree and serializes it into the canonical form
+    /// of [`Conditions`] (see [`Conditions::canonical`]).
+    pub fn build(self) -> Result<Conditions, ConditionsError> {
+        let lingo = ConditionLingo::new(self);
+        lingo.validate()?;
+        Conditions::from(&lingo).canonical()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use serde_json::{json, Value};
+
+    use crate::{Comparator, Condition, ConditionsError, ReturnValueTest};
+
+    const CONTRACT: &str = "0x1e6F4Bd5E3f8A2f1aFbF4E6D6B3bA1c0D9e2F3a4";
+
+    #[test]
+    fn build_canonical() {
+        let conditions = Condition::block_time(1, Comparator::Ge, 1700000000)
+            .and(Condition::erc721_ownership(137, CONTRACT, 5))
+            .and(Condition::native_balance(1, Comparator::Gt, u128::MAX).negate())
+            .build()
+            .unwrap();
+
+        assert!(conditions
+            .as_ref()
+            .starts_with(r#"{"condition":{"conditionType":"compound","operands":["#));
+        assert_eq!(conditions.canonical().unwrap(), conditions);
+        assert_eq!(
+            serde_json::from_str::<Value>(conditions.as_ref()).unwrap(),
+            json!({
+                "version": "1.0.0",
+                "condition": {
+                    "conditionType": "compound",
+                    "operator": "and",
+                    "operands": [
+                        {
+                            "conditionType": "time",
+                            "chain": 1,
+                            "method": "blocktime",
+                            "returnValueTest": {"comparator": ">=", "value": 1700000000u64}
+                        },
+                        {
+                            "conditionType": "contract",
+                            "chain": 137,
+                            "contractAddress": CONTRACT,
+                            "standardContractType": "ERC721",
+                            "method": "ownerOf",
+                            "parameters": [5],
+                            "returnValueTest": {"comparator": "==", "value": ":userAddress"}
+                        },
+                        {
+                            "conditionType": "compound",
+                            "operator": "not",
+                            "operands": [{
+                                "conditionType": "rpc",
+                                "chain": 1,
+                                "method": "eth_getBalance",
+                                "parameters": [":userAddress", "latest"],
+                                "returnValueTest": {
+                                    "comparator": ">",
+                                    "value": "340282366920938463463374607431768211455"
+                                }
+                            }]
+                        }
+                    ]
+                }
+            })
+        );
+
+        // Composition order does not affect the result
+        let a = Condition::erc20_balance(1, CONTRACT, Comparator::Gt, 0);
+        let b = Condition::erc721_balance(1, CONTRACT, Comparator::Gt, 0);
+        let c = Condition::block_time(1, Comparator::Lt, 1);
+        assert_eq!(
+            a.clone().and(b.clone()).and(c.clone()).build(),
+            a.and(b.and(c)).build()
+        );
+        assert_eq!("<=".parse::<Comparator>(), Ok(Comparator::Le));
+    }
+
+    #[test]
+    fn build_custom_call() {
+        let abi = json!({
+            "name": "isMember",
+            "type": "function",
+            "inputs": [{"name": "account", "type": "address"}],
+            "outputs": [{"name": "", "type": "bool"}],
+            "stateMutability": "view"
+        });
+        let condition = Condition::contract_call(
+            1,
+            CONTRACT,
+            abi,
+            vec![json!(":userAddress")],
+            ReturnValueTest::new(Comparator::Eq, json!(true)),
+        );
+        assert!(condition.clone().build().is_ok());
+
+        let invalid = Condition::contract_call(
+            1,
+            "not an address",
+            json!({"name": "isMember"}),
+            vec![],
+            ReturnValueTest::new(Comparator::Eq, json!(true)),
+        );
+        assert!(matches!(
+            invalid.build(),
+            Err(ConditionsError::Invalid { .. })
+        ));
+    }
+}
@@ -0,0 +1,575 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+
+use serde_json::Value;
+
+use crate::conditions::{
+    Comparator, CompoundOperator, Condition, ConditionLingo, ConditionsError, ContractCondition,
+    ReturnValueTest, RpcCondition,
+};
+
+/// A source of the on-chain state required to evaluate conditions.
+///
+/// Implementations are expected to return values as they would appear in a JSON RPC response
+/// (e.g. large integers as decimal or `0x`-prefixed hex strings).
+pub trait ChainStateProvider {
+    /// Returns the timestamp of the latest block on the given chain.
+    fn block_time(&self, chain: u64) -> Result<u64, String>;
+
+    /// Calls a contract method described by `condition` with the given parameters.
+    fn call_contract(
+        &self,
+        condition: &ContractCondition,
+        parameters: &[Value],
+    ) -> Result<Value, String>;
+
+    /// Performs an RPC call described by `condition` with the given parameters.
+    fn call_rpc(&self, condition: &RpcCondition, parameters: &[Value]) -> Result<Value, String>;
+}
+
+/// An in-memory [`ChainStateProvider`] with predefined responses, intended for testing.
+#[derive(Debug, Clone, Default)]
+pub struct MockChainState {
+    block_times: BTreeMap<u64, u64>,
+    contract_calls: BTreeMap<(u64, String, String, String), Value>,
+    rpc_calls: BTreeMap<(u64, String, String), Value>,
+}
+
+fn parameters_key(parameters: &[Value]) -> String {
+    serde_json::to_string(parameters).expect("Error serializing parameters into JSON")
+}
+
+impl MockChainState {
+    /// Creates an empty chain state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the latest block time for the given chain.
+    pub fn set_block_time(&mut self, chain: u64, timestamp: u64) {
+        self.block_times.insert(chain, timestamp);
+    }
+
+    /// Sets the value returned by a contract call with the given parameters.
+    pub fn set_contract_call(
+        &mut self,
+        chain: u64,
+        contract_address: &str,
+        method: &str,
+        parameters: &[Value],
+        result: Value,
+    ) {
+        self.contract_calls.insert(
+            (
+                chain,
+                contract_address.to_ascii_lowercase(),
+                method.into(),
+                parameters_key(parameters),
+            ),
+            result,
+        );
+    }
+
+    /// Sets the value returned by an RPC call with the given parameters.
+    pub fn set_rpc_call(&mut self, chain: u64, method: &str, parameters: &[Value], result: Value) {
+        self.rpc_calls
+            .insert((chain, method.into(), parameters_key(parameters)), result);
+    }
+}
+
+impl ChainStateProvider for MockChainState {
+    fn block_time(&self, chain: u64) -> Result<u64, String> {
+        self.block_times
+            .get(&chain)
+            .copied()
+            .ok_or_else(|| format!("no block time set for chain {}", chain))
+    }
+
+    fn call_contract(
+        &self,
+        condition: &ContractCondition,
+        parameters: &[Value],
+    ) -> Result<Value, String> {
+        let key = (
+            condition.chain,
+            condition.contract_address.to_ascii_lowercase(),
+            condition.method.clone(),
+            parameters_key(parameters),
+        );
+        self.contract_calls.get(&key).cloned().ok_or_else(|| {
+            format!(
+                "no result set for {}.{} on chain {}",
+                condition.contract_address, condition.method, condition.chain
+            )
+        })
+    }
+
+    fn call_rpc(&self, condition: &RpcCondition, parameters: &[Value]) -> Result<Value, String> {
+        let key = (
+            condition.chain,
+            condition.method.clone(),
+            parameters_key(parameters),
+        );
+        self.rpc_calls.get(&key).cloned().ok_or_else(|| {
+            format!(
+                "no result set for {} on chain {}",
+                condition.method, condition.chain
+            )
+        })
+    }
+}
+
+/// An error that prevented the conditions from being evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvaluationError {
+    /// The condition tree did not pass validation.
+    InvalidConditions(ConditionsError),
+    /// The chain state provider returned an error.
+    ProviderFailed {
+        /// The location of the condition being evaluated.
+        path: String,
+        /// The error returned by the provider.
+        error: String,
+    },
+    /// The return value test refers to an element that is not present in the returned value.
+    IndexOutOfRange {
+        /// The location of the condition being evaluated.
+        path: String,
+        /// The index from the return value test.
+        index: u32,
+    },
+    /// The returned value and the expected value cannot be compared with the given comparator.
+    IncomparableValues {
+        /// The location of the condition being evaluated.
+        path: String,
+        /// The value returned by the provider.
+        returned: String,
+        /// The value from the return value test.
+        expected: String,
+    },
+}
+
+impl fmt::Display for EvaluationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidConditions(_) => write!(f, "invalid conditions"),
+            Self::ProviderFailed { path, error } => {
+                write!(f, "chain state request failed at {}: {}", path, error)
+            }
+            Self::IndexOutOfRange { path, index } => {
+                write!(f, "index {} out of range for the value at {}", index, path)
+            }
+            Self::IncomparableValues {
+                path,
+                returned,
+                expected,
+            } => write!(
+                f,
+                "cannot compare {} with {} at {}",
+                returned, expected, path
+            ),
+        }
+    }
+}
+
+/// The outcome of evaluating a single node of the condition tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// The location of the condition (e.g. `condition.operands[1]`).
+    pub path: String,
+    /// Whether the condition was satisfied.
+    pub satisfied: bool,
+    /// Whether the condition is a leaf (that is, not a compound condition).
+    pub is_leaf: bool,
+    /// A human-readable description of the check that was performed.
+    pub description: String,
+}
+
+/// The result of evaluating a condition tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionEvaluation {
+    /// Whether the whole condition tree was satisfied.
+    pub satisfied: bool,
+    /// The outcomes of the evaluated nodes, in the order of evaluation.
+    ///
+    /// Compound conditions are short-circuited,
+    /// so the operands that did not affect the result are not listed.
+    pub trace: Vec<TraceEntry>,
+}
+
+impl ConditionEvaluation {
+    /// Returns the trace entries for the leaf conditions that were not satisfied.
+    pub fn failures(&self) -> impl Iterator<Item = &TraceEntry> {
+        self.trace
+            .iter()
+            .filter(|entry| entry.is_leaf && !entry.satisfied)
+    }
+}
+
+/// A 256-bit unsigned integer, stored as big-endian bytes so that it can be compared directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Uint256([u8; 32]);
+
+impl Uint256 {
+    fn from_u64(value: u64) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[24..].copy_from_slice(&value.to_be_bytes());
+        Self(bytes)
+    }
+
+    fn from_hex(hex_str: &str) -> Option<Self> {
+        if hex_str.is_empty() || hex_str.len() > 64 {
+            return None;
+        }
+        let padded = format!("{:0>64}", hex_str);
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(padded, &mut bytes).ok()?;
+        Some(Self(bytes))
+    }
+
+    fn from_decimal(dec_str: &str) -> Option<Self> {
+        if dec_str.is_empty() {
+            return None;
+        }
+        let mut bytes = [0u8; 32];
+        for c in dec_str.chars() {
+            let mut carry = c.to_digit(10)?;
+            for byte in bytes.iter_mut().rev() {
+                let value = (*byte as u32) * 10 + carry;
+                *byte = (value & 0xff) as u8;
+                carry = value >> 8;
+            }
+            if carry != 0 {
+                // Overflow
+                return None;
+            }
+        }
+        Some(Self(bytes))
+    }
+
+    pub(crate) fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Number(number) => number.as_u64().map(Self::from_u64),
+            Value::String(string) => match string.strip_prefix("0x") {
+                Some(hex_str) => Self::from_hex(hex_str),
+                None => Self::from_decimal(string),
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the canonical JSON form of the value:
+    /// a number if it fits into `u64`, and a decimal string otherwise.
+    pub(crate) fn to_canonical_value(self) -> Value {
+        let (high, low) = self.0.split_at(24);
+        if high.iter().all(|byte| *byte == 0) {
+            let mut low_bytes = [0u8; 8];
+            low_bytes.copy_from_slice(low);
+            return Value::from(u64::from_be_bytes(low_bytes));
+        }
+
+        // Long division by 10, collecting the digits from the least significant one.
+        let mut bytes = self.0;
+        let mut digits = Vec::new();
+        while bytes.iter().any(|byte| *byte != 0) {
+            let mut remainder = 0u32;
+            for byte in bytes.iter_mut() {
+                let value = (remainder << 8) | (*byte as u32);
+                *byte = (value / 10) as u8;
+                remainder = value % 10;
+            }
+            digits.push(b'0' + remainder as u8);
+        }
+        digits.reverse();
+        Value::String(String::from_utf8(digits).expect("digits are ASCII"))
+    }
+}
+
+fn compare(comparator: Comparator, ordering: Ordering) -> bool {
+    match comparator {
+        Comparator::Eq => ordering == Ordering::Equal,
+        Comparator::Ne => ordering != Ordering::Equal,
+        Comparator::Gt => ordering == Ordering::Greater,
+        Comparator::Lt => ordering == Ordering::Less,
+        Comparator::Ge => ordering != Ordering::Less,
+        Comparator::Le => ordering != Ordering::Greater,
+    }
+}
+
+fn comparator_str(comparator: Comparator) -> &'static str {
+    match comparator {
+        Comparator::Eq => "==",
+        Comparator::Ne => "!=",
+        Comparator::Gt => ">",
+        Comparator::Lt => "<",
+        Comparator::Ge => ">=",
+        Comparator::Le => "<=",
+    }
+}
+
+impl ReturnValueTest {
+    /// Applies the test to the given value.
+    ///
+    /// Unsigned integers (JSON numbers, decimal or `0x`-prefixed hex strings)
+    /// are compared as 256-bit values; other values can only be checked for equality.
+    fn apply(&self, path: &str, returned: &Value) -> Result<(bool, String), EvaluationError> {
+        let returned = match self.index {
+            None => returned,
+            Some(index) => returned
+                .as_array()
+                .and_then(|elements| elements.get(index as usize))
+                .ok_or_else(|| EvaluationError::IndexOutOfRange {
+                    path: path.into(),
+                    index,
+                })?,
+        };
+
+        let description = format!(
+            "{} {} {}",
+            returned,
+            comparator_str(self.comparator),
+            self.value
+        );
+
+        let satisfied = match (
+            Uint256::from_value(returned),
+            Uint256::from_value(&self.value),
+        ) {
+            (Some(returned_int), Some(expected_int)) => {
+                compare(self.comparator, returned_int.cmp(&expected_int))
+            }
+            _ => match self.comparator {
+                Comparator::Eq => returned == &self.value,
+                Comparator::Ne => returned != &self.value,
+                _ => {
+                    return Err(EvaluationError::IncomparableValues {
+                        path: path.into(),
+                        returned: returned.to_string(),
+                        expected: self.value.to_string(),
+                    })
+                }
+            },
+        };
+
+        Ok((satisfied, description))
+    }
+}
+
+struct Evaluator<'a> {
+    provider: &'a dyn ChainStateProvider,
+    trace: Vec<TraceEntry>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn provider_error(path: &str) -> impl FnOnce(String) -> EvaluationError + '_ {
+        move |error| EvaluationError::ProviderFailed {
+            path: path.into(),
+            error,
+        }
+    }
+
+    fn record(&mut self, path: &str, satisfied: bool, is_leaf: bool, description: String) -> bool {
+        self.trace.push(TraceEntry {
+            path: path.into(),
+            satisfied,
+            is_leaf,
+            description,
+        });
+        satisfied
+    }
+
+    fn evaluate(&mut self, path: &str, condition: &Condition) -> Result<bool, EvaluationError> {
+        match condition {
+            Condition::Time(condition) => {
+                let block_time = self
+                    .provider
+                    .block_time(condition.chain)
+                    .map_err(Self::provider_error(path))?;
+                let (satisfied, description) = condition
+                    .return_value_test
+                    .apply(path, &Value::from(block_time))?;
+                Ok(self.record(path, satisfied, true, format!("blocktime: {}", description)))
+            }
+            Condition::Contract(condition) => {
+                let returned = self
+                    .provider
+                    .call_contract(condition, &condition.parameters)
+                    .map_err(Self::provider_error(path))?;
+                let (satisfied, description) =
+                    condition.return_value_test.apply(path, &returned)?;
+                Ok(self.record(
+                    path,
+                    satisfied,
+                    true,
+                    format!("{}: {}", condition.method, description),
+                ))
+            }
+            Condition::Rpc(condition) => {
+                let returned = self
+                    .provider
+                    .call_rpc(condition, &condition.parameters)
+                    .map_err(Self::provider_error(path))?;
+                let (satisfied, description) =
+                    condition.return_value_test.apply(path, &returned)?;
+                Ok(self.record(
+                    path,
+                    satisfied,
+                    true,
+                    format!("{}: {}", condition.method, description),
+                ))
+            }
+            Condition::Compound(condition) => {
+                let mut satisfied = match condition.operator {
+                    CompoundOperator::And => true,
+                    CompoundOperator::Or => false,
+                    CompoundOperator::Not => false,
+                };
+                for (i, operand) in condition.operands.iter().enumerate() {
+                    let operand_path = format!("{}.operands[{}]", path, i);
+                    let operand_satisfied = self.evaluate(&operand_path, operand)?;
+                    match condition.operator {
+                        CompoundOperator::And if !operand_satisfied => {
+                            satisfied = false;
+                            break;
+                        }
+                        CompoundOperator::Or if operand_satisfied => {
+                            satisfied = true;
+                            break;
+                        }
+                        CompoundOperator::Not => satisfied = !operand_satisfied,
+                        _ => {}
+                    }
+                }
+                let operator = match condition.operator {
+                    CompoundOperator::And => "and",
+                    CompoundOperator::Or => "or",
+                    CompoundOperator::Not => "not",
+                };
+                Ok(self.record(path, satisfied, false, format!("compound: {}", operator)))
+            }
+        }
+    }
+}
+
+impl ConditionLingo {
+    /// Evaluates the condition tree against the chain state supplied by `provider`.
+    ///
+    /// Context variables in call parameters are passed to the provider as is.
+    pub fn evaluate(
+        &self,
+        provider: &dyn ChainStateProvider,
+    ) -> Result<ConditionEvaluation, EvaluationError> {
+        self.validate()
+            .map_err(EvaluationError::InvalidConditions)?;
+        let mut evaluator = Evaluator {
+            provider,
+            trace: Vec::new(),
+        };
+        let satisfied = evaluator.evaluate("condition", &self.condition)?;
+        Ok(ConditionEvaluation {
+            satisfied,
+            trace: evaluator.trace,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    use serde_json::json;
+
+    use super::{EvaluationError, MockChainState, Uint256};
+    use crate::Conditions;
+
+    const CONTRACT: &str = "0x1e6F4Bd5E3f8A2f1aFbF4E6D6B3bA1c0D9e2F3a4";
+
+    fn balance_and_time(min_balance: &str) -> Conditions {
+        let lingo = json!({
+            "version": "1.0.0",
+            "condition": {
+                "conditionType": "compound",
+                "operator": "and",
+                "operands": [
+                    {
+                        "conditionType": "time",
+                        "chain": 1,
+                        "method": "blocktime",
+                        "returnValueTest": {"comparator": ">=", "value": 1700000000u64}
+                    },
+                    {
+                        "conditionType": "contract",
+                        "chain": 1,
+                        "contractAddress": CONTRACT,
+                        "standardContractType": "ERC20",
+                        "method": "balanceOf",
+                        "parameters": ["0x0000000000000000000000000000000000000001"],
+                        "returnValueTest": {"comparator": ">", "value": min_balance}
+                    }
+                ]
+            }
+        });
+        Conditions::new(&lingo.to_string())
+    }
+
+    fn chain_state(balance: &str) -> MockChainState {
+        let mut state = MockChainState::new();
+        state.set_block_time(1, 1700000001);
+        state.set_contract_call(
+            1,
+            CONTRACT,
+            "balanceOf",
+            &[json!("0x0000000000000000000000000000000000000001")],
+            json!(balance),
+        );
+        state
+    }
+
+    #[test]
+    fn uint256_parsing() {
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(
+            Uint256::from_decimal(max),
+            Uint256::from_hex(&"f".repeat(64))
+        );
+        assert_eq!(Uint256::from_decimal(&[max, "0"].concat()), None);
+        assert_eq!(Uint256::from_decimal("255"), Uint256::from_hex("ff"));
+        assert!(
+            Uint256::from_u64(u64::MAX) < Uint256::from_decimal("18446744073709551616").unwrap()
+        );
+    }
+
+    #[test]
+    fn evaluate_uint256_and_trace() {
+        // A balance larger than u64::MAX
+        let conditions = balance_and_time("100000000000000000000");
+        let lingo = conditions.parse().unwrap();
+
+        let evaluation = lingo
+            .evaluate(&chain_state("100000000000000000001"))
+            .unwrap();
+        assert!(evaluation.satisfied);
+        assert_eq!(evaluation.trace.len(), 3);
+
+        let evaluation = lingo.evaluate(&chain_state("0x56bc75e2d63100000")).unwrap();
+        assert!(!evaluation.satisfied);
+        let failures = evaluation.failures().collect::<Vec<_>>();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].path, "condition.operands[1]");
+    }
+
+    #[test]
+    fn evaluate_provider_failure() {
+        let lingo = balance_and_time("0").parse().unwrap();
+        let result = lingo.evaluate(&MockChainState::new());
+        assert!(matches!(
+            result,
+            Err(EvaluationError::ProviderFailed { path, .. }) if path == "condition.operands[0]"
+        ));
+    }
+}
@@ -1,6 +1,13 @@
-use alloc::string::String;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The major version of the condition language supported by this implementation.
+const CONDITION_LINGO_MAJOR_VERSION: &str = "1";
 
 /// Reencryption conditions.
 #[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
@@ -11,6 +18,14 @@ impl Conditions {
     pub fn new(conditions: &str) -> Self {
         Self(conditions.into())
     }
+
+    /// Parses the conditions into a typed representation and validates it.
+    pub fn parse(&self) -> Result<ConditionLingo, ConditionsError> {
+        let lingo: ConditionLingo = serde_json::from_str(&self.0)
+            .map_err(|err| ConditionsError::Malformed(err.to_string()))?;
+        lingo.validate()?;
+        Ok(lingo)
+    }
 }
 
 impl AsRef<str> for Conditions {
@@ -25,6 +40,13 @@ impl From<String> for Conditions {
     }
 }
 
+impl From<&ConditionLingo> for Conditions {
+    fn from(lingo: &ConditionLingo) -> Self {
+        // Serialization of a structure with string keys into JSON cannot fail.
+        Self(serde_json::to_string(lingo).expect("Error serializing conditions into JSON"))
+    }
+}
+
 /// Context for reencryption conditions.
 #[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub struct Context(String);
@@ -47,3 +69,435 @@ impl From<String> for Context {
         Self(source)
     }
 }
+
+/// An error returned when parsing [`Conditions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConditionsError {
+    /// The conditions are not a valid JSON, or do not follow the condition schema.
+    Malformed(String),
+    /// The condition language version is not supported.
+    UnsupportedVersion(String),
+    /// A condition has the correct structure, but contains an invalid value.
+    Invalid {
+        /// The location of the offending value (e.g. `condition.operands[1].method`).
+        path: String,
+        /// The description of the problem.
+        reason: &'static str,
+    },
+    /// The conditions exceed a complexity limit.
+    LimitExceeded {
+        /// The name of the exceeded limit.
+        limit: &'static str,
+        /// The actual value.
+        value: usize,
+        /// The maximum allowed value.
+        max: usize,
+    },
+}
+
+impl fmt::Display for ConditionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(err) => write!(f, "malformed conditions: {}", err),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported condition language version: {}", version)
+            }
+            Self::Invalid { path, reason } => {
+                write!(f, "invalid condition at {}: {}", path, reason)
+            }
+            Self::LimitExceeded { limit, value, max } => write!(
+                f,
+                "conditions exceed the {} limit: {} > {}",
+                limit, value, max
+            ),
+        }
+    }
+}
+
+fn invalid(path: &str, field: &str, reason: &'static str) -> ConditionsError {
+    ConditionsError::Invalid {
+        path: format!("{}.{}", path, field),
+        reason,
+    }
+}
+
+/// A comparison operator used in [`ReturnValueTest`].
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Comparator {
+    /// `==`
+    #[serde(rename = "==")]
+    Eq,
+    /// `!=`
+    #[serde(rename = "!=")]
+    Ne,
+    /// `>`
+    #[serde(rename = ">")]
+    Gt,
+    /// `<`
+    #[serde(rename = "<")]
+    Lt,
+    /// `>=`
+    #[serde(rename = ">=")]
+    Ge,
+    /// `<=`
+    #[serde(rename = "<=")]
+    Le,
+}
+
+/// A test applied to the value returned by a condition's call.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ReturnValueTest {
+    /// The comparison operator.
+    pub comparator: Comparator,
+    /// The value to compare the returned value with.
+    pub value: Value,
+    /// If the call returns a tuple, the index of the element to compare.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<u32>,
+}
+
+impl ReturnValueTest {
+    fn validate(&self, path: &str) -> Result<(), ConditionsError> {
+        match self.value {
+            Value::Object(_) => Err(invalid(
+                path,
+                "value",
+                "comparison with an object is not supported",
+            )),
+            Value::Array(_) | Value::Bool(_) | Value::Null
+                if !matches!(self.comparator, Comparator::Eq | Comparator::Ne) =>
+            {
+                Err(invalid(
+                    path,
+                    "comparator",
+                    "only `==` and `!=` are supported for non-numeric values",
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A condition on the current block time.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TimeCondition {
+    /// The chain ID.
+    pub chain: u64,
+    /// The method used to obtain the time (currently only `blocktime`).
+    pub method: String,
+    /// The test applied to the time.
+    pub return_value_test: ReturnValueTest,
+}
+
+/// A standard contract interface whose ABI does not need to be specified explicitly.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum StandardContractType {
+    /// ERC-20 fungible token.
+    ERC20,
+    /// ERC-721 non-fungible token.
+    ERC721,
+}
+
+/// A condition on the value returned by a contract call.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ContractCondition {
+    /// The chain ID.
+    pub chain: u64,
+    /// The hex-encoded address of the contract (with the `0x` prefix).
+    pub contract_address: String,
+    /// The standard contract interface, if the called method belongs to one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub standard_contract_type: Option<StandardContractType>,
+    /// The ABI of the called method, if it does not belong to a standard interface.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_abi: Option<Value>,
+    /// The name of the called method.
+    pub method: String,
+    /// The call parameters (may include context variables, e.g. `:userAddress`).
+    #[serde(default)]
+    pub parameters: Vec<Value>,
+    /// The test applied to the returned value.
+    pub return_value_test: ReturnValueTest,
+}
+
+/// A condition on the value returned by an Ethereum JSON RPC call.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RpcCondition {
+    /// The chain ID.
+    pub chain: u64,
+    /// The RPC method (e.g. `eth_getBalance`).
+    pub method: String,
+    /// The call parameters (may include context variables, e.g. `:userAddress`).
+    #[serde(default)]
+    pub parameters: Vec<Value>,
+    /// The test applied to the returned value.
+    pub return_value_test: ReturnValueTest,
+}
+
+/// A logical operator combining several conditions.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CompoundOperator {
+    /// All the operands must be satisfied.
+    And,
+    /// At least one of the operands must be satisfied.
+    Or,
+    /// The only operand must not be satisfied.
+    Not,
+}
+
+/// A logical combination of several conditions.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CompoundCondition {
+    /// The logical operator.
+    pub operator: CompoundOperator,
+    /// The combined conditions.
+    pub operands: Vec<Condition>,
+}
+
+/// A single node of the condition tree.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "conditionType", rename_all = "camelCase")]
+pub enum Condition {
+    /// A block time condition.
+    Time(TimeCondition),
+    /// A contract call condition.
+    Contract(ContractCondition),
+    /// An RPC call condition.
+    Rpc(RpcCondition),
+    /// A logical combination of conditions.
+    Compound(CompoundCondition),
+}
+
+fn validate_chain(path: &str, chain: u64) -> Result<(), ConditionsError> {
+    if chain == 0 {
+        return Err(invalid(path, "chain", "chain ID must be non-zero"));
+    }
+    Ok(())
+}
+
+pub(crate) fn is_hex_address(address: &str) -> bool {
+    match address.strip_prefix("0x") {
+        Some(hex_part) => hex_part.len() == 40 && hex_part.chars().all(|c| c.is_ascii_hexdigit()),
+        None => false,
+    }
+}
+
+impl Condition {
+    fn validate(&self, path: &str) -> Result<(), ConditionsError> {
+        match self {
+            Self::Time(condition) => {
+                validate_chain(path, condition.chain)?;
+                if condition.method != "blocktime" {
+                    return Err(invalid(path, "method", "unsupported time method"));
+                }
+                condition
+                    .return_value_test
+                    .validate(&format!("{}.returnValueTest", path))
+            }
+            Self::Contract(condition) => {
+                validate_chain(path, condition.chain)?;
+                if !is_hex_address(&condition.contract_address) {
+                    return Err(invalid(
+                        path,
+                        "contractAddress",
+                        "expected a 0x-prefixed 20-byte hex string",
+                    ));
+                }
+                match (&condition.standard_contract_type, &condition.function_abi) {
+                    (None, None) => {
+                        return Err(invalid(
+                            path,
+                            "functionAbi",
+                            "either `standardContractType` or `functionAbi` must be provided",
+                        ))
+                    }
+                    (Some(_), Some(_)) => {
+                        return Err(invalid(
+                            path,
+                            "functionAbi",
+                            "`standardContractType` and `functionAbi` are mutually exclusive",
+                        ))
+                    }
+                    (None, Some(abi)) if !abi.is_object() => {
+                        return Err(invalid(path, "functionAbi", "expected an object"))
+                    }
+                    _ => {}
+                }
+                if condition.method.is_empty() {
+                    return Err(invalid(path, "method", "method must be non-empty"));
+                }
+                condition
+                    .return_value_test
+                    .validate(&format!("{}.returnValueTest", path))
+            }
+            Self::Rpc(condition) => {
+                validate_chain(path, condition.chain)?;
+                if !condition.method.starts_with("eth_") {
+                    return Err(invalid(path, "method", "only `eth_` methods are supported"));
+                }
+                condition
+                    .return_value_test
+                    .validate(&format!("{}.returnValueTest", path))
+            }
+            Self::Compound(condition) => {
+                let operands_ok = match condition.operator {
+                    CompoundOperator::And | CompoundOperator::Or => condition.operands.len() >= 2,
+                    CompoundOperator::Not => condition.operands.len() == 1,
+                };
+                if !operands_ok {
+                    return Err(invalid(
+                        path,
+                        "operands",
+                        "`and`/`or` require at least two operands, `not` requires exactly one",
+                    ));
+                }
+                condition
+                    .operands
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(i, operand)| {
+                        operand.validate(&format!("{}.operands[{}]", path, i))
+                    })
+            }
+        }
+    }
+}
+
+/// The typed representation of [`Conditions`].
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConditionLingo {
+    /// The version of the condition language.
+    pub version: String,
+    /// The root condition.
+    pub condition: Condition,
+}
+
+impl ConditionLingo {
+    /// The latest version of the condition language.
+    pub const VERSION: &'static str = "1.0.0";
+
+    /// Creates a new condition tree of the latest version.
+    pub fn new(condition: Condition) -> Self {
+        Self {
+            version: Self::VERSION.into(),
+            condition,
+        }
+    }
+
+    /// Checks the condition tree for invalid values.
+    pub fn validate(&self) -> Result<(), ConditionsError> {
+        let major_version = self.version.split('.').next().unwrap_or("");
+        if major_version != CONDITION_LINGO_MAJOR_VERSION {
+            return Err(ConditionsError::UnsupportedVersion(self.version.clone()));
+        }
+        self.condition.validate("condition")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Comparator, CompoundOperator, Condition, ConditionLingo, Conditions, ConditionsError,
+    };
+
+    const BALANCE_OR_TIME: &str = r#"{
+        "version": "1.0.0",
+        "condition": {
+            "conditionType": "compound",
+            "operator": "or",
+            "operands": [
+                {
+                    "conditionType": "contract",
+                    "chain": 137,
+                    "contractAddress": "0x1e6F4Bd5E3f8A2f1aFbF4E6D6B3bA1c0D9e2F3a4",
+                    "standardContractType": "ERC20",
+                    "method": "balanceOf",
+                    "parameters": [":userAddress"],
+                    "returnValueTest": {"comparator": ">", "value": 0}
+                },
+                {
+                    "conditionType": "time",
+                    "chain": 137,
+                    "method": "blocktime",
+                    "returnValueTest": {"comparator": ">=", "value": 1700000000}
+                }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn parse_and_serialize() {
+        let lingo = Conditions::new(BALANCE_OR_TIME).parse().unwrap();
+        let compound = match &lingo.condition {
+            Condition::Compound(compound) => compound,
+            _ => panic!("expected a compound condition"),
+        };
+        assert_eq!(compound.operator, CompoundOperator::Or);
+        assert_eq!(compound.operands.len(), 2);
+        match &compound.operands[1] {
+            Condition::Time(time) => {
+                assert_eq!(time.return_value_test.comparator, Comparator::Ge)
+            }
+            _ => panic!("expected a time condition"),
+        }
+
+        let conditions = Conditions::from(&lingo);
+        assert_eq!(conditions.parse().unwrap(), lingo);
+    }
+
+    #[test]
+    fn validation_errors() {
+        let bad_address = BALANCE_OR_TIME.replace("0x1e6F4Bd5", "0xZZ");
+        assert_eq!(
+            Conditions::new(&bad_address).parse(),
+            Err(ConditionsError::Invalid {
+                path: "condition.operands[0].contractAddress".into(),
+                reason: "expected a 0x-prefixed 20-byte hex string",
+            })
+        );
+
+        let misspelled_key = BALANCE_OR_TIME.replace(
+            "\"method\": \"blocktime\"",
+            "\"method\": \"blocktime\", \"chainId\": 1",
+        );
+        assert!(matches!(
+            Conditions::new(&misspelled_key).parse(),
+            Err(ConditionsError::Malformed(_))
+        ));
+
+        let unknown_top_level_key = BALANCE_OR_TIME.replacen("{", "{\"extra\": 1,", 1);
+        assert!(matches!(
+            Conditions::new(&unknown_top_level_key).parse(),
+            Err(ConditionsError::Malformed(_))
+        ));
+
+        let bad_version = BALANCE_OR_TIME.replace("1.0.0", "2.0.0");
+        assert_eq!(
+            Conditions::new(&bad_version).parse(),
+            Err(ConditionsError::UnsupportedVersion("2.0.0".into()))
+        );
+
+        let bad_comparator = BALANCE_OR_TIME.replace(">=", "=>");
+        assert!(matches!(
+            Conditions::new(&bad_comparator).parse(),
+            Err(ConditionsError::Malformed(_))
+        ));
+
+        let lingo = ConditionLingo::new(Condition::Compound(super::CompoundCondition {
+            operator: CompoundOperator::Not,
+            operands: [].into(),
+        }));
+        assert!(matches!(
+            lingo.validate(),
+            Err(ConditionsError::Invalid { .. })
+        ));
+    }
+}
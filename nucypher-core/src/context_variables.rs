@@ -0,0 +1,590 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::condition_evaluation::Uint256;
+use crate::conditions::{
+    is_hex_address, Condition, ConditionLingo, Conditions, ConditionsError, Context,
+    ContractCondition, StandardContractType,
+};
+use crate::siwe::{address_to_hex, SiweAuthentication, SiweError, SiweVerificationParameters};
+
+/// The name of the context variable holding the requester's address.
+pub const USER_ADDRESS_CONTEXT_VARIABLE: &str = ":userAddress";
+
+/// The expected type of a context variable value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ContextVariableType {
+    /// A `0x`-prefixed 20-byte hex string.
+    Address,
+    /// An unsigned integer (a JSON number, a decimal string, or a `0x`-prefixed hex string).
+    Uint,
+    /// A boolean.
+    Bool,
+    /// A string.
+    String,
+    /// A `0x`-prefixed hex string.
+    Bytes,
+    /// Any JSON value.
+    Any,
+}
+
+impl ContextVariableType {
+    fn from_abi_type(abi_type: &str) -> Self {
+        match abi_type {
+            "address" => Self::Address,
+            "bool" => Self::Bool,
+            "string" => Self::String,
+            _ if abi_type.starts_with("uint") && !abi_type.ends_with(']') => Self::Uint,
+            _ if abi_type.starts_with("bytes") && !abi_type.ends_with(']') => Self::Bytes,
+            _ => Self::Any,
+        }
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        match self {
+            // An address may also be provided as a SIWE authentication entry,
+            // which is replaced by the verified address in `ContextVariables::authenticate()`.
+            Self::Address => {
+                matches!(value.as_str(), Some(s) if is_hex_address(s))
+                    || SiweAuthentication::deserialize(value).is_ok()
+            }
+            Self::Uint => Uint256::from_value(value).is_some(),
+            Self::Bool => value.is_boolean(),
+            Self::String => value.is_string(),
+            Self::Bytes => matches!(
+                value.as_str().and_then(|s| s.strip_prefix("0x")),
+                Some(s) if hex::decode(s).is_ok()
+            ),
+            Self::Any => true,
+        }
+    }
+}
+
+impl fmt::Display for ContextVariableType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Address => "address",
+            Self::Uint => "uint",
+            Self::Bool => "bool",
+            Self::String => "string",
+            Self::Bytes => "bytes",
+            Self::Any => "any",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Returns `true` if the value is a reference to a context variable (e.g. `:userAddress`).
+pub fn is_context_variable(value: &Value) -> bool {
+    let name = match value.as_str().and_then(|s| s.strip_prefix(':')) {
+        Some(name) => name,
+        None => return false,
+    };
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Returns the expected types of the parameters of a standard contract method.
+fn standard_parameter_types(
+    contract_type: StandardContractType,
+    method: &str,
+) -> &'static [ContextVariableType] {
+    match (contract_type, method) {
+        (StandardContractType::ERC20, "balanceOf") => &[ContextVariableType::Address],
+        (StandardContractType::ERC721, "balanceOf") => &[ContextVariableType::Address],
+        (StandardContractType::ERC721, "ownerOf") => &[ContextVariableType::Uint],
+        _ => &[],
+    }
+}
+
+fn contract_parameter_types(condition: &ContractCondition) -> Vec<ContextVariableType> {
+    if let Some(contract_type) = condition.standard_contract_type {
+        return standard_parameter_types(contract_type, &condition.method).to_vec();
+    }
+    condition
+        .function_abi
+        .as_ref()
+        .and_then(|abi| abi.get("inputs"))
+        .and_then(|inputs| inputs.as_array())
+        .map(|inputs| {
+            inputs
+                .iter()
+                .map(|input| {
+                    input
+                        .get("type")
+                        .and_then(|t| t.as_str())
+                        .map_or(ContextVariableType::Any, ContextVariableType::from_abi_type)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn rpc_parameter_types(method: &str) -> &'static [ContextVariableType] {
+    match method {
+        "eth_getBalance" => &[ContextVariableType::Address, ContextVariableType::Any],
+        _ => &[],
+    }
+}
+
+fn declare(
+    variables: &mut BTreeMap<String, ContextVariableType>,
+    value: &Value,
+    declared_type: ContextVariableType,
+) -> Result<(), ContextError> {
+    if !is_context_variable(value) {
+        return Ok(());
+    }
+    let name = value.as_str().unwrap_or_default();
+
+    // The requester's address has a fixed meaning regardless of where it is used.
+    let declared_type = if name == USER_ADDRESS_CONTEXT_VARIABLE {
+        ContextVariableType::Address
+    } else {
+        declared_type
+    };
+
+    // If a variable is used in several places, the most specific type wins,
+    // but it cannot be used with two different specific types.
+    let entry = variables
+        .entry(name.into())
+        .or_insert(ContextVariableType::Any);
+    if *entry == ContextVariableType::Any {
+        *entry = declared_type;
+    } else if declared_type != ContextVariableType::Any && declared_type != *entry {
+        return Err(ContextError::ConflictingTypes {
+            name: name.into(),
+            types: (*entry, declared_type),
+        });
+    }
+    Ok(())
+}
+
+fn collect_variables(
+    condition: &Condition,
+    variables: &mut BTreeMap<String, ContextVariableType>,
+) -> Result<(), ContextError> {
+    let (parameters, types, test_value): (&[Value], Vec<ContextVariableType>, &Value) =
+        match condition {
+            Condition::Time(condition) => (&[], Vec::new(), &condition.return_value_test.value),
+            Condition::Contract(condition) => (
+                &condition.parameters,
+                contract_parameter_types(condition),
+                &condition.return_value_test.value,
+            ),
+            Condition::Rpc(condition) => (
+                &condition.parameters,
+                rpc_parameter_types(&condition.method).to_vec(),
+                &condition.return_value_test.value,
+            ),
+            Condition::Compound(condition) => {
+                return condition
+                    .operands
+                    .iter()
+                    .try_for_each(|operand| collect_variables(operand, variables));
+            }
+        };
+
+    for (i, parameter) in parameters.iter().enumerate() {
+        let declared_type = types.get(i).copied().unwrap_or(ContextVariableType::Any);
+        declare(variables, parameter, declared_type)?;
+    }
+    declare(variables, test_value, ContextVariableType::Any)
+}
+
+fn substitute(value: &mut Value, values: &BTreeMap<String, Value>) {
+    if let Some(substitution) = value.as_str().and_then(|name| values.get(name)) {
+        *value = substitution.clone();
+    }
+}
+
+fn substitute_variables(condition: &mut Condition, values: &BTreeMap<String, Value>) {
+    match condition {
+        Condition::Time(condition) => substitute(&mut condition.return_value_test.value, values),
+        Condition::Contract(condition) => {
+            condition
+                .parameters
+                .iter_mut()
+                .for_each(|parameter| substitute(parameter, values));
+            substitute(&mut condition.return_value_test.value, values);
+        }
+        Condition::Rpc(condition) => {
+            condition
+                .parameters
+                .iter_mut()
+                .for_each(|parameter| substitute(parameter, values));
+            substitute(&mut condition.return_value_test.value, values);
+        }
+        Condition::Compound(condition) => condition
+            .operands
+            .iter_mut()
+            .for_each(|operand| substitute_variables(operand, values)),
+    }
+}
+
+/// An error returned when matching a [`Context`] against [`Conditions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContextError {
+    /// The context is not a JSON object mapping context variable names to values.
+    Malformed(String),
+    /// The conditions the context is checked against are invalid.
+    InvalidConditions(ConditionsError),
+    /// The context does not match the variables declared by the conditions.
+    Mismatch {
+        /// Variables referenced by the conditions, but not provided by the context.
+        missing: BTreeSet<String>,
+        /// Variables provided by the context, but not referenced by the conditions.
+        extra: BTreeSet<String>,
+        /// Variables whose values do not have the expected type.
+        wrong_type: BTreeMap<String, ContextVariableType>,
+    },
+    /// The requester's address is provided without an authentication entry,
+    /// or the entry was not verified before resolving the context.
+    Unauthenticated,
+    /// The authentication of the requester's address failed.
+    Authentication(SiweError),
+    /// A context variable is used by the conditions in places expecting different types.
+    ConflictingTypes {
+        /// The name of the variable.
+        name: String,
+        /// The two conflicting types.
+        types: (ContextVariableType, ContextVariableType),
+    },
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(err) => write!(f, "malformed context: {}", err),
+            Self::InvalidConditions(_) => write!(f, "invalid conditions"),
+            Self::Mismatch {
+                missing,
+                extra,
+                wrong_type,
+            } => {
+                write!(f, "context does not match conditions:")?;
+                if !missing.is_empty() {
+                    write!(f, " missing {:?};", missing)?;
+                }
+                if !extra.is_empty() {
+                    write!(f, " extra {:?};", extra)?;
+                }
+                for (name, expected) in wrong_type.iter() {
+                    write!(f, " {} must be of type {};", name, expected)?;
+                }
+                Ok(())
+            }
+            Self::Unauthenticated => write!(
+                f,
+                "{} must be authenticated with a signed message",
+                USER_ADDRESS_CONTEXT_VARIABLE
+            ),
+            Self::Authentication(_) => write!(f, "authentication failed"),
+            Self::ConflictingTypes { name, types } => {
+                write!(f, "{} is used both as {} and as {}", name, types.0, types.1)
+            }
+        }
+    }
+}
+
+/// Parsed values of the context variables.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ContextVariables(BTreeMap<String, Value>);
+
+impl ContextVariables {
+    /// Returns the value of the given context variable.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0.get(name)
+    }
+
+    /// Iterates over the names and values of the context variables.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.0.iter()
+    }
+
+    /// Verifies the SIWE authentication entry provided for [`USER_ADDRESS_CONTEXT_VARIABLE`]
+    /// and replaces it with the verified address.
+    ///
+    /// Contexts not referencing the requester's address are returned unchanged.
+    pub fn authenticate(
+        &self,
+        params: &SiweVerificationParameters<'_>,
+    ) -> Result<Self, ContextError> {
+        let value = match self.0.get(USER_ADDRESS_CONTEXT_VARIABLE) {
+            Some(value) => value,
+            None => return Ok(self.clone()),
+        };
+        let authentication =
+            SiweAuthentication::deserialize(value).map_err(|_err| ContextError::Unauthenticated)?;
+        let address = authentication
+            .verify(params)
+            .map_err(ContextError::Authentication)?;
+
+        let mut authenticated = self.clone();
+        authenticated.0.insert(
+            USER_ADDRESS_CONTEXT_VARIABLE.into(),
+            Value::from(address_to_hex(&address)),
+        );
+        Ok(authenticated)
+    }
+}
+
+impl Context {
+    /// Parses the context as a JSON object mapping variable names (e.g. `:userAddress`)
+    /// to their values.
+    pub fn parse(&self) -> Result<ContextVariables, ContextError> {
+        let values: BTreeMap<String, Value> = serde_json::from_str(self.as_ref())
+            .map_err(|err| ContextError::Malformed(err.to_string()))?;
+        if let Some(name) = values
+            .keys()
+            .find(|name| !is_context_variable(&Value::from(name.as_str())))
+        {
+            return Err(ContextError::Malformed(
+                [name, " is not a valid context variable name"].concat(),
+            ));
+        }
+        Ok(ContextVariables(values))
+    }
+}
+
+impl ConditionLingo {
+    /// Returns the context variables referenced by the conditions, along with their expected types.
+    ///
+    /// Fails if a variable is used in places expecting different types.
+    pub fn context_variables(&self) -> Result<BTreeMap<String, ContextVariableType>, ContextError> {
+        let mut variables = BTreeMap::new();
+        collect_variables(&self.condition, &mut variables)?;
+        Ok(variables)
+    }
+
+    /// Checks that the context provides exactly the variables referenced by the conditions,
+    /// with values of correct types.
+    pub fn check_context(&self, context: &ContextVariables) -> Result<(), ContextError> {
+        let declared = self.context_variables()?;
+
+        let missing = declared
+            .keys()
+            .filter(|name| !context.0.contains_key(*name))
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        let extra = context
+            .0
+            .keys()
+            .filter(|name| !declared.contains_key(*name))
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        let wrong_type = declared
+            .iter()
+            .filter(|(name, expected)| {
+                matches!(context.0.get(*name), Some(value) if !expected.matches(value))
+            })
+            .map(|(name, expected)| (name.clone(), *expected))
+            .collect::<BTreeMap<_, _>>();
+
+        if missing.is_empty() && extra.is_empty() && wrong_type.is_empty() {
+            Ok(())
+        } else {
+            Err(ContextError::Mismatch {
+                missing,
+                extra,
+                wrong_type,
+            })
+        }
+    }
+
+    /// Checks the context against the conditions and returns a condition tree
+    /// with the context variables substituted by their values.
+    ///
+    /// Address variables must hold plain addresses:
+    /// SIWE authentication entries have to be verified with
+    /// [`ContextVariables::authenticate`] first.
+    pub fn resolve_context(&self, context: &ContextVariables) -> Result<Self, ContextError> {
+        self.check_context(context)?;
+        let unauthenticated = self.context_variables()?.iter().any(|(name, expected)| {
+            let address = context.0.get(name).and_then(|value| value.as_str());
+            *expected == ContextVariableType::Address
+                && !matches!(address, Some(address) if is_hex_address(address))
+        });
+        if unauthenticated {
+            return Err(ContextError::Unauthenticated);
+        }
+        let mut resolved = self.clone();
+        substitute_variables(&mut resolved.condition, &context.0);
+        Ok(resolved)
+    }
+}
+
+impl Conditions {
+    /// Checks that `context` matches the context variables referenced by the conditions.
+    ///
+    /// Intended to be called before building a `ReencryptionRequest`,
+    /// so that a mismatch is reported to the requester rather than discovered by Ursulas.
+    pub fn check_context(&self, context: Option<&Context>) -> Result<(), ContextError> {
+        let lingo = self.parse().map_err(ContextError::InvalidConditions)?;
+        let variables = match context {
+            Some(context) => context.parse()?,
+            None => ContextVariables::default(),
+        };
+        lingo.check_context(&variables)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use serde_json::json;
+
+    use super::{ContextError, ContextVariableType};
+    use crate::{Condition, Conditions, Context};
+
+    fn conditions() -> Conditions {
+        let lingo = json!({
+            "version": "1.0.0",
+            "condition": {
+                "conditionType": "compound",
+                "operator": "and",
+                "operands": [
+                    {
+                        "conditionType": "contract",
+                        "chain": 1,
+                        "contractAddress": "0x1e6F4Bd5E3f8A2f1aFbF4E6D6B3bA1c0D9e2F3a4",
+                        "standardContractType": "ERC721",
+                        "method": "ownerOf",
+                        "parameters": [":tokenId"],
+                        "returnValueTest": {"comparator": "==", "value": ":userAddress"}
+                    },
+                    {
+                        "conditionType": "rpc",
+                        "chain": 1,
+                        "method": "eth_getBalance",
+                        "parameters": [":userAddress", "latest"],
+                        "returnValueTest": {"comparator": ">", "value": ":minBalance"}
+                    }
+                ]
+            }
+        });
+        Conditions::new(&lingo.to_string())
+    }
+
+    #[test]
+    fn declared_variables() {
+        let variables = conditions().parse().unwrap().context_variables().unwrap();
+        assert_eq!(variables.len(), 3);
+        assert_eq!(variables[":tokenId"], ContextVariableType::Uint);
+        assert_eq!(variables[":userAddress"], ContextVariableType::Address);
+        assert_eq!(variables[":minBalance"], ContextVariableType::Any);
+    }
+
+    #[test]
+    fn check_and_resolve() {
+        let conditions = conditions();
+        let user_address = "0x0000000000000000000000000000000000000001";
+
+        let context = Context::new(
+            &json!({":tokenId": "0x10", ":userAddress": user_address, ":minBalance": 5})
+                .to_string(),
+        );
+        assert_eq!(conditions.check_context(Some(&context)), Ok(()));
+
+        let resolved = conditions
+            .parse()
+            .unwrap()
+            .resolve_context(&context.parse().unwrap())
+            .unwrap();
+        assert!(resolved.context_variables().unwrap().is_empty());
+        match &resolved.condition {
+            Condition::Compound(compound) => match &compound.operands[1] {
+                Condition::Rpc(rpc) => assert_eq!(rpc.parameters[0], json!(user_address)),
+                _ => panic!("expected an RPC condition"),
+            },
+            _ => panic!("expected a compound condition"),
+        }
+
+        let context = Context::new(&json!({":tokenId": true, ":extra": 1}).to_string());
+        match conditions.check_context(Some(&context)) {
+            Err(ContextError::Mismatch {
+                missing,
+                extra,
+                wrong_type,
+            }) => {
+                assert_eq!(missing.len(), 2);
+                assert!(extra.contains(":extra"));
+                assert_eq!(wrong_type[":tokenId"], ContextVariableType::Uint);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        assert!(matches!(
+            conditions.check_context(None),
+            Err(ContextError::Mismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn unauthenticated_address_not_resolved() {
+        let conditions = conditions();
+        let siwe_entry = json!({
+            "address": "0x0000000000000000000000000000000000000001",
+            "scheme": "EIP4361",
+            "typedData": "not verified",
+            "signature": "0x00",
+        });
+        let context = Context::new(
+            &json!({":tokenId": 1, ":userAddress": siwe_entry, ":minBalance": 5}).to_string(),
+        );
+
+        // The entry has the right shape, but it must be verified before it is substituted.
+        assert_eq!(conditions.check_context(Some(&context)), Ok(()));
+        assert_eq!(
+            conditions
+                .parse()
+                .unwrap()
+                .resolve_context(&context.parse().unwrap()),
+            Err(ContextError::Unauthenticated)
+        );
+    }
+
+    #[test]
+    fn conflicting_types() {
+        let lingo = json!({
+            "version": "1.0.0",
+            "condition": {
+                "conditionType": "compound",
+                "operator": "and",
+                "operands": [
+                    {
+                        "conditionType": "contract",
+                        "chain": 1,
+                        "contractAddress": "0x1e6F4Bd5E3f8A2f1aFbF4E6D6B3bA1c0D9e2F3a4",
+                        "standardContractType": "ERC721",
+                        "method": "ownerOf",
+                        "parameters": [":value"],
+                        "returnValueTest": {"comparator": "==", "value": ":userAddress"}
+                    },
+                    {
+                        "conditionType": "rpc",
+                        "chain": 1,
+                        "method": "eth_getBalance",
+                        "parameters": [":value", "latest"],
+                        "returnValueTest": {"comparator": ">", "value": 0}
+                    }
+                ]
+            }
+        });
+        let conditions = Conditions::new(&lingo.to_string());
+        assert_eq!(
+            conditions.parse().unwrap().context_variables(),
+            Err(ContextError::ConflictingTypes {
+                name: ":value".into(),
+                types: (ContextVariableType::Uint, ContextVariableType::Address),
+            })
+        );
+    }
+}
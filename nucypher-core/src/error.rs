@@ -0,0 +1,780 @@
+//! The crate-wide error type and stable error codes.
+
+use core::fmt;
+
+use crate::condition_evaluation::EvaluationError;
+use crate::conditions::ConditionsError;
+use crate::context_variables::ContextError;
+#[cfg(feature = "alloy-primitives")]
+use crate::eth_interop::SignatureConversionError;
+use crate::key_frag::{DecryptionError, UnsignedKeyFragError};
+use crate::node_metadata::AddressDerivationError;
+use crate::policy_grant::PolicyGrantError;
+use crate::reencryption::{CapsuleFragError, RefusalReason, RequestError, ResponseError};
+use crate::reencryption_service::ReencryptionRejection;
+use crate::retrieval_plan::RetrievalError;
+use crate::revocation_registry::RevocationError;
+use crate::siwe::SiweError;
+use crate::treasure_map_builder::TreasureMapError;
+use crate::versioning::DeserializationError;
+
+/// Error returned by various `verify()` methods in the crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationError {
+    /// The signature does not match the object and the verifying key.
+    InvalidSignature,
+    /// The number of capsule frags differs from the number of capsules.
+    CapsuleCountMismatch {
+        /// The number of capsules.
+        expected: usize,
+        /// The number of capsule frags.
+        received: usize,
+    },
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSignature => write!(f, "invalid signature"),
+            Self::CapsuleCountMismatch { expected, received } => write!(
+                f,
+                "mismatched number of capsule frags: expected {}, got {}",
+                expected, received
+            ),
+        }
+    }
+}
+
+/// Any error returned by this crate.
+///
+/// Every error type of the crate converts into it,
+/// so that callers can propagate them with `?`.
+#[derive(Debug)]
+pub enum Error {
+    /// See [`VerificationError`].
+    Verification(VerificationError),
+    /// See [`DecryptionError`].
+    Decryption(DecryptionError),
+    /// See [`DeserializationError`].
+    Deserialization(DeserializationError),
+    /// See [`AddressDerivationError`].
+    AddressDerivation(AddressDerivationError),
+    /// See [`ConditionsError`].
+    Conditions(ConditionsError),
+    /// See [`ContextError`].
+    Context(ContextError),
+    /// See [`EvaluationError`].
+    Evaluation(EvaluationError),
+    /// See [`SiweError`].
+    Siwe(SiweError),
+    /// See [`TreasureMapError`].
+    TreasureMap(TreasureMapError),
+    /// See [`PolicyGrantError`].
+    PolicyGrant(PolicyGrantError),
+    /// See [`RetrievalError`].
+    Retrieval(RetrievalError),
+    /// See [`ReencryptionRejection`].
+    Reencryption(ReencryptionRejection),
+    /// See [`RevocationError`].
+    Revocation(RevocationError),
+    /// See [`CapsuleFragError`].
+    CapsuleFrag(CapsuleFragError),
+    /// See [`UnsignedKeyFragError`].
+    UnsignedKeyFrag(UnsignedKeyFragError),
+    /// See [`RequestError`].
+    Request(RequestError),
+    /// See [`ResponseError`].
+    Response(ResponseError),
+    /// See [`SignatureConversionError`].
+    #[cfg(feature = "alloy-primitives")]
+    SignatureConversion(SignatureConversionError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Verification(_) => write!(f, "verification failed"),
+            Self::Decryption(_) => write!(f, "decryption failed"),
+            Self::Deserialization(_) => write!(f, "deserialization failed"),
+            Self::AddressDerivation(_) => write!(f, "address derivation failed"),
+            Self::Conditions(_) => write!(f, "invalid conditions"),
+            Self::Context(_) => write!(f, "invalid context"),
+            Self::Evaluation(_) => write!(f, "condition evaluation failed"),
+            Self::Siwe(_) => write!(f, "SIWE authentication failed"),
+            Self::TreasureMap(_) => write!(f, "invalid treasure map"),
+            Self::PolicyGrant(_) => write!(f, "policy grant failed"),
+            Self::Retrieval(_) => write!(f, "retrieval failed"),
+            Self::Reencryption(_) => write!(f, "reencryption rejected"),
+            Self::Revocation(_) => write!(f, "revocation failed"),
+            Self::CapsuleFrag(_) => write!(f, "capsule frag unavailable"),
+            Self::UnsignedKeyFrag(_) => write!(f, "invalid key frag"),
+            Self::Request(_) => write!(f, "invalid reencryption request"),
+            Self::Response(_) => write!(f, "invalid reencryption response"),
+            #[cfg(feature = "alloy-primitives")]
+            Self::SignatureConversion(_) => write!(f, "signature conversion failed"),
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($($variant:ident($error:ty)),* $(,)?) => {
+        $(
+            impl From<$error> for Error {
+                fn from(err: $error) -> Self {
+                    Self::$variant(err)
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(
+    Verification(VerificationError),
+    Decryption(DecryptionError),
+    Deserialization(DeserializationError),
+    AddressDerivation(AddressDerivationError),
+    Conditions(ConditionsError),
+    Context(ContextError),
+    Evaluation(EvaluationError),
+    Siwe(SiweError),
+    TreasureMap(TreasureMapError),
+    PolicyGrant(PolicyGrantError),
+    Retrieval(RetrievalError),
+    Reencryption(ReencryptionRejection),
+    Revocation(RevocationError),
+    CapsuleFrag(CapsuleFragError),
+    UnsignedKeyFrag(UnsignedKeyFragError),
+    Request(RequestError),
+    Response(ResponseError),
+);
+
+#[cfg(feature = "alloy-primitives")]
+impl_from!(SignatureConversion(SignatureConversionError));
+
+macro_rules! error_codes {
+    ($($(#[$doc:meta])* $variant:ident = $number:literal, $name:literal;)*) => {
+        /// A stable identifier of an error variant, shared by all the language bindings.
+        ///
+        /// Codes are grouped by error type in blocks of 100.
+        /// Neither the numbers nor the names of existing codes will be changed.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[non_exhaustive]
+        pub enum ErrorCode {
+            $(
+                $(#[$doc])*
+                $variant = $number,
+            )*
+        }
+
+        impl ErrorCode {
+            /// Returns the numeric value of the code.
+            pub fn number(&self) -> u16 {
+                *self as u16
+            }
+
+            /// Returns the string value of the code.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+
+            /// Returns the code with the given numeric value.
+            pub fn from_number(number: u16) -> Option<Self> {
+                match number {
+                    $($number => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+error_codes! {
+    /// [`VerificationError::InvalidSignature`]
+    VerificationInvalidSignature = 101, "VERIFICATION_INVALID_SIGNATURE";
+    /// [`VerificationError::CapsuleCountMismatch`]
+    VerificationCapsuleCountMismatch = 102, "VERIFICATION_CAPSULE_COUNT_MISMATCH";
+    // 103 was `VERIFICATION_CAPSULE_FRAG_FAILED`; it is retired and must not be reused.
+
+    /// [`DecryptionError::DecryptionFailed`]
+    DecryptionFailed = 201, "DECRYPTION_FAILED";
+    /// [`DecryptionError::DeserializationFailed`]
+    DecryptionDeserializationFailed = 202, "DECRYPTION_DESERIALIZATION_FAILED";
+    /// [`DecryptionError::VerificationFailed`]
+    DecryptionVerificationFailed = 203, "DECRYPTION_VERIFICATION_FAILED";
+    /// [`DecryptionError::KeyFragVerificationFailed`]
+    DecryptionKeyFragVerificationFailed = 204, "DECRYPTION_KEY_FRAG_VERIFICATION_FAILED";
+    /// [`DecryptionError::NotYetValid`]
+    DecryptionNotYetValid = 205, "DECRYPTION_NOT_YET_VALID";
+    /// [`DecryptionError::Expired`]
+    DecryptionExpired = 206, "DECRYPTION_EXPIRED";
+    /// [`DecryptionError::NotARecipient`]
+    DecryptionNotARecipient = 207, "DECRYPTION_NOT_A_RECIPIENT";
+    /// [`DecryptionError::PublisherMismatch`]
+    DecryptionPublisherMismatch = 208, "DECRYPTION_PUBLISHER_MISMATCH";
+    /// [`DecryptionError::UnsignedKeyFrag`]
+    DecryptionUnsignedKeyFrag = 209, "DECRYPTION_UNSIGNED_KEY_FRAG";
+    /// [`DecryptionError::OpenReencryptedFailed`]
+    DecryptionOpenReencryptedFailed = 210, "DECRYPTION_OPEN_REENCRYPTED_FAILED";
+
+    /// [`DeserializationError::TooShort`]
+    DeserializationTooShort = 301, "DESERIALIZATION_TOO_SHORT";
+    /// [`DeserializationError::IncorrectHeader`]
+    DeserializationIncorrectHeader = 302, "DESERIALIZATION_INCORRECT_HEADER";
+    /// [`DeserializationError::MajorVersionMismatch`]
+    DeserializationMajorVersionMismatch = 303, "DESERIALIZATION_MAJOR_VERSION_MISMATCH";
+    /// [`DeserializationError::UnsupportedMinorVersion`]
+    DeserializationUnsupportedMinorVersion = 304, "DESERIALIZATION_UNSUPPORTED_MINOR_VERSION";
+    /// [`DeserializationError::BadPayload`]
+    DeserializationBadPayload = 305, "DESERIALIZATION_BAD_PAYLOAD";
+    /// [`DeserializationError::InvalidObject`]
+    DeserializationInvalidObject = 306, "DESERIALIZATION_INVALID_OBJECT";
+
+    /// [`AddressDerivationError::NoSignatureInPayload`]
+    AddressDerivationNoSignature = 401, "ADDRESS_DERIVATION_NO_SIGNATURE";
+    /// [`AddressDerivationError::RecoveryFailed`]
+    AddressDerivationRecoveryFailed = 402, "ADDRESS_DERIVATION_RECOVERY_FAILED";
+
+    /// [`ConditionsError::Malformed`]
+    ConditionsMalformed = 501, "CONDITIONS_MALFORMED";
+    /// [`ConditionsError::UnsupportedVersion`]
+    ConditionsUnsupportedVersion = 502, "CONDITIONS_UNSUPPORTED_VERSION";
+    /// [`ConditionsError::Invalid`]
+    ConditionsInvalid = 503, "CONDITIONS_INVALID";
+    /// [`ConditionsError::LimitExceeded`]
+    ConditionsLimitExceeded = 504, "CONDITIONS_LIMIT_EXCEEDED";
+
+    /// [`ContextError::Malformed`]
+    ContextMalformed = 601, "CONTEXT_MALFORMED";
+    /// [`ContextError::InvalidConditions`]
+    ContextInvalidConditions = 602, "CONTEXT_INVALID_CONDITIONS";
+    /// [`ContextError::Mismatch`]
+    ContextMismatch = 603, "CONTEXT_MISMATCH";
+    /// [`ContextError::Unauthenticated`]
+    ContextUnauthenticated = 604, "CONTEXT_UNAUTHENTICATED";
+    /// [`ContextError::Authentication`]
+    ContextAuthenticationFailed = 605, "CONTEXT_AUTHENTICATION_FAILED";
+    /// [`ContextError::ConflictingTypes`]
+    ContextConflictingTypes = 606, "CONTEXT_CONFLICTING_TYPES";
+
+    /// [`EvaluationError::InvalidConditions`]
+    EvaluationInvalidConditions = 701, "EVALUATION_INVALID_CONDITIONS";
+    /// [`EvaluationError::ProviderFailed`]
+    EvaluationProviderFailed = 702, "EVALUATION_PROVIDER_FAILED";
+    /// [`EvaluationError::IndexOutOfRange`]
+    EvaluationIndexOutOfRange = 703, "EVALUATION_INDEX_OUT_OF_RANGE";
+    /// [`EvaluationError::IncomparableValues`]
+    EvaluationIncomparableValues = 704, "EVALUATION_INCOMPARABLE_VALUES";
+
+    /// [`SiweError::MalformedMessage`]
+    SiweMalformedMessage = 801, "SIWE_MALFORMED_MESSAGE";
+    /// [`SiweError::UnsupportedScheme`]
+    SiweUnsupportedScheme = 802, "SIWE_UNSUPPORTED_SCHEME";
+    /// [`SiweError::InvalidSignature`]
+    SiweInvalidSignature = 803, "SIWE_INVALID_SIGNATURE";
+    /// [`SiweError::SignerMismatch`]
+    SiweSignerMismatch = 804, "SIWE_SIGNER_MISMATCH";
+    /// [`SiweError::AddressMismatch`]
+    SiweAddressMismatch = 805, "SIWE_ADDRESS_MISMATCH";
+    /// [`SiweError::DomainMismatch`]
+    SiweDomainMismatch = 806, "SIWE_DOMAIN_MISMATCH";
+    /// [`SiweError::NonceMismatch`]
+    SiweNonceMismatch = 807, "SIWE_NONCE_MISMATCH";
+    /// [`SiweError::IssuedInFuture`]
+    SiweIssuedInFuture = 808, "SIWE_ISSUED_IN_FUTURE";
+    /// [`SiweError::NotYetValid`]
+    SiweNotYetValid = 809, "SIWE_NOT_YET_VALID";
+    /// [`SiweError::Expired`]
+    SiweExpired = 810, "SIWE_EXPIRED";
+
+    /// [`TreasureMapError`]
+    TreasureMapInvalid = 901, "TREASURE_MAP_INVALID";
+
+    /// [`PolicyGrantError::InvalidNodeMetadata`]
+    PolicyGrantInvalidNodeMetadata = 1001, "POLICY_GRANT_INVALID_NODE_METADATA";
+    /// [`PolicyGrantError::TreasureMap`]
+    PolicyGrantInvalidTreasureMap = 1002, "POLICY_GRANT_INVALID_TREASURE_MAP";
+
+    /// [`RetrievalError::UnexpectedResponse`]
+    RetrievalUnexpectedResponse = 1101, "RETRIEVAL_UNEXPECTED_RESPONSE";
+    /// [`RetrievalError::VerificationFailed`]
+    RetrievalVerificationFailed = 1102, "RETRIEVAL_VERIFICATION_FAILED";
+
+    /// [`ReencryptionRejection::Revoked`]
+    ReencryptionRevoked = 1201, "REENCRYPTION_REVOKED";
+    /// [`ReencryptionRejection::RevocationCheckFailed`]
+    ReencryptionRevocationCheckFailed = 1202, "REENCRYPTION_REVOCATION_CHECK_FAILED";
+    /// [`ReencryptionRejection::InvalidKeyFrag`]
+    ReencryptionInvalidKeyFrag = 1203, "REENCRYPTION_INVALID_KEY_FRAG";
+    /// [`ReencryptionRejection::ConditionsNotSatisfied`]
+    ReencryptionConditionsNotSatisfied = 1204, "REENCRYPTION_CONDITIONS_NOT_SATISFIED";
+    /// [`ReencryptionRejection::ConditionEvaluationFailed`]
+    ReencryptionConditionEvaluationFailed = 1205, "REENCRYPTION_CONDITION_EVALUATION_FAILED";
+
+    /// [`RevocationError::InvalidOrder`]
+    RevocationInvalidOrder = 1301, "REVOCATION_INVALID_ORDER";
+    /// [`RevocationError::WrongUrsula`]
+    RevocationWrongUrsula = 1302, "REVOCATION_WRONG_URSULA";
+    /// [`RevocationError::InvalidKeyFrag`]
+    RevocationInvalidKeyFrag = 1303, "REVOCATION_INVALID_KEY_FRAG";
+    /// [`RevocationError::NotADestination`]
+    RevocationNotADestination = 1304, "REVOCATION_NOT_A_DESTINATION";
+    /// [`RevocationError::IssuedInFuture`]
+    RevocationIssuedInFuture = 1305, "REVOCATION_ISSUED_IN_FUTURE";
+    /// [`RevocationError::Stale`]
+    RevocationStale = 1306, "REVOCATION_STALE";
+
+    /// [`CapsuleFragError::Refused`] with [`RefusalReason::ConditionsNotSatisfied`]
+    CapsuleFragConditionsNotSatisfied = 1401, "CAPSULE_FRAG_CONDITIONS_NOT_SATISFIED";
+    /// [`CapsuleFragError::Refused`] with [`RefusalReason::ConditionEvaluationFailed`]
+    CapsuleFragConditionEvaluationFailed = 1402, "CAPSULE_FRAG_CONDITION_EVALUATION_FAILED";
+    /// [`CapsuleFragError::VerificationFailed`]
+    CapsuleFragVerificationFailed = 1403, "CAPSULE_FRAG_VERIFICATION_FAILED";
+
+    /// `SignatureConversionError` (with the `alloy-primitives` feature)
+    SignatureConversionFailed = 1501, "SIGNATURE_CONVERSION_FAILED";
+
+    /// [`UnsignedKeyFragError`]
+    UnsignedKeyFrag = 1601, "UNSIGNED_KEY_FRAG";
+
+    /// [`RequestError::NoCapsules`]
+    RequestNoCapsules = 1701, "REQUEST_NO_CAPSULES";
+
+    /// [`ResponseError::CapsuleCountMismatch`]
+    ResponseCapsuleCountMismatch = 1801, "RESPONSE_CAPSULE_COUNT_MISMATCH";
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name(), self.number())
+    }
+}
+
+impl VerificationError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidSignature => ErrorCode::VerificationInvalidSignature,
+            Self::CapsuleCountMismatch { .. } => ErrorCode::VerificationCapsuleCountMismatch,
+        }
+    }
+}
+
+impl DecryptionError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::DecryptionFailed(_) => ErrorCode::DecryptionFailed,
+            Self::DeserializationFailed(_) => ErrorCode::DecryptionDeserializationFailed,
+            Self::VerificationFailed => ErrorCode::DecryptionVerificationFailed,
+            Self::KeyFragVerificationFailed(_) => ErrorCode::DecryptionKeyFragVerificationFailed,
+            Self::NotYetValid => ErrorCode::DecryptionNotYetValid,
+            Self::Expired => ErrorCode::DecryptionExpired,
+            Self::NotARecipient => ErrorCode::DecryptionNotARecipient,
+            Self::PublisherMismatch => ErrorCode::DecryptionPublisherMismatch,
+            Self::UnsignedKeyFrag => ErrorCode::DecryptionUnsignedKeyFrag,
+            Self::OpenReencryptedFailed(_) => ErrorCode::DecryptionOpenReencryptedFailed,
+        }
+    }
+}
+
+impl DeserializationError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::TooShort { .. } => ErrorCode::DeserializationTooShort,
+            Self::IncorrectHeader { .. } => ErrorCode::DeserializationIncorrectHeader,
+            Self::MajorVersionMismatch { .. } => ErrorCode::DeserializationMajorVersionMismatch,
+            Self::UnsupportedMinorVersion { .. } => {
+                ErrorCode::DeserializationUnsupportedMinorVersion
+            }
+            Self::BadPayload { .. } => ErrorCode::DeserializationBadPayload,
+            Self::InvalidObject { .. } => ErrorCode::DeserializationInvalidObject,
+        }
+    }
+}
+
+impl AddressDerivationError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::NoSignatureInPayload => ErrorCode::AddressDerivationNoSignature,
+            Self::RecoveryFailed(_) => ErrorCode::AddressDerivationRecoveryFailed,
+        }
+    }
+}
+
+impl ConditionsError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Malformed(_) => ErrorCode::ConditionsMalformed,
+            Self::UnsupportedVersion(_) => ErrorCode::ConditionsUnsupportedVersion,
+            Self::Invalid { .. } => ErrorCode::ConditionsInvalid,
+            Self::LimitExceeded { .. } => ErrorCode::ConditionsLimitExceeded,
+        }
+    }
+}
+
+impl ContextError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Malformed(_) => ErrorCode::ContextMalformed,
+            Self::InvalidConditions(_) => ErrorCode::ContextInvalidConditions,
+            Self::Mismatch { .. } => ErrorCode::ContextMismatch,
+            Self::Unauthenticated => ErrorCode::ContextUnauthenticated,
+            Self::Authentication(_) => ErrorCode::ContextAuthenticationFailed,
+            Self::ConflictingTypes { .. } => ErrorCode::ContextConflictingTypes,
+        }
+    }
+}
+
+impl EvaluationError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidConditions(_) => ErrorCode::EvaluationInvalidConditions,
+            Self::ProviderFailed { .. } => ErrorCode::EvaluationProviderFailed,
+            Self::IndexOutOfRange { .. } => ErrorCode::EvaluationIndexOutOfRange,
+            Self::IncomparableValues { .. } => ErrorCode::EvaluationIncomparableValues,
+        }
+    }
+}
+
+impl SiweError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::MalformedMessage(_) => ErrorCode::SiweMalformedMessage,
+            Self::UnsupportedScheme(_) => ErrorCode::SiweUnsupportedScheme,
+            Self::InvalidSignature(_) => ErrorCode::SiweInvalidSignature,
+            Self::SignerMismatch => ErrorCode::SiweSignerMismatch,
+            Self::AddressMismatch => ErrorCode::SiweAddressMismatch,
+            Self::DomainMismatch { .. } => ErrorCode::SiweDomainMismatch,
+            Self::NonceMismatch => ErrorCode::SiweNonceMismatch,
+            Self::IssuedInFuture => ErrorCode::SiweIssuedInFuture,
+            Self::NotYetValid => ErrorCode::SiweNotYetValid,
+            Self::Expired => ErrorCode::SiweExpired,
+        }
+    }
+}
+
+impl TreasureMapError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::TreasureMapInvalid
+    }
+}
+
+impl PolicyGrantError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidNodeMetadata(_) => ErrorCode::PolicyGrantInvalidNodeMetadata,
+            Self::TreasureMap(_) => ErrorCode::PolicyGrantInvalidTreasureMap,
+        }
+    }
+}
+
+impl RetrievalError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::UnexpectedResponse(_) => ErrorCode::RetrievalUnexpectedResponse,
+            Self::VerificationFailed { .. } => ErrorCode::RetrievalVerificationFailed,
+        }
+    }
+}
+
+impl ReencryptionRejection {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Revoked => ErrorCode::ReencryptionRevoked,
+            Self::RevocationCheckFailed(_) => ErrorCode::ReencryptionRevocationCheckFailed,
+            Self::InvalidKeyFrag(_) => ErrorCode::ReencryptionInvalidKeyFrag,
+            Self::ConditionsNotSatisfied => ErrorCode::ReencryptionConditionsNotSatisfied,
+            Self::ConditionEvaluationFailed(_) => ErrorCode::ReencryptionConditionEvaluationFailed,
+        }
+    }
+}
+
+impl RevocationError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidOrder(_) => ErrorCode::RevocationInvalidOrder,
+            Self::WrongUrsula { .. } => ErrorCode::RevocationWrongUrsula,
+            Self::InvalidKeyFrag(_) => ErrorCode::RevocationInvalidKeyFrag,
+            Self::NotADestination(_) => ErrorCode::RevocationNotADestination,
+            Self::IssuedInFuture { .. } => ErrorCode::RevocationIssuedInFuture,
+            Self::Stale { .. } => ErrorCode::RevocationStale,
+        }
+    }
+}
+
+impl CapsuleFragError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Refused(RefusalReason::ConditionsNotSatisfied) => {
+                ErrorCode::CapsuleFragConditionsNotSatisfied
+            }
+            Self::Refused(RefusalReason::ConditionEvaluationFailed) => {
+                ErrorCode::CapsuleFragConditionEvaluationFailed
+            }
+            Self::VerificationFailed(_) => ErrorCode::CapsuleFragVerificationFailed,
+        }
+    }
+}
+
+impl UnsignedKeyFragError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::UnsignedKeyFrag
+    }
+}
+
+impl RequestError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::NoCapsules => ErrorCode::RequestNoCapsules,
+            Self::Conditions(err) => err.code(),
+        }
+    }
+}
+
+impl ResponseError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::CapsuleCountMismatch { .. } => ErrorCode::ResponseCapsuleCountMismatch,
+        }
+    }
+}
+
+#[cfg(feature = "alloy-primitives")]
+impl SignatureConversionError {
+    /// Returns the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::SignatureConversionFailed
+    }
+}
+
+impl Error {
+    /// Returns the stable code of the underlying error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Verification(err) => err.code(),
+            Self::Decryption(err) => err.code(),
+            Self::Deserialization(err) => err.code(),
+            Self::AddressDerivation(err) => err.code(),
+            Self::Conditions(err) => err.code(),
+            Self::Context(err) => err.code(),
+            Self::Evaluation(err) => err.code(),
+            Self::Siwe(err) => err.code(),
+            Self::TreasureMap(err) => err.code(),
+            Self::PolicyGrant(err) => err.code(),
+            Self::Retrieval(err) => err.code(),
+            Self::Reencryption(err) => err.code(),
+            Self::Revocation(err) => err.code(),
+            Self::CapsuleFrag(err) => err.code(),
+            Self::UnsignedKeyFrag(err) => err.code(),
+            Self::Request(err) => err.code(),
+            Self::Response(err) => err.code(),
+            #[cfg(feature = "alloy-primitives")]
+            Self::SignatureConversion(err) => err.code(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod std_impls {
+    use std::error::Error as StdError;
+
+    use super::*;
+
+    impl StdError for Error {
+        fn source(&self) -> Option<&(dyn StdError + 'static)> {
+            match self {
+                Self::Verification(err) => Some(err),
+                Self::Decryption(err) => Some(err),
+                Self::Deserialization(err) => Some(err),
+                Self::AddressDerivation(err) => Some(err),
+                Self::Conditions(err) => Some(err),
+                Self::Context(err) => Some(err),
+                Self::Evaluation(err) => Some(err),
+                Self::Siwe(err) => Some(err),
+                Self::TreasureMap(err) => Some(err),
+                Self::PolicyGrant(err) => Some(err),
+                Self::Retrieval(err) => Some(err),
+                Self::Reencryption(err) => Some(err),
+                Self::Revocation(err) => Some(err),
+                Self::CapsuleFrag(err) => Some(err),
+                Self::UnsignedKeyFrag(err) => Some(err),
+                Self::Request(err) => Some(err),
+                Self::Response(err) => Some(err),
+                #[cfg(feature = "alloy-primitives")]
+                Self::SignatureConversion(err) => Some(err),
+            }
+        }
+    }
+
+    // `umbral-pre` errors do not implement `std::error::Error`,
+    // so they are only reported through `Display`.
+    impl StdError for VerificationError {}
+    impl StdError for DeserializationError {}
+    impl StdError for AddressDerivationError {}
+    impl StdError for ConditionsError {}
+    impl StdError for SiweError {}
+    impl StdError for TreasureMapError {}
+    impl StdError for CapsuleFragError {}
+    impl StdError for UnsignedKeyFragError {}
+    impl StdError for ResponseError {}
+
+    impl StdError for DecryptionError {
+        fn source(&self) -> Option<&(dyn StdError + 'static)> {
+            match self {
+                Self::DeserializationFailed(err) => Some(err),
+                _ => None,
+            }
+        }
+    }
+
+    impl StdError for ContextError {
+        fn source(&self) -> Option<&(dyn StdError + 'static)> {
+            match self {
+                Self::InvalidConditions(err) => Some(err),
+                Self::Authentication(err) => Some(err),
+                _ => None,
+            }
+        }
+    }
+
+    impl StdError for EvaluationError {
+        fn source(&self) -> Option<&(dyn StdError + 'static)> {
+            match self {
+                Self::InvalidConditions(err) => Some(err),
+                _ => None,
+            }
+        }
+    }
+
+    impl StdError for PolicyGrantError {
+        fn source(&self) -> Option<&(dyn StdError + 'static)> {
+            match self {
+                Self::TreasureMap(err) => Some(err),
+                _ => None,
+            }
+        }
+    }
+
+    impl StdError for RetrievalError {
+        fn source(&self) -> Option<&(dyn StdError + 'static)> {
+            match self {
+                Self::VerificationFailed { error, .. } => Some(error),
+                _ => None,
+            }
+        }
+    }
+
+    impl StdError for ReencryptionRejection {
+        fn source(&self) -> Option<&(dyn StdError + 'static)> {
+            match self {
+                Self::InvalidKeyFrag(err) => Some(err),
+                _ => None,
+            }
+        }
+    }
+
+    impl StdError for RevocationError {
+        fn source(&self) -> Option<&(dyn StdError + 'static)> {
+            match self {
+                Self::InvalidOrder(err) => Some(err),
+                Self::InvalidKeyFrag(err) => Some(err),
+                _ => None,
+            }
+        }
+    }
+
+    impl StdError for RequestError {
+        fn source(&self) -> Option<&(dyn StdError + 'static)> {
+            match self {
+                Self::Conditions(err) => Some(err),
+                _ => None,
+            }
+        }
+    }
+
+    #[cfg(feature = "alloy-primitives")]
+    impl StdError for SignatureConversionError {}
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use umbral_pre::SecretKey;
+
+    use super::{Error, ErrorCode, VerificationError};
+    use crate::{DeserializationError, MessageKit, ProtocolObject, RevocationOrder};
+
+    fn parse_revocation_order(bytes: &[u8]) -> Result<RevocationOrder, Error> {
+        let order = RevocationOrder::from_bytes(bytes)?;
+        Ok(order)
+    }
+
+    #[test]
+    fn propagation() {
+        let message_kit = MessageKit::new(&SecretKey::random().public_key(), b"", None).unwrap();
+        let err = parse_revocation_order(&message_kit.to_bytes()).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Deserialization(DeserializationError::IncorrectHeader { .. })
+        ));
+        assert!(err.to_string().starts_with("deserialization failed"));
+
+        let err = Error::from(VerificationError::InvalidSignature);
+        assert_eq!(err.to_string(), "verification failed");
+    }
+
+    #[test]
+    fn codes() {
+        let err = Error::from(DeserializationError::BadPayload {
+            error_msg: "".into(),
+        });
+        assert_eq!(err.code(), ErrorCode::DeserializationBadPayload);
+        assert_eq!(err.code().number(), 305);
+        assert_eq!(err.code().name(), "DESERIALIZATION_BAD_PAYLOAD");
+        assert_eq!(ErrorCode::from_number(305), Some(err.code()));
+        assert_eq!(ErrorCode::from_number(300), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn source_chain() {
+        use std::error::Error as StdError;
+        use std::vec::Vec;
+
+        use crate::{ContextError, SiweError};
+
+        let err = Error::from(ContextError::Authentication(SiweError::Expired));
+        let mut chain = Vec::new();
+        let mut current: Option<&dyn StdError> = Some(&err);
+        while let Some(err) = current {
+            chain.push(err.to_string());
+            current = err.source();
+        }
+        assert_eq!(
+            chain,
+            [
+                "invalid context",
+                "authentication failed",
+                "message has expired"
+            ]
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Conversions between `nucypher-core` types and the commonly used Ethereum primitive crates.
+//!
+//! Each set of conversions is enabled by the feature named after the corresponding crate
+//! (`ethereum-types` for `ethers`-based code, `alloy-primitives` for `alloy`-based code).
+
+#[cfg(feature = "alloy-primitives")]
+use alloc::string::String;
+#[cfg(feature = "alloy-primitives")]
+use core::fmt;
+
+#[cfg(feature = "alloy-primitives")]
+use umbral_pre::RecoverableSignature;
+
+use crate::address::Address;
+use crate::fleet_state::FleetStateChecksum;
+
+#[cfg(feature = "ethereum-types")]
+impl From<ethereum_types::Address> for Address {
+    fn from(address: ethereum_types::Address) -> Self {
+        Self::new(address.as_fixed_bytes())
+    }
+}
+
+#[cfg(feature = "ethereum-types")]
+impl From<Address> for ethereum_types::Address {
+    fn from(address: Address) -> Self {
+        Self::from(<[u8; Address::SIZE]>::from(address))
+    }
+}
+
+#[cfg(feature = "ethereum-types")]
+impl From<ethereum_types::H256> for FleetStateChecksum {
+    fn from(hash: ethereum_types::H256) -> Self {
+        Self::from(hash.to_fixed_bytes())
+    }
+}
+
+#[cfg(feature = "ethereum-types")]
+impl From<FleetStateChecksum> for ethereum_types::H256 {
+    fn from(checksum: FleetStateChecksum) -> Self {
+        Self::from(<[u8; 32]>::from(checksum))
+    }
+}
+
+#[cfg(feature = "alloy-primitives")]
+impl From<alloy_primitives::Address> for Address {
+    fn from(address: alloy_primitives::Address) -> Self {
+        Self::new(&address.into_array())
+    }
+}
+
+#[cfg(feature = "alloy-primitives")]
+impl From<Address> for alloy_primitives::Address {
+    fn from(address: Address) -> Self {
+        Self::from(<[u8; Address::SIZE]>::from(address))
+    }
+}
+
+#[cfg(feature = "alloy-primitives")]
+impl From<alloy_primitives::B256> for FleetStateChecksum {
+    fn from(hash: alloy_primitives::B256) -> Self {
+        Self::from(hash.0)
+    }
+}
+
+#[cfg(feature = "alloy-primitives")]
+impl From<FleetStateChecksum> for alloy_primitives::B256 {
+    fn from(checksum: FleetStateChecksum) -> Self {
+        Self::from(<[u8; 32]>::from(checksum))
+    }
+}
+
+/// An error returned by [`recoverable_signature_from_alloy`]
+/// if the signature components do not form a valid ECDSA signature.
+#[cfg(feature = "alloy-primitives")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureConversionError(pub String);
+
+#[cfg(feature = "alloy-primitives")]
+impl fmt::Display for SignatureConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid signature: {}", self.0)
+    }
+}
+
+// `RecoverableSignature` and `alloy_primitives::Signature` are both foreign types,
+// so we cannot implement `From` between them and have to use free functions instead.
+
+/// Converts a recoverable signature into the `alloy` signature type.
+#[cfg(feature = "alloy-primitives")]
+pub fn recoverable_signature_to_alloy(
+    signature: &RecoverableSignature,
+) -> alloy_primitives::Signature {
+    // The layout is `r || s || y_parity` with the recovery byte being 0 or 1.
+    let bytes = signature.to_be_bytes();
+    alloy_primitives::Signature::new(
+        alloy_primitives::U256::from_be_slice(&bytes[..32]),
+        alloy_primitives::U256::from_be_slice(&bytes[32..64]),
+        bytes[64] != 0,
+    )
+}
+
+/// Converts an `alloy` signature into a recoverable signature.
+#[cfg(feature = "alloy-primitives")]
+pub fn recoverable_signature_from_alloy(
+    signature: &alloy_primitives::Signature,
+) -> Result<RecoverableSignature, SignatureConversionError> {
+    RecoverableSignature::try_from_be_bytes(&signature.as_rsy()).map_err(SignatureConversionError)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "alloy-primitives")]
+    use umbral_pre::RecoverableSignature;
+
+    #[cfg(any(feature = "ethereum-types", feature = "alloy-primitives"))]
+    use crate::{Address, FleetStateChecksum};
+
+    #[cfg(feature = "ethereum-types")]
+    #[test]
+    fn ethereum_types_round_trip() {
+        let address = Address::new(b"00000000000000000001");
+        let eth_address = ethereum_types::Address::from(address);
+        assert_eq!(eth_address.as_bytes(), address.as_ref());
+        assert_eq!(Address::from(eth_address), address);
+
+        let checksum = FleetStateChecksum::from_nodes(&[], None);
+        let eth_hash = ethereum_types::H256::from(checksum);
+        assert_eq!(eth_hash.as_bytes(), checksum.as_ref());
+        assert_eq!(FleetStateChecksum::from(eth_hash), checksum);
+    }
+
+    #[cfg(feature = "alloy-primitives")]
+    #[test]
+    fn alloy_primitives_round_trip() {
+        let address = Address::new(b"00000000000000000001");
+        let alloy_address = alloy_primitives::Address::from(address);
+        assert_eq!(alloy_address.as_slice(), address.as_ref());
+        assert_eq!(Address::from(alloy_address), address);
+
+        let checksum = FleetStateChecksum::from_nodes(&[], None);
+        let alloy_hash = alloy_primitives::B256::from(checksum);
+        assert_eq!(alloy_hash.as_slice(), checksum.as_ref());
+        assert_eq!(FleetStateChecksum::from(alloy_hash), checksum);
+
+        let signature = RecoverableSignature::try_from_be_bytes(
+            b"0000000000000000000000000000000100000000000000000000000000000001\x01",
+        )
+        .unwrap();
+        let alloy_signature = super::recoverable_signature_to_alloy(&signature);
+        assert!(alloy_signature.v());
+        assert_eq!(
+            super::recoverable_signature_from_alloy(&alloy_signature).unwrap(),
+            signature
+        );
+
+        // `r` must be a non-zero scalar.
+        let invalid = alloy_primitives::Signature::new(
+            alloy_primitives::U256::ZERO,
+            alloy_primitives::U256::from(1),
+            false,
+        );
+        assert!(super::recoverable_signature_from_alloy(&invalid).is_err());
+    }
+}
@@ -37,7 +37,7 @@ impl FleetStateChecksum {
                 // so this may lead to unnecessary fleet state update.
                 // But, unlike ProtocolObject::to_bytes(), payload serialization
                 // is not standardized, so it is better not to rely on it.
-                digest.chain(&node.to_bytes())
+                digest.chain(node.to_bytes())
             })
             .finalize();
 
@@ -45,6 +45,18 @@ impl FleetStateChecksum {
     }
 }
 
+impl From<[u8; 32]> for FleetStateChecksum {
+    fn from(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<FleetStateChecksum> for [u8; 32] {
+    fn from(checksum: FleetStateChecksum) -> [u8; 32] {
+        checksum.0
+    }
+}
+
 impl AsRef<[u8]> for FleetStateChecksum {
     fn as_ref(&self) -> &[u8] {
         self.0.as_ref()
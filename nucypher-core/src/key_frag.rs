@@ -6,7 +6,8 @@ use core::fmt;
 use serde::{Deserialize, Serialize};
 use umbral_pre::{
     decrypt_original, encrypt, serde_bytes, Capsule, DecryptionError as UmbralDecryptionError,
-    EncryptionError, KeyFrag, PublicKey, SecretKey, Signature, Signer, VerifiedKeyFrag,
+    EncryptionError, KeyFrag, KeyFragVerificationError, OpenReencryptedError, PublicKey,
+    ReencryptionError, SecretKey, Signature, Signer, VerifiedKeyFrag,
 };
 
 use crate::hrac::HRAC;
@@ -15,40 +16,171 @@ use crate::versioning::{
     ProtocolObjectInner,
 };
 
+/// The period during which the key frags of a policy can be used.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PolicyValidity {
+    /// The time (UNIX timestamp) the policy becomes active at.
+    pub not_before: Option<u64>,
+    /// The time (UNIX timestamp) the policy expires at.
+    pub expiration: u64,
+}
+
+impl PolicyValidity {
+    /// Creates a new validity period.
+    pub fn new(expiration: u64, not_before: Option<u64>) -> Self {
+        Self {
+            not_before,
+            expiration,
+        }
+    }
+
+    /// Returns `true` if the policy is active at the given time (UNIX timestamp).
+    pub fn is_valid_at(&self, now: u64) -> bool {
+        !matches!(self.not_before, Some(not_before) if now < not_before) && now < self.expiration
+    }
+
+    fn check(&self, now: u64) -> Result<(), DecryptionError> {
+        if matches!(self.not_before, Some(not_before) if now < not_before) {
+            return Err(DecryptionError::NotYetValid);
+        }
+        if now >= self.expiration {
+            return Err(DecryptionError::Expired);
+        }
+        Ok(())
+    }
+}
+
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 struct AuthorizedKeyFrag {
     signature: Signature,
     kfrag: KeyFrag,
+    delegating_key: PublicKey,
+    receiving_key: PublicKey,
+    validity: PolicyValidity,
+}
+
+fn signed_message(
+    hrac: &HRAC,
+    kfrag: &KeyFrag,
+    delegating_key: &PublicKey,
+    receiving_key: &PublicKey,
+    validity: &PolicyValidity,
+) -> Vec<u8> {
+    [
+        hrac.as_ref(),
+        messagepack_serialize(kfrag).as_ref(),
+        messagepack_serialize(delegating_key).as_ref(),
+        messagepack_serialize(receiving_key).as_ref(),
+        messagepack_serialize(validity).as_ref(),
+    ]
+    .concat()
 }
 
-fn signed_message(hrac: &HRAC, kfrag: &KeyFrag) -> Vec<u8> {
-    [hrac.as_ref(), messagepack_serialize(kfrag).as_ref()].concat()
+/// Returns `true` if both the delegating and the receiving keys were signed into the key frag
+/// when it was generated.
+///
+/// `KeyFrag::verify()` ignores the keys that were not signed,
+/// so a key frag without them would pass the verification against any keys.
+pub(crate) fn keys_signed(kfrag: &KeyFrag, verifying_key: &PublicKey) -> bool {
+    let delegating_key_signed = matches!(
+        kfrag
+            .clone()
+            .verify(verifying_key, None, Some(verifying_key)),
+        Err((KeyFragVerificationError::DelegatingKeyNotProvided, _))
+    );
+    let receiving_key_signed = matches!(
+        kfrag
+            .clone()
+            .verify(verifying_key, Some(verifying_key), None),
+        Err((KeyFragVerificationError::ReceivingKeyNotProvided, _))
+    );
+    delegating_key_signed && receiving_key_signed
+}
+
+/// An error returned by [`EncryptedKeyFrag::new`] if the delegating and receiving keys
+/// were not signed into the key frag when it was generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsignedKeyFragError;
+
+impl fmt::Display for UnsignedKeyFragError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the delegating and receiving keys are not signed into the key frag"
+        )
+    }
 }
 
 impl AuthorizedKeyFrag {
-    fn new(signer: &Signer, hrac: &HRAC, verified_kfrag: VerifiedKeyFrag) -> Self {
+    fn new(
+        signer: &Signer,
+        hrac: &HRAC,
+        verified_kfrag: VerifiedKeyFrag,
+        delegating_key: &PublicKey,
+        receiving_key: &PublicKey,
+        validity: &PolicyValidity,
+    ) -> Result<Self, UnsignedKeyFragError> {
         // Alice makes plain to Ursula that, upon decrypting this message,
-        // this particular KFrag is authorized for use in the policy identified by this HRAC.
+        // this particular KFrag is authorized for use in the policy identified by this HRAC,
+        // during the given validity period.
+        // The delegating and receiving keys are attached so that Ursula could verify the KFrag.
 
         // TODO (rust-umbral#73): add VerifiedKeyFrag::unverify()?
         let kfrag = verified_kfrag.unverify();
+        if !keys_signed(&kfrag, &signer.verifying_key()) {
+            return Err(UnsignedKeyFragError);
+        }
 
-        let signature = signer.sign(&signed_message(hrac, &kfrag));
+        let signature = signer.sign(&signed_message(
+            hrac,
+            &kfrag,
+            delegating_key,
+            receiving_key,
+            validity,
+        ));
 
-        Self { signature, kfrag }
+        Ok(Self {
+            signature,
+            kfrag,
+            delegating_key: *delegating_key,
+            receiving_key: *receiving_key,
+            validity: *validity,
+        })
     }
 
-    fn verify(self, hrac: &HRAC, publisher_verifying_key: &PublicKey) -> Option<VerifiedKeyFrag> {
-        if !self
-            .signature
-            .verify(publisher_verifying_key, &signed_message(hrac, &self.kfrag))
-        {
-            return None;
+    fn verify(
+        self,
+        hrac: &HRAC,
+        publisher_verifying_key: &PublicKey,
+    ) -> Result<(VerifiedKeyFrag, PolicyValidity), DecryptionError> {
+        if !self.signature.verify(
+            publisher_verifying_key,
+            &signed_message(
+                hrac,
+                &self.kfrag,
+                &self.delegating_key,
+                &self.receiving_key,
+                &self.validity,
+            ),
+        ) {
+            return Err(DecryptionError::VerificationFailed);
+        }
+
+        // The KFrag is created by the publisher, so it is signed by the same key.
+        let verified_kfrag = self
+            .kfrag
+            .clone()
+            .verify(
+                publisher_verifying_key,
+                Some(&self.delegating_key),
+                Some(&self.receiving_key),
+            )
+            .map_err(|(err, _kfrag)| DecryptionError::KeyFragVerificationFailed(err))?;
+        if !keys_signed(&self.kfrag, publisher_verifying_key) {
+            return Err(DecryptionError::UnsignedKeyFrag);
         }
 
-        // Ursula has no side channel to get the KeyFrag author's key,
-        // so verifying the keyfrag is useless.
-        Some(self.kfrag.skip_verification())
+        Ok((verified_kfrag, self.validity))
     }
 }
 
@@ -58,7 +190,7 @@ impl<'a> ProtocolObjectInner<'a> for AuthorizedKeyFrag {
     }
 
     fn version() -> (u16, u16) {
-        (3, 0)
+        (4, 0)
     }
 
     fn unversioned_to_bytes(&self) -> Box<[u8]> {
@@ -76,20 +208,65 @@ impl<'a> ProtocolObjectInner<'a> for AuthorizedKeyFrag {
 
 impl<'a> ProtocolObject<'a> for AuthorizedKeyFrag {}
 
+/// An error returned when decrypting an [`EncryptedKeyFrag`] or an
+/// [`EncryptedTreasureMap`](crate::EncryptedTreasureMap).
 #[allow(clippy::enum_variant_names)]
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum DecryptionError {
+    /// The ciphertext could not be decrypted with the given key.
     DecryptionFailed(UmbralDecryptionError),
+    /// The decrypted payload could not be deserialized.
     DeserializationFailed(DeserializationError),
+    /// The publisher's signature is invalid.
     VerificationFailed,
+    /// The key frag does not correspond to the attached delegating and receiving keys.
+    KeyFragVerificationFailed(KeyFragVerificationError),
+    /// The delegating and receiving keys were not signed into the key frag,
+    /// so it cannot be verified against them.
+    UnsignedKeyFrag,
+    /// The policy is not active yet.
+    NotYetValid,
+    /// The policy has expired.
+    Expired,
+    /// None of the key wraps of a multi-recipient treasure map can be opened with the given key.
+    NotARecipient,
+    /// The publisher's verifying key supplied for verification differs from the one
+    /// embedded in the treasure map.
+    PublisherMismatch,
+    /// The capsule could not be opened with the given capsule frags.
+    OpenReencryptedFailed(OpenReencryptedError),
 }
 
 impl fmt::Display for DecryptionError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::DecryptionFailed(err) => write!(f, "decryption failed: {}", err),
-            Self::DeserializationFailed(err) => write!(f, "deserialization failed: {}", err),
+            Self::DeserializationFailed(_) => write!(f, "deserialization failed"),
             Self::VerificationFailed => write!(f, "verification failed"),
+            Self::KeyFragVerificationFailed(err) => {
+                write!(f, "key frag verification failed: {}", err)
+            }
+            Self::UnsignedKeyFrag => write!(
+                f,
+                "the delegating and receiving keys are not signed into the key frag"
+            ),
+            Self::NotYetValid => write!(f, "the policy is not active yet"),
+            Self::Expired => write!(f, "the policy has expired"),
+            Self::NotARecipient => write!(f, "the key is not among the recipients"),
+            Self::PublisherMismatch => write!(
+                f,
+                "the publisher's key does not match the one in the treasure map"
+            ),
+            Self::OpenReencryptedFailed(err) => write!(f, "opening the capsule failed: {}", err),
+        }
+    }
+}
+
+impl From<ReencryptionError> for DecryptionError {
+    fn from(err: ReencryptionError) -> Self {
+        match err {
+            ReencryptionError::OnOpen(err) => Self::OpenReencryptedFailed(err),
+            ReencryptionError::OnDecryption(err) => Self::DecryptionFailed(err),
         }
     }
 }
@@ -103,17 +280,38 @@ pub struct EncryptedKeyFrag {
 }
 
 impl EncryptedKeyFrag {
-    /// Encrypts and signs a key frag.
+    /// Encrypts and signs a key frag, authorizing it for use during the validity period.
+    ///
+    /// `delegating_key` and `receiving_key` are the keys the key frag was generated for;
+    /// they are used by the recipient to verify the key frag.
+    /// Fails if the key frag was generated without signing these keys into it.
     pub fn new(
         signer: &Signer,
         recipient_key: &PublicKey,
         hrac: &HRAC,
         verified_kfrag: VerifiedKeyFrag,
-    ) -> Self {
-        let auth_kfrag = AuthorizedKeyFrag::new(signer, hrac, verified_kfrag);
+        delegating_key: &PublicKey,
+        receiving_key: &PublicKey,
+        validity: &PolicyValidity,
+    ) -> Result<Self, UnsignedKeyFragError> {
+        let auth_kfrag = AuthorizedKeyFrag::new(
+            signer,
+            hrac,
+            verified_kfrag,
+            delegating_key,
+            receiving_key,
+            validity,
+        )?;
+        Ok(Self::encrypt_authorized(
+            recipient_key,
+            &auth_kfrag.to_bytes(),
+        ))
+    }
+
+    fn encrypt_authorized(recipient_key: &PublicKey, auth_kfrag_bytes: &[u8]) -> Self {
         // Using Umbral for asymmetric encryption here for simplicity,
         // even though we do not plan to re-encrypt the capsule.
-        let (capsule, ciphertext) = match encrypt(recipient_key, &auth_kfrag.to_bytes()) {
+        let (capsule, ciphertext) = match encrypt(recipient_key, auth_kfrag_bytes) {
             Ok(result) => result,
             Err(err) => match err {
                 // For now this is the only error that can happen during encryption,
@@ -127,20 +325,54 @@ impl EncryptedKeyFrag {
         }
     }
 
-    /// Decrypts and verifies a key frag.
+    /// Decrypts and verifies a key frag, returning it along with the signed validity period.
+    ///
+    /// Note that the validity period is not checked; see [`decrypt_at`](`Self::decrypt_at`).
     pub fn decrypt(
         &self,
         sk: &SecretKey,
         hrac: &HRAC,
         publisher_verifying_key: &PublicKey,
-    ) -> Result<VerifiedKeyFrag, DecryptionError> {
+    ) -> Result<(VerifiedKeyFrag, PolicyValidity), DecryptionError> {
         let auth_kfrag_bytes = decrypt_original(sk, &self.capsule, &self.ciphertext)
             .map_err(DecryptionError::DecryptionFailed)?;
         let auth_kfrag = AuthorizedKeyFrag::from_bytes(&auth_kfrag_bytes)
             .map_err(DecryptionError::DeserializationFailed)?;
-        auth_kfrag
-            .verify(hrac, publisher_verifying_key)
-            .ok_or(DecryptionError::VerificationFailed)
+        auth_kfrag.verify(hrac, publisher_verifying_key)
+    }
+
+    /// Re-encrypts the key frag for a new recipient key, preserving the publisher's signature.
+    ///
+    /// Intended to be used by an Ursula rotating her encrypting key,
+    /// see [`EncryptingKeyRotation`](`crate::EncryptingKeyRotation`).
+    pub fn rewrap(
+        &self,
+        sk: &SecretKey,
+        new_recipient_key: &PublicKey,
+    ) -> Result<Self, DecryptionError> {
+        let auth_kfrag_bytes = decrypt_original(sk, &self.capsule, &self.ciphertext)
+            .map_err(DecryptionError::DecryptionFailed)?;
+        // Make sure we are not re-wrapping garbage.
+        AuthorizedKeyFrag::from_bytes(&auth_kfrag_bytes)
+            .map_err(DecryptionError::DeserializationFailed)?;
+        Ok(Self::encrypt_authorized(
+            new_recipient_key,
+            &auth_kfrag_bytes,
+        ))
+    }
+
+    /// Decrypts and verifies a key frag,
+    /// rejecting it if the policy is not active at the given time (UNIX timestamp).
+    pub fn decrypt_at(
+        &self,
+        sk: &SecretKey,
+        hrac: &HRAC,
+        publisher_verifying_key: &PublicKey,
+        now: u64,
+    ) -> Result<VerifiedKeyFrag, DecryptionError> {
+        let (verified_kfrag, validity) = self.decrypt(sk, hrac, publisher_verifying_key)?;
+        validity.check(now)?;
+        Ok(verified_kfrag)
     }
 }
 
@@ -167,3 +399,226 @@ impl<'a> ProtocolObjectInner<'a> for EncryptedKeyFrag {
 }
 
 impl<'a> ProtocolObject<'a> for EncryptedKeyFrag {}
+
+#[cfg(test)]
+mod tests {
+    use umbral_pre::{generate_kfrags, SecretKey, Signer};
+
+    use super::{
+        signed_message, AuthorizedKeyFrag, DecryptionError, EncryptedKeyFrag, PolicyValidity,
+        UnsignedKeyFragError,
+    };
+    use crate::{ProtocolObject, HRAC};
+
+    #[test]
+    fn validity_period() {
+        let delegating_sk = SecretKey::random();
+        let receiving_sk = SecretKey::random();
+        let ursula_sk = SecretKey::random();
+        let signer = Signer::new(SecretKey::random());
+        let hrac = HRAC::new(
+            &delegating_sk.public_key(),
+            &receiving_sk.public_key(),
+            b"label",
+        );
+        let kfrags = generate_kfrags(
+            &delegating_sk,
+            &receiving_sk.public_key(),
+            &signer,
+            1,
+            1,
+            true,
+            true,
+        );
+
+        let validity = PolicyValidity::new(2000, Some(1000));
+        let ekfrag = EncryptedKeyFrag::new(
+            &signer,
+            &ursula_sk.public_key(),
+            &hrac,
+            kfrags[0].clone(),
+            &delegating_sk.public_key(),
+            &receiving_sk.public_key(),
+            &validity,
+        )
+        .unwrap();
+        let publisher_vk = signer.verifying_key();
+
+        let (kfrag, decrypted_validity) = ekfrag.decrypt(&ursula_sk, &hrac, &publisher_vk).unwrap();
+        assert_eq!(kfrag, kfrags[0]);
+        assert_eq!(decrypted_validity, validity);
+
+        assert!(ekfrag
+            .decrypt_at(&ursula_sk, &hrac, &publisher_vk, 1500)
+            .is_ok());
+        assert!(matches!(
+            ekfrag.decrypt_at(&ursula_sk, &hrac, &publisher_vk, 999),
+            Err(DecryptionError::NotYetValid)
+        ));
+        assert!(matches!(
+            ekfrag.decrypt_at(&ursula_sk, &hrac, &publisher_vk, 2000),
+            Err(DecryptionError::Expired)
+        ));
+    }
+
+    #[test]
+    fn rewrap() {
+        let delegating_sk = SecretKey::random();
+        let receiving_sk = SecretKey::random();
+        let ursula_sk = SecretKey::random();
+        let signer = Signer::new(SecretKey::random());
+        let hrac = HRAC::new(
+            &delegating_sk.public_key(),
+            &receiving_sk.public_key(),
+            b"label",
+        );
+        let kfrags = generate_kfrags(
+            &delegating_sk,
+            &receiving_sk.public_key(),
+            &signer,
+            1,
+            1,
+            true,
+            true,
+        );
+        let ekfrag = EncryptedKeyFrag::new(
+            &signer,
+            &ursula_sk.public_key(),
+            &hrac,
+            kfrags[0].clone(),
+            &delegating_sk.public_key(),
+            &receiving_sk.public_key(),
+            &PolicyValidity::new(2000, Some(1000)),
+        )
+        .unwrap();
+        let publisher_vk = signer.verifying_key();
+
+        // Ursula rotates her encrypting key
+        let new_ursula_sk = SecretKey::random();
+        let rewrapped = ekfrag
+            .rewrap(&ursula_sk, &new_ursula_sk.public_key())
+            .unwrap();
+        assert!(rewrapped.decrypt(&ursula_sk, &hrac, &publisher_vk).is_err());
+        let (kfrag, _) = rewrapped
+            .decrypt(&new_ursula_sk, &hrac, &publisher_vk)
+            .unwrap();
+        assert_eq!(kfrag, kfrags[0]);
+        assert!(ekfrag
+            .rewrap(&new_ursula_sk, &ursula_sk.public_key())
+            .is_err());
+    }
+
+    #[test]
+    fn mismatched_kfrag() {
+        let delegating_sk = SecretKey::random();
+        let receiving_sk = SecretKey::random();
+        let ursula_sk = SecretKey::random();
+        let signer = Signer::new(SecretKey::random());
+        let hrac = HRAC::new(
+            &delegating_sk.public_key(),
+            &receiving_sk.public_key(),
+            b"label",
+        );
+        let kfrags = generate_kfrags(
+            &delegating_sk,
+            &receiving_sk.public_key(),
+            &signer,
+            1,
+            1,
+            true,
+            true,
+        );
+
+        // The publisher attaches a wrong receiving key
+        let ekfrag = EncryptedKeyFrag::new(
+            &signer,
+            &ursula_sk.public_key(),
+            &hrac,
+            kfrags[0].clone(),
+            &delegating_sk.public_key(),
+            &SecretKey::random().public_key(),
+            &PolicyValidity::new(2000, None),
+        )
+        .unwrap();
+        assert!(matches!(
+            ekfrag.decrypt(&ursula_sk, &hrac, &signer.verifying_key()),
+            Err(DecryptionError::KeyFragVerificationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn unsigned_keys() {
+        let delegating_sk = SecretKey::random();
+        let receiving_sk = SecretKey::random();
+        let ursula_sk = SecretKey::random();
+        let signer = Signer::new(SecretKey::random());
+        let hrac = HRAC::new(
+            &delegating_sk.public_key(),
+            &receiving_sk.public_key(),
+            b"label",
+        );
+        let validity = PolicyValidity::new(2000, None);
+
+        for (sign_delegating_key, sign_receiving_key) in
+            [(false, false), (true, false), (false, true)]
+        {
+            let kfrags = generate_kfrags(
+                &delegating_sk,
+                &receiving_sk.public_key(),
+                &signer,
+                1,
+                1,
+                sign_delegating_key,
+                sign_receiving_key,
+            );
+
+            // `KeyFrag::verify()` does not check the keys that were not signed
+            let unchecked =
+                kfrags[0]
+                    .clone()
+                    .unverify()
+                    .verify(&signer.verifying_key(), None, None);
+            assert_eq!(
+                unchecked.is_ok(),
+                !sign_delegating_key && !sign_receiving_key
+            );
+
+            assert_eq!(
+                EncryptedKeyFrag::new(
+                    &signer,
+                    &ursula_sk.public_key(),
+                    &hrac,
+                    kfrags[0].clone(),
+                    &delegating_sk.public_key(),
+                    &receiving_sk.public_key(),
+                    &validity,
+                ),
+                Err(UnsignedKeyFragError)
+            );
+
+            // A malicious publisher bypassing the check is caught on decryption
+            let kfrag = kfrags[0].clone().unverify();
+            let auth_kfrag = AuthorizedKeyFrag {
+                signature: signer.sign(&signed_message(
+                    &hrac,
+                    &kfrag,
+                    &delegating_sk.public_key(),
+                    &receiving_sk.public_key(),
+                    &validity,
+                )),
+                kfrag,
+                delegating_key: delegating_sk.public_key(),
+                receiving_key: receiving_sk.public_key(),
+                validity,
+            };
+            let ekfrag = EncryptedKeyFrag::encrypt_authorized(
+                &ursula_sk.public_key(),
+                &auth_kfrag.to_bytes(),
+            );
+            assert_eq!(
+                ekfrag.decrypt(&ursula_sk, &hrac, &signer.verifying_key()),
+                Err(DecryptionError::UnsignedKeyFrag)
+            );
+        }
+    }
+}
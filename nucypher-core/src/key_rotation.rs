@@ -0,0 +1,149 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+
+use serde::{Deserialize, Serialize};
+use umbral_pre::{PublicKey, Signature, Signer};
+
+use crate::address::Address;
+use crate::versioning::{
+    messagepack_deserialize, messagepack_serialize, ProtocolObject, ProtocolObjectInner,
+};
+use crate::VerificationError;
+
+#[derive(Serialize)]
+struct SignedRotation<'a> {
+    staking_provider_address: &'a Address,
+    old_encrypting_key: &'a PublicKey,
+    new_encrypting_key: &'a PublicKey,
+    timestamp_epoch: u64,
+}
+
+fn signed_message(
+    staking_provider_address: &Address,
+    old_encrypting_key: &PublicKey,
+    new_encrypting_key: &PublicKey,
+    timestamp_epoch: u64,
+) -> Box<[u8]> {
+    messagepack_serialize(&SignedRotation {
+        staking_provider_address,
+        old_encrypting_key,
+        new_encrypting_key,
+        timestamp_epoch,
+    })
+}
+
+/// A statement by an Ursula that she replaced her encrypting key.
+///
+/// Accompanies the key frags re-wrapped with
+/// [`EncryptedKeyFrag::rewrap`](`crate::EncryptedKeyFrag::rewrap`).
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptingKeyRotation {
+    /// The staking provider's address.
+    pub staking_provider_address: Address,
+    /// The previous encrypting key.
+    pub old_encrypting_key: PublicKey,
+    /// The new encrypting key.
+    pub new_encrypting_key: PublicKey,
+    /// The time of the rotation (UNIX timestamp).
+    pub timestamp_epoch: u64,
+    signature: Signature,
+}
+
+impl EncryptingKeyRotation {
+    /// Creates and signs a new key rotation statement with the Ursula's signer.
+    pub fn new(
+        signer: &Signer,
+        staking_provider_address: &Address,
+        old_encrypting_key: &PublicKey,
+        new_encrypting_key: &PublicKey,
+        timestamp_epoch: u64,
+    ) -> Self {
+        let message = signed_message(
+            staking_provider_address,
+            old_encrypting_key,
+            new_encrypting_key,
+            timestamp_epoch,
+        );
+        Self {
+            staking_provider_address: *staking_provider_address,
+            old_encrypting_key: *old_encrypting_key,
+            new_encrypting_key: *new_encrypting_key,
+            timestamp_epoch,
+            signature: signer.sign(&message),
+        }
+    }
+
+    /// Verifies the statement against the Ursula's verifying key.
+    pub fn verify(&self, verifying_key: &PublicKey) -> Result<(), VerificationError> {
+        let message = signed_message(
+            &self.staking_provider_address,
+            &self.old_encrypting_key,
+            &self.new_encrypting_key,
+            self.timestamp_epoch,
+        );
+        if self.signature.verify(verifying_key, &message) {
+            Ok(())
+        } else {
+            Err(VerificationError::InvalidSignature)
+        }
+    }
+}
+
+impl<'a> ProtocolObjectInner<'a> for EncryptingKeyRotation {
+    fn brand() -> [u8; 4] {
+        *b"KRot"
+    }
+
+    fn version() -> (u16, u16) {
+        (1, 0)
+    }
+
+    fn unversioned_to_bytes(&self) -> Box<[u8]> {
+        messagepack_serialize(&self)
+    }
+
+    fn unversioned_from_bytes(minor_version: u16, bytes: &[u8]) -> Option<Result<Self, String>> {
+        if minor_version == 0 {
+            Some(messagepack_deserialize(bytes))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> ProtocolObject<'a> for EncryptingKeyRotation {}
+
+#[cfg(test)]
+mod tests {
+    use umbral_pre::{SecretKey, Signer};
+
+    use super::EncryptingKeyRotation;
+    use crate::{Address, ProtocolObject, VerificationError};
+
+    #[test]
+    fn sign_and_verify() {
+        let ursula_signer = Signer::new(SecretKey::random());
+        let rotation = EncryptingKeyRotation::new(
+            &ursula_signer,
+            &Address::new(b"00000000000000000001"),
+            &SecretKey::random().public_key(),
+            &SecretKey::random().public_key(),
+            1700000000,
+        );
+        assert_eq!(rotation.verify(&ursula_signer.verifying_key()), Ok(()));
+        assert_eq!(
+            rotation.verify(&SecretKey::random().public_key()),
+            Err(VerificationError::InvalidSignature)
+        );
+
+        let restored = EncryptingKeyRotation::from_bytes(&rotation.to_bytes()).unwrap();
+        assert_eq!(restored, rotation);
+
+        let mut tampered = rotation;
+        tampered.timestamp_epoch += 1;
+        assert_eq!(
+            tampered.verify(&ursula_signer.verifying_key()),
+            Err(VerificationError::InvalidSignature)
+        );
+    }
+}
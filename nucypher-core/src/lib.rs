@@ -7,36 +7,90 @@
 
 extern crate alloc;
 
+#[cfg(feature = "std")]
+extern crate std;
+
 mod address;
+mod canonicalization;
+mod condition_builder;
+mod condition_evaluation;
 mod conditions;
+mod context_variables;
+mod error;
+#[cfg(any(feature = "ethereum-types", feature = "alloy-primitives"))]
+mod eth_interop;
 mod fleet_state;
 mod hrac;
 mod key_frag;
+mod key_rotation;
 mod message_kit;
 mod node_metadata;
+mod policy_grant;
 mod reencryption;
+mod reencryption_service;
 mod retrieval_kit;
+mod retrieval_plan;
 mod revocation_order;
+mod revocation_registry;
+mod siwe;
 mod treasure_map;
+mod treasure_map_builder;
 mod versioning;
 
-/// Error returned by various `verify()` methods in the crate.
-pub struct VerificationError;
-
 pub use address::Address;
-pub use conditions::{Conditions, Context};
+pub use canonicalization::ConditionsLimits;
+pub use condition_evaluation::{
+    ChainStateProvider, ConditionEvaluation, EvaluationError, MockChainState, TraceEntry,
+};
+pub use conditions::{
+    Comparator, CompoundCondition, CompoundOperator, Condition, ConditionLingo, Conditions,
+    ConditionsError, Context, ContractCondition, ReturnValueTest, RpcCondition,
+    StandardContractType, TimeCondition,
+};
+pub use context_variables::{
+    is_context_variable, ContextError, ContextVariableType, ContextVariables,
+    USER_ADDRESS_CONTEXT_VARIABLE,
+};
+pub use error::{Error, ErrorCode, VerificationError};
+#[cfg(feature = "alloy-primitives")]
+pub use eth_interop::{
+    recoverable_signature_from_alloy, recoverable_signature_to_alloy, SignatureConversionError,
+};
 pub use fleet_state::FleetStateChecksum;
 pub use hrac::HRAC;
-pub use key_frag::EncryptedKeyFrag;
+pub use key_frag::{DecryptionError, EncryptedKeyFrag, PolicyValidity, UnsignedKeyFragError};
+pub use key_rotation::EncryptingKeyRotation;
 pub use message_kit::MessageKit;
 pub use node_metadata::{
-    MetadataRequest, MetadataResponse, MetadataResponsePayload, NodeMetadata, NodeMetadataPayload,
+    AddressDerivationError, MetadataRequest, MetadataResponse, MetadataResponsePayload,
+    NodeMetadata, NodeMetadataPayload,
+};
+pub use policy_grant::{PolicyGrant, PolicyGrantError, RevocationKit};
+pub use reencryption::{
+    CapsuleFragError, ReencryptionRequest, ReencryptionResponse, RefusalReason, RequestError,
+    ResponseError,
+};
+pub use reencryption_service::{
+    ConditionEvaluator, ReencryptionRejection, ReencryptionService, RevocationChecker,
 };
-pub use reencryption::{ReencryptionRequest, ReencryptionResponse};
 pub use retrieval_kit::RetrievalKit;
-pub use revocation_order::RevocationOrder;
-pub use treasure_map::{EncryptedTreasureMap, TreasureMap};
-pub use versioning::ProtocolObject;
+pub use retrieval_plan::{RetrievalError, RetrievalPlan};
+pub use revocation_order::{RevocationCertificate, RevocationOrder, RevocationReceipt};
+pub use revocation_registry::{
+    is_kfrag_revoked, revoke, revoke_policy, InMemoryRevocationRegistry, KeyFragDigest,
+    RevocationError, RevocationRegistry, RevocationRegistrySnapshot,
+};
+pub use siwe::{
+    SiweAuthentication, SiweError, SiweMessage, SiweVerificationParameters, EIP4361_SCHEME,
+};
+pub use treasure_map::{
+    EncryptedTreasureMap, MultiRecipientEncryptedTreasureMap, TreasureMap, TreasureMapAmendment,
+};
+pub use treasure_map_builder::{TreasureMapBuilder, TreasureMapError, TreasureMapProblem};
+pub use versioning::{DeserializationError, ProtocolObject};
 
 // Re-export umbral_pre so that the users don't have to version-match.
 pub use umbral_pre;
+
+// Re-export serde_json since its `Value` is a part of the condition tree API.
+pub use serde_json;
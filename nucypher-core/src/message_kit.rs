@@ -1,5 +1,5 @@
 use alloc::boxed::Box;
-use alloc::string::String;
+use alloc::string::{String, ToString};
 
 use serde::{Deserialize, Serialize};
 use umbral_pre::{
@@ -7,7 +7,8 @@ use umbral_pre::{
     EncryptionError, PublicKey, ReencryptionError, SecretKey, VerifiedCapsuleFrag,
 };
 
-use crate::conditions::Conditions;
+use crate::canonicalization::check_default_limits;
+use crate::conditions::{Conditions, ConditionsError};
 use crate::versioning::{
     messagepack_deserialize, messagepack_serialize, ProtocolObject, ProtocolObjectInner,
 };
@@ -25,11 +26,14 @@ pub struct MessageKit {
 
 impl MessageKit {
     /// Creates a new encrypted message for the given policy key.
+    ///
+    /// Fails if `conditions` exceed [`ConditionsLimits::DEFAULT`](crate::ConditionsLimits::DEFAULT).
     pub fn new(
         policy_encrypting_key: &PublicKey,
         plaintext: &[u8],
         conditions: Option<&Conditions>,
-    ) -> Self {
+    ) -> Result<Self, ConditionsError> {
+        check_default_limits(conditions)?;
         let (capsule, ciphertext) = match encrypt(policy_encrypting_key, plaintext) {
             Ok(result) => result,
             Err(err) => match err {
@@ -38,11 +42,11 @@ impl MessageKit {
                 EncryptionError::PlaintextTooLarge => panic!("encryption failed - out of memory?"),
             },
         };
-        Self {
+        Ok(Self {
             capsule,
             ciphertext,
             conditions: conditions.cloned(),
-        }
+        })
     }
 
     /// Decrypts the message using the original (Alice's) key.
@@ -87,6 +91,10 @@ impl<'a> ProtocolObjectInner<'a> for MessageKit {
             None
         }
     }
+
+    fn validate(&self) -> Result<(), String> {
+        check_default_limits(self.conditions.as_ref()).map_err(|err| err.to_string())
+    }
 }
 
 impl<'a> ProtocolObject<'a> for MessageKit {}
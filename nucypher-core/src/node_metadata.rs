@@ -16,6 +16,7 @@ use crate::versioning::{
 use crate::VerificationError;
 
 /// Indicates an error during canonical address derivation from a signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AddressDerivationError {
     /// Signature is missing from the payload.
     NoSignatureInPayload,
@@ -37,7 +38,7 @@ impl fmt::Display for AddressDerivationError {
 }
 
 /// Mimics the format of `eth_account.messages.encode_defunct()` which NuCypher codebase uses.
-fn encode_defunct(message: &[u8]) -> Keccak256 {
+pub(crate) fn encode_defunct(message: &[u8]) -> Keccak256 {
     Keccak256::new()
         .chain(b"\x19")
         .chain(b"E") // version
@@ -242,7 +243,7 @@ impl MetadataResponse {
         {
             Ok(self.payload)
         } else {
-            Err(VerificationError)
+            Err(VerificationError::InvalidSignature)
         }
     }
 }
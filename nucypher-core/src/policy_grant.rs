@@ -0,0 +1,315 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+use umbral_pre::{generate_kfrags, PublicKey, SecretKey, Signer};
+
+use crate::address::Address;
+use crate::hrac::HRAC;
+use crate::key_frag::PolicyValidity;
+use crate::node_metadata::NodeMetadata;
+use crate::revocation_order::RevocationOrder;
+use crate::treasure_map::{EncryptedTreasureMap, TreasureMap};
+use crate::treasure_map_builder::{TreasureMapBuilder, TreasureMapError, TreasureMapProblem};
+use crate::versioning::{
+    messagepack_deserialize, messagepack_serialize, ProtocolObject, ProtocolObjectInner,
+};
+
+/// An error returned by [`PolicyGrant::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyGrantError {
+    /// The metadata of the Ursula with this address has an invalid signature.
+    InvalidNodeMetadata(Address),
+    /// The treasure map could not be created.
+    TreasureMap(TreasureMapError),
+}
+
+impl fmt::Display for PolicyGrantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNodeMetadata(address) => {
+                write!(f, "invalid node metadata for {:?}", address)
+            }
+            Self::TreasureMap(_) => write!(f, "the treasure map could not be created"),
+        }
+    }
+}
+
+impl From<TreasureMapError> for PolicyGrantError {
+    fn from(err: TreasureMapError) -> Self {
+        Self::TreasureMap(err)
+    }
+}
+
+/// Revocation orders for all the Ursulas of a policy,
+/// to be stored by Alice until she decides to revoke it.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct RevocationKit {
+    /// Policy HRAC.
+    pub hrac: HRAC,
+    /// Revocation orders for each Ursula of the policy.
+    pub revocation_orders: BTreeMap<Address, RevocationOrder>,
+}
+
+impl RevocationKit {
+    /// Makes revocation orders for all destinations in the treasure map,
+    /// issued at the given time (UNIX timestamp).
+    pub fn new(signer: &Signer, treasure_map: &TreasureMap, issued_at: u64) -> Self {
+        Self {
+            hrac: treasure_map.hrac,
+            revocation_orders: treasure_map
+                .destinations
+                .keys()
+                .copied()
+                .zip(treasure_map.make_revocation_orders(signer, issued_at))
+                .collect(),
+        }
+    }
+}
+
+impl<'a> ProtocolObjectInner<'a> for RevocationKit {
+    fn brand() -> [u8; 4] {
+        *b"RvKt"
+    }
+
+    fn version() -> (u16, u16) {
+        (1, 0)
+    }
+
+    fn unversioned_to_bytes(&self) -> Box<[u8]> {
+        messagepack_serialize(&self)
+    }
+
+    fn unversioned_from_bytes(minor_version: u16, bytes: &[u8]) -> Option<Result<Self, String>> {
+        if minor_version == 0 {
+            Some(messagepack_deserialize(bytes))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> ProtocolObject<'a> for RevocationKit {}
+
+/// The result of Alice granting a policy to Bob.
+#[derive(PartialEq, Debug, Clone)]
+pub struct PolicyGrant {
+    /// Policy HRAC.
+    pub hrac: HRAC,
+    /// A key to create encrypted messages under this policy.
+    pub policy_encrypting_key: PublicKey,
+    /// The treasure map encrypted for Bob.
+    pub encrypted_treasure_map: EncryptedTreasureMap,
+    /// Revocation orders for the Ursulas of the policy.
+    pub revocation_kit: RevocationKit,
+}
+
+impl PolicyGrant {
+    /// Grants Bob access to messages encrypted under `label`,
+    /// assigning a key frag to each of `ursulas`.
+    ///
+    /// `delegating_sk` is Alice's secret key for the policy
+    /// (normally derived from `label` with a `SecretKeyFactory`).
+    /// Bob is identified by his encrypting and verifying keys.
+    /// The number of shares is the number of Ursulas given.
+    /// The revocation orders are marked as issued at `now` (UNIX timestamp).
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        signer: &Signer,
+        delegating_sk: &SecretKey,
+        bob_encrypting_key: &PublicKey,
+        bob_verifying_key: &PublicKey,
+        label: &[u8],
+        threshold: u8,
+        ursulas: &[NodeMetadata],
+        validity: &PolicyValidity,
+        now: u64,
+    ) -> Result<Self, PolicyGrantError> {
+        for ursula in ursulas {
+            if !ursula.verify() {
+                return Err(PolicyGrantError::InvalidNodeMetadata(
+                    ursula.payload.staking_provider_address,
+                ));
+            }
+        }
+
+        // `generate_kfrags()` panics on these, so they have to be checked beforehand.
+        if threshold == 0 {
+            return Err(TreasureMapError {
+                problems: vec![TreasureMapProblem::ZeroThreshold],
+            }
+            .into());
+        }
+        if threshold as usize > ursulas.len() {
+            return Err(TreasureMapError {
+                problems: vec![TreasureMapProblem::ThresholdExceedsShares {
+                    threshold,
+                    shares: ursulas.len(),
+                }],
+            }
+            .into());
+        }
+
+        let policy_encrypting_key = delegating_sk.public_key();
+        let hrac = HRAC::new(&signer.verifying_key(), bob_verifying_key, label);
+        let kfrags = generate_kfrags(
+            delegating_sk,
+            bob_encrypting_key,
+            signer,
+            threshold as usize,
+            ursulas.len(),
+            true,
+            true,
+        );
+
+        let mut builder = TreasureMapBuilder::new(
+            &hrac,
+            &policy_encrypting_key,
+            bob_encrypting_key,
+            threshold,
+            validity,
+        );
+        for (ursula, kfrag) in ursulas.iter().zip(kfrags.into_vec()) {
+            builder.add_destination(
+                &ursula.payload.staking_provider_address,
+                &ursula.payload.encrypting_key,
+                kfrag,
+            );
+        }
+        let treasure_map = builder.build(signer)?;
+
+        Ok(Self {
+            hrac,
+            policy_encrypting_key,
+            encrypted_treasure_map: treasure_map.encrypt(signer, bob_encrypting_key)?,
+            revocation_kit: RevocationKit::new(signer, &treasure_map, now),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use umbral_pre::{RecoverableSignature, SecretKey, Signer};
+
+    use super::{PolicyGrant, PolicyGrantError, RevocationKit};
+    use crate::{
+        Address, NodeMetadata, NodeMetadataPayload, PolicyValidity, ProtocolObject,
+        TreasureMapProblem,
+    };
+
+    fn make_ursula(index: u8) -> (SecretKey, NodeMetadata) {
+        let signing_sk = SecretKey::random();
+        let encrypting_sk = SecretKey::random();
+        let mut address = [0u8; Address::SIZE];
+        address[0] = index;
+        let payload = NodeMetadataPayload {
+            staking_provider_address: Address::new(&address),
+            domain: "localhost".into(),
+            timestamp_epoch: 1546300800,
+            verifying_key: signing_sk.public_key(),
+            encrypting_key: encrypting_sk.public_key(),
+            certificate_der: b"certificate"[..].into(),
+            host: "127.0.0.1".into(),
+            port: 9151,
+            operator_signature: RecoverableSignature::try_from_be_bytes(&[1u8; 65]).unwrap(),
+        };
+        let metadata = NodeMetadata::new(&Signer::new(signing_sk), &payload);
+        (encrypting_sk, metadata)
+    }
+
+    #[test]
+    fn grant() {
+        let publisher_sk = SecretKey::random();
+        let signer = Signer::new(publisher_sk.clone());
+        let delegating_sk = SecretKey::random();
+        let bob_sk = SecretKey::random();
+        let bob_verifying_key = SecretKey::random().public_key();
+        let validity = PolicyValidity::new(2000, None);
+        let (ursula_sks, ursulas): (Vec<_>, Vec<_>) = (1..=3).map(make_ursula).unzip();
+
+        let grant = PolicyGrant::new(
+            &signer,
+            &delegating_sk,
+            &bob_sk.public_key(),
+            &bob_verifying_key,
+            b"label",
+            2,
+            &ursulas,
+            &validity,
+            1000,
+        )
+        .unwrap();
+        assert_eq!(grant.policy_encrypting_key, delegating_sk.public_key());
+
+        let treasure_map = grant
+            .encrypted_treasure_map
+            .decrypt(&bob_sk, &publisher_sk.public_key())
+            .unwrap();
+        assert_eq!(treasure_map.hrac, grant.hrac);
+        assert_eq!(treasure_map.threshold, 2);
+        for (ursula_sk, ursula) in ursula_sks.iter().zip(ursulas.iter()) {
+            let address = ursula.payload.staking_provider_address;
+            let (_kfrag, kfrag_validity) = treasure_map.destinations[&address]
+                .decrypt(ursula_sk, &grant.hrac, &publisher_sk.public_key())
+                .unwrap();
+            assert_eq!(kfrag_validity, validity);
+
+            let (revoked_address, revoked_ekfrag) = grant.revocation_kit.revocation_orders
+                [&address]
+                .clone()
+                .verify(&publisher_sk.public_key())
+                .unwrap();
+            assert_eq!(revoked_address, address);
+            assert_eq!(revoked_ekfrag, treasure_map.destinations[&address]);
+        }
+
+        let revocation_kit = &grant.revocation_kit;
+        assert_eq!(
+            &RevocationKit::from_bytes(&revocation_kit.to_bytes()).unwrap(),
+            revocation_kit
+        );
+
+        let err = PolicyGrant::new(
+            &signer,
+            &delegating_sk,
+            &bob_sk.public_key(),
+            &bob_verifying_key,
+            b"label",
+            4,
+            &ursulas,
+            &validity,
+            1000,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            PolicyGrantError::TreasureMap(err) if err.problems == [
+                TreasureMapProblem::ThresholdExceedsShares { threshold: 4, shares: 3 }
+            ]
+        ));
+
+        let mut tampered_ursulas = ursulas.clone();
+        tampered_ursulas[1].payload.port = 1;
+        let err = PolicyGrant::new(
+            &signer,
+            &delegating_sk,
+            &bob_sk.public_key(),
+            &bob_verifying_key,
+            b"label",
+            2,
+            &tampered_ursulas,
+            &validity,
+            1000,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            PolicyGrantError::InvalidNodeMetadata(ursulas[1].payload.staking_provider_address)
+        );
+    }
+}
@@ -1,11 +1,17 @@
 use alloc::boxed::Box;
-use alloc::string::String;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::fmt;
 
 use serde::{Deserialize, Serialize};
-use umbral_pre::{Capsule, CapsuleFrag, PublicKey, Signature, Signer, VerifiedCapsuleFrag};
+use sha3::{Digest, Keccak256};
+use umbral_pre::{
+    Capsule, CapsuleFrag, CapsuleFragVerificationError, PublicKey, Signature, Signer,
+    VerifiedCapsuleFrag,
+};
 
-use crate::conditions::{Conditions, Context};
+use crate::canonicalization::check_default_limits;
+use crate::conditions::{Conditions, ConditionsError, Context};
 use crate::hrac::HRAC;
 use crate::key_frag::EncryptedKeyFrag;
 use crate::versioning::{
@@ -13,8 +19,56 @@ use crate::versioning::{
 };
 use crate::VerificationError;
 
+/// An error returned by [`ReencryptionRequest::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestError {
+    /// No capsules were given.
+    NoCapsules,
+    /// The conditions exceed [`ConditionsLimits::DEFAULT`](crate::ConditionsLimits::DEFAULT).
+    Conditions(ConditionsError),
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoCapsules => write!(f, "at least one capsule is required"),
+            Self::Conditions(_) => write!(f, "invalid conditions"),
+        }
+    }
+}
+
+impl From<ConditionsError> for RequestError {
+    fn from(err: ConditionsError) -> Self {
+        Self::Conditions(err)
+    }
+}
+
+/// An error returned by [`ReencryptionResponse::new`] and [`ReencryptionResponse::new_partial`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResponseError {
+    /// The number of capsule frags or refusals differs from the number of requested capsules.
+    CapsuleCountMismatch {
+        /// The number of capsules in the request.
+        expected: usize,
+        /// The number of results given.
+        received: usize,
+    },
+}
+
+impl fmt::Display for ResponseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CapsuleCountMismatch { expected, received } => write!(
+                f,
+                "expected {} results (one per capsule), got {}",
+                expected, received
+            ),
+        }
+    }
+}
+
 /// A request for an Ursula to reencrypt for several capsules.
-#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
 pub struct ReencryptionRequest {
     /// Capsules to re-encrypt.
     pub capsules: Box<[Capsule]>,
@@ -34,6 +88,9 @@ pub struct ReencryptionRequest {
 
 impl ReencryptionRequest {
     /// Creates a new reencryption request.
+    ///
+    /// Fails if `capsules` is empty
+    /// or `conditions` exceed [`ConditionsLimits::DEFAULT`](crate::ConditionsLimits::DEFAULT).
     pub fn new(
         capsules: &[Capsule],
         hrac: &HRAC,
@@ -42,8 +99,12 @@ impl ReencryptionRequest {
         bob_verifying_key: &PublicKey,
         conditions: Option<&Conditions>,
         context: Option<&Context>,
-    ) -> Self {
-        Self {
+    ) -> Result<Self, RequestError> {
+        if capsules.is_empty() {
+            return Err(RequestError::NoCapsules);
+        }
+        check_default_limits(conditions)?;
+        Ok(Self {
             capsules: capsules.to_vec().into(),
             hrac: *hrac,
             encrypted_kfrag: encrypted_kfrag.clone(),
@@ -51,7 +112,7 @@ impl ReencryptionRequest {
             bob_verifying_key: *bob_verifying_key,
             conditions: conditions.cloned(),
             context: context.cloned(),
-        }
+        })
     }
 }
 
@@ -75,97 +136,173 @@ impl<'a> ProtocolObjectInner<'a> for ReencryptionRequest {
             None
         }
     }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.capsules.is_empty() {
+            return Err("the request must contain at least one capsule".into());
+        }
+        check_default_limits(self.conditions.as_ref()).map_err(|err| err.to_string())
+    }
 }
 
 impl<'a> ProtocolObject<'a> for ReencryptionRequest {}
 
+/// The reason an Ursula refused to reencrypt a capsule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RefusalReason {
+    /// The conditions for the capsule are not satisfied.
+    ConditionsNotSatisfied,
+    /// The conditions for the capsule could not be evaluated.
+    ConditionEvaluationFailed,
+}
+
+impl fmt::Display for RefusalReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConditionsNotSatisfied => write!(f, "conditions are not satisfied"),
+            Self::ConditionEvaluationFailed => write!(f, "condition evaluation failed"),
+        }
+    }
+}
+
+/// The reason a capsule frag could not be obtained from a [`ReencryptionResponse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CapsuleFragError {
+    /// The Ursula refused to reencrypt the capsule.
+    Refused(RefusalReason),
+    /// The capsule frag failed verification.
+    VerificationFailed(CapsuleFragVerificationError),
+}
+
+impl fmt::Display for CapsuleFragError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Refused(reason) => write!(f, "reencryption refused: {}", reason),
+            Self::VerificationFailed(err) => {
+                write!(f, "capsule frag verification failed: {}", err)
+            }
+        }
+    }
+}
+
 /// A response from Ursula with reencrypted capsule frags.
+///
+/// The response contains an outcome for each capsule of the request:
+/// either a capsule frag, or the reason the Ursula refused to reencrypt it.
+/// The signature covers the digest of the originating [`ReencryptionRequest`],
+/// so the response cannot be replayed for another request sharing the same capsules.
 #[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
 pub struct ReencryptionResponse {
-    cfrags: Box<[CapsuleFrag]>,
+    results: Box<[Result<CapsuleFrag, RefusalReason>]>,
     signature: Signature,
 }
 
-fn message_to_sign(capsules: &[Capsule], cfrags: &[CapsuleFrag]) -> Vec<u8> {
-    let capsule_bytes = capsules.iter().fold(Vec::<u8>::new(), |mut acc, capsule| {
-        acc.extend(messagepack_serialize(capsule).as_ref());
-        acc
-    });
-
-    let cfrag_bytes = cfrags.iter().fold(Vec::<u8>::new(), |mut acc, cfrag| {
-        acc.extend(messagepack_serialize(cfrag).as_ref());
-        acc
-    });
-
-    [capsule_bytes, cfrag_bytes].concat()
+fn message_to_sign(
+    request: &ReencryptionRequest,
+    results: &[Result<CapsuleFrag, RefusalReason>],
+) -> Vec<u8> {
+    [
+        b"ReencryptionResponse".as_ref(),
+        &Keccak256::digest(request.to_bytes()),
+        &messagepack_serialize(&results),
+    ]
+    .concat()
 }
 
 impl ReencryptionResponse {
-    /// Creates and signs a new reencryption response.
-    pub fn new<'a>(
+    /// Creates and signs a new reencryption response to `request`,
+    /// with `vcfrags` listed in the order of the request's capsules.
+    ///
+    /// Fails if there is not exactly one capsule frag per capsule.
+    pub fn new(
         signer: &Signer,
-        capsules_and_vcfrags: impl IntoIterator<Item = (&'a Capsule, VerifiedCapsuleFrag)>,
-    ) -> Self {
-        let (capsules, vcfrags): (Vec<_>, Vec<_>) = capsules_and_vcfrags.into_iter().unzip();
+        request: &ReencryptionRequest,
+        vcfrags: impl IntoIterator<Item = VerifiedCapsuleFrag>,
+    ) -> Result<Self, ResponseError> {
+        Self::new_partial(signer, request, vcfrags.into_iter().map(Ok))
+    }
 
+    /// Creates and signs a new reencryption response to `request`,
+    /// with either a capsule frag or a refusal reason
+    /// for each of the request's capsules (in the same order).
+    ///
+    /// Fails if there is not exactly one result per capsule.
+    pub fn new_partial(
+        signer: &Signer,
+        request: &ReencryptionRequest,
+        results: impl IntoIterator<Item = Result<VerifiedCapsuleFrag, RefusalReason>>,
+    ) -> Result<Self, ResponseError> {
         // un-verify
-        let cfrags: Vec<_> = vcfrags
+        let results: Vec<_> = results
             .into_iter()
-            .map(|vcfrag| vcfrag.unverify())
+            .map(|result| result.map(|vcfrag| vcfrag.unverify()))
             .collect();
+        if results.len() != request.capsules.len() {
+            return Err(ResponseError::CapsuleCountMismatch {
+                expected: request.capsules.len(),
+                received: results.len(),
+            });
+        }
 
-        let capsules: Vec<_> = capsules.into_iter().cloned().collect();
-
-        let signature = signer.sign(&message_to_sign(&capsules, &cfrags));
+        let signature = signer.sign(&message_to_sign(request, &results));
 
-        ReencryptionResponse {
-            cfrags: cfrags.into_boxed_slice(),
+        Ok(ReencryptionResponse {
+            results: results.into_boxed_slice(),
             signature,
-        }
+        })
     }
 
-    /// Verifies the reencryption response and returns the contained kfrags on success.
+    /// Verifies the reencryption response to `request`.
+    ///
+    /// Returns the outcome for each of the request's capsules (in the same order):
+    /// either a verified capsule frag, or the reason it could not be obtained.
+    /// Fails only if the response as a whole cannot be trusted.
     pub fn verify(
         self,
-        capsules: &[Capsule],
+        request: &ReencryptionRequest,
         alice_verifying_key: &PublicKey,
         ursula_verifying_key: &PublicKey,
         policy_encrypting_key: &PublicKey,
         bob_encrypting_key: &PublicKey,
-    ) -> Result<Box<[VerifiedCapsuleFrag]>, VerificationError> {
-        if capsules.len() != self.cfrags.len() {
-            // Mismatched number of capsules and cfrags
-            return Err(VerificationError);
+    ) -> Result<Box<[Result<VerifiedCapsuleFrag, CapsuleFragError>]>, VerificationError> {
+        let capsules = &request.capsules;
+        if capsules.len() != self.results.len() {
+            return Err(VerificationError::CapsuleCountMismatch {
+                expected: capsules.len(),
+                received: self.results.len(),
+            });
         }
 
         // Validate re-encryption signature
         if !self.signature.verify(
             ursula_verifying_key,
-            &message_to_sign(capsules, &self.cfrags),
+            &message_to_sign(request, &self.results),
         ) {
-            return Err(VerificationError);
+            return Err(VerificationError::InvalidSignature);
         }
 
-        let vcfrags = self
-            .cfrags
+        let results = self
+            .results
             .into_vec()
             .into_iter()
             .zip(capsules.iter())
-            .map(|(cfrag, capsule)| {
-                cfrag.verify(
-                    capsule,
-                    alice_verifying_key,
-                    policy_encrypting_key,
-                    bob_encrypting_key,
-                )
+            .map(|(result, capsule)| {
+                result.map_err(CapsuleFragError::Refused).and_then(|cfrag| {
+                    cfrag
+                        .verify(
+                            capsule,
+                            alice_verifying_key,
+                            policy_encrypting_key,
+                            bob_encrypting_key,
+                        )
+                        // We get a (CapsuleFragVerificationError, CapsuleFrag) tuple
+                        // in the error case, but at this point nobody's interested in the cfrag.
+                        .map_err(|(err, _cfrag)| CapsuleFragError::VerificationFailed(err))
+                })
             })
-            .collect::<Result<Vec<_>, _>>();
+            .collect::<Vec<_>>();
 
-        // From the above statement we get a list of (CapsuleFragVerificationError, CapsuleFrag)
-        // in the error case, but at this point nobody's interested in that.
-        vcfrags
-            .map(|vcfrags| vcfrags.into_boxed_slice())
-            .map_err(|_err| VerificationError)
+        Ok(results.into_boxed_slice())
     }
 }
 
@@ -175,7 +312,7 @@ impl<'a> ProtocolObjectInner<'a> for ReencryptionResponse {
     }
 
     fn version() -> (u16, u16) {
-        (3, 0)
+        (4, 0)
     }
 
     fn unversioned_to_bytes(&self) -> Box<[u8]> {
@@ -196,11 +333,19 @@ impl<'a> ProtocolObject<'a> for ReencryptionResponse {}
 #[cfg(test)]
 mod tests {
     use umbral_pre::SecretKey;
-    use umbral_pre::{encrypt, generate_kfrags, Signer};
+    use umbral_pre::{encrypt, generate_kfrags, reencrypt, Signer};
 
-    use crate::{Conditions, Context, EncryptedKeyFrag, HRAC};
+    use alloc::boxed::Box;
 
-    use super::ReencryptionRequest;
+    use crate::{
+        Conditions, Context, DeserializationError, EncryptedKeyFrag, PolicyValidity,
+        ProtocolObject, HRAC,
+    };
+
+    use super::{
+        CapsuleFragError, ReencryptionRequest, ReencryptionResponse, RefusalReason, RequestError,
+        ResponseError,
+    };
 
     #[test]
     fn conditions_and_context_are_different() {
@@ -227,7 +372,11 @@ mod tests {
             &another_trinket,
             &hrac,
             one_verified_krag_in_particular,
-        );
+            &some_trinket,
+            &another_trinket,
+            &PolicyValidity::new(u64::MAX, None),
+        )
+        .unwrap();
 
         let request = ReencryptionRequest::new(
             &[capsule],
@@ -237,11 +386,144 @@ mod tests {
             &another_trinket,
             Some(&Conditions::new("abcd")),
             Some(&Context::new("efgh")),
-        );
+        )
+        .unwrap();
         let conditions = request.conditions.unwrap();
         assert_eq!(conditions.as_ref(), "abcd");
 
         let context = request.context.unwrap();
         assert_eq!(context.as_ref(), "efgh");
     }
+
+    #[test]
+    fn empty_request_rejected() {
+        let some_secret = SecretKey::random();
+        let some_trinket = some_secret.public_key();
+        let another_trinket = SecretKey::random().public_key();
+        let (capsule, _ciphertext) = encrypt(&some_trinket, b"peace at dawn").unwrap();
+        let hrac = HRAC::new(&some_trinket, &another_trinket, &[42]);
+        let signer = Signer::new(SecretKey::random());
+        let verified_kfrags =
+            generate_kfrags(&some_secret, &another_trinket, &signer, 1, 1, true, true);
+        let encrypted_kfrag = EncryptedKeyFrag::new(
+            &signer,
+            &another_trinket,
+            &hrac,
+            verified_kfrags[0].clone(),
+            &some_trinket,
+            &another_trinket,
+            &PolicyValidity::new(u64::MAX, None),
+        )
+        .unwrap();
+
+        assert_eq!(
+            ReencryptionRequest::new(
+                &[],
+                &hrac,
+                &encrypted_kfrag,
+                &some_trinket,
+                &another_trinket,
+                None,
+                None,
+            ),
+            Err(RequestError::NoCapsules)
+        );
+
+        let mut request = ReencryptionRequest::new(
+            &[capsule],
+            &hrac,
+            &encrypted_kfrag,
+            &some_trinket,
+            &another_trinket,
+            None,
+            None,
+        )
+        .unwrap();
+        request.capsules = Box::new([]);
+        assert!(matches!(
+            ReencryptionRequest::from_bytes(&request.to_bytes()),
+            Err(DeserializationError::InvalidObject { .. })
+        ));
+    }
+
+    #[test]
+    fn partial_response() {
+        let delegating_sk = SecretKey::random();
+        let delegating_pk = delegating_sk.public_key();
+        let publisher_signer = Signer::new(delegating_sk.clone());
+        let bob_sk = SecretKey::random();
+        let ursula_sk = SecretKey::random();
+        let ursula_signer = Signer::new(ursula_sk.clone());
+
+        let hrac = HRAC::new(&delegating_pk, &bob_sk.public_key(), &[42]);
+        let kfrags = generate_kfrags(
+            &delegating_sk,
+            &bob_sk.public_key(),
+            &publisher_signer,
+            1,
+            1,
+            true,
+            true,
+        );
+        let encrypted_kfrag = EncryptedKeyFrag::new(
+            &publisher_signer,
+            &ursula_sk.public_key(),
+            &hrac,
+            kfrags[0].clone(),
+            &delegating_pk,
+            &bob_sk.public_key(),
+            &PolicyValidity::new(u64::MAX, None),
+        )
+        .unwrap();
+        let (capsule1, _ciphertext) = encrypt(&delegating_pk, b"first").unwrap();
+        let (capsule2, _ciphertext) = encrypt(&delegating_pk, b"second").unwrap();
+        let request = ReencryptionRequest::new(
+            &[capsule1.clone(), capsule2],
+            &hrac,
+            &encrypted_kfrag,
+            &delegating_pk,
+            &bob_sk.public_key(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let vcfrag = reencrypt(&capsule1, kfrags[0].clone());
+        assert_eq!(
+            ReencryptionResponse::new(&ursula_signer, &request, [vcfrag.clone()]).unwrap_err(),
+            ResponseError::CapsuleCountMismatch {
+                expected: 2,
+                received: 1
+            }
+        );
+        let response = ReencryptionResponse::new_partial(
+            &ursula_signer,
+            &request,
+            [
+                Ok(vcfrag.clone()),
+                Err(RefusalReason::ConditionsNotSatisfied),
+            ],
+        )
+        .unwrap();
+        let response = ReencryptionResponse::from_bytes(&response.to_bytes()).unwrap();
+
+        let results = response
+            .verify(
+                &request,
+                &delegating_pk,
+                &ursula_sk.public_key(),
+                &delegating_pk,
+                &bob_sk.public_key(),
+            )
+            .unwrap();
+        assert_eq!(
+            results.as_ref(),
+            [
+                Ok(vcfrag),
+                Err(CapsuleFragError::Refused(
+                    RefusalReason::ConditionsNotSatisfied
+                ))
+            ]
+        );
+    }
 }
@@ -0,0 +1,303 @@
+use alloc::string::String;
+use core::fmt;
+
+use umbral_pre::{reencrypt, SecretKey, Signer};
+
+use crate::conditions::{Conditions, Context};
+use crate::hrac::HRAC;
+use crate::key_frag::{DecryptionError, EncryptedKeyFrag};
+use crate::reencryption::{ReencryptionRequest, ReencryptionResponse};
+
+/// A source of information about revoked key frags.
+pub trait RevocationChecker {
+    /// Returns `true` if the publisher revoked the given key frag of the policy.
+    fn is_revoked(&self, hrac: &HRAC, encrypted_kfrag: &EncryptedKeyFrag) -> Result<bool, String>;
+}
+
+impl<F> RevocationChecker for F
+where
+    F: Fn(&HRAC, &EncryptedKeyFrag) -> Result<bool, String>,
+{
+    fn is_revoked(&self, hrac: &HRAC, encrypted_kfrag: &EncryptedKeyFrag) -> Result<bool, String> {
+        self(hrac, encrypted_kfrag)
+    }
+}
+
+/// Decides whether the conditions of a reencryption request are satisfied.
+pub trait ConditionEvaluator {
+    /// Returns `true` if `conditions` are satisfied in the given context.
+    fn evaluate(&self, conditions: &Conditions, context: Option<&Context>) -> Result<bool, String>;
+}
+
+impl<F> ConditionEvaluator for F
+where
+    F: Fn(&Conditions, Option<&Context>) -> Result<bool, String>,
+{
+    fn evaluate(&self, conditions: &Conditions, context: Option<&Context>) -> Result<bool, String> {
+        self(conditions, context)
+    }
+}
+
+/// The reason a [`ReencryptionService`] refused to reencrypt.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReencryptionRejection {
+    /// The key frag was revoked by the publisher.
+    Revoked,
+    /// The revocation checker failed.
+    RevocationCheckFailed(String),
+    /// The key frag could not be decrypted or verified
+    /// (including the cases of a mismatched HRAC or publisher, and of an inactive policy).
+    InvalidKeyFrag(DecryptionError),
+    /// The conditions of the request are not satisfied.
+    ConditionsNotSatisfied,
+    /// The condition evaluator failed.
+    ConditionEvaluationFailed(String),
+}
+
+impl fmt::Display for ReencryptionRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Revoked => write!(f, "the key frag was revoked"),
+            Self::RevocationCheckFailed(err) => write!(f, "revocation check failed: {}", err),
+            Self::InvalidKeyFrag(_) => write!(f, "invalid key frag"),
+            Self::ConditionsNotSatisfied => write!(f, "conditions are not satisfied"),
+            Self::ConditionEvaluationFailed(err) => {
+                write!(f, "condition evaluation failed: {}", err)
+            }
+        }
+    }
+}
+
+/// Ursula's handler of reencryption requests.
+pub struct ReencryptionService<R, E> {
+    signer: Signer,
+    decrypting_key: SecretKey,
+    revocation_checker: R,
+    condition_evaluator: E,
+}
+
+impl<R: RevocationChecker, E: ConditionEvaluator> ReencryptionService<R, E> {
+    /// Creates a new service for the Ursula with the given signer
+    /// and the secret key corresponding to her encrypting key.
+    pub fn new(
+        signer: Signer,
+        decrypting_key: SecretKey,
+        revocation_checker: R,
+        condition_evaluator: E,
+    ) -> Self {
+        Self {
+            signer,
+            decrypting_key,
+            revocation_checker,
+            condition_evaluator,
+        }
+    }
+
+    /// Returns the revocation checker of the service.
+    pub fn revocation_checker(&self) -> &R {
+        &self.revocation_checker
+    }
+
+    /// Returns the revocation checker of the service,
+    /// allowing to update it (e.g. to record a revocation order).
+    pub fn revocation_checker_mut(&mut self) -> &mut R {
+        &mut self.revocation_checker
+    }
+
+    /// Validates the request at the given time (UNIX timestamp),
+    /// and reencrypts its capsules on success.
+    ///
+    /// The checks are performed in the following order:
+    /// the key frag is not revoked, it is authorized by the publisher for the request's HRAC
+    /// and the policy is active, and the conditions (if any) are satisfied.
+    ///
+    /// The key frag, its revocation status and the conditions are shared by all the capsules
+    /// of the request, so the service either reencrypts all of them or rejects the request
+    /// as a whole, and never refuses individual capsules. Ursulas that have conditions
+    /// for each capsule can respond with [`ReencryptionResponse::new_partial`] instead.
+    pub fn handle(
+        &self,
+        request: &ReencryptionRequest,
+        now: u64,
+    ) -> Result<ReencryptionResponse, ReencryptionRejection> {
+        if self
+            .revocation_checker
+            .is_revoked(&request.hrac, &request.encrypted_kfrag)
+            .map_err(ReencryptionRejection::RevocationCheckFailed)?
+        {
+            return Err(ReencryptionRejection::Revoked);
+        }
+
+        let verified_kfrag = request
+            .encrypted_kfrag
+            .decrypt_at(
+                &self.decrypting_key,
+                &request.hrac,
+                &request.publisher_verifying_key,
+                now,
+            )
+            .map_err(ReencryptionRejection::InvalidKeyFrag)?;
+
+        if let Some(conditions) = &request.conditions {
+            if !self
+                .condition_evaluator
+                .evaluate(conditions, request.context.as_ref())
+                .map_err(ReencryptionRejection::ConditionEvaluationFailed)?
+            {
+                return Err(ReencryptionRejection::ConditionsNotSatisfied);
+            }
+        }
+
+        let vcfrags = request
+            .capsules
+            .iter()
+            .map(|capsule| reencrypt(capsule, verified_kfrag.clone()));
+        Ok(ReencryptionResponse::new(&self.signer, request, vcfrags)
+            .expect("a capsule frag is made for each requested capsule"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+
+    use umbral_pre::{encrypt, generate_kfrags, SecretKey, Signer};
+
+    use super::{ReencryptionRejection, ReencryptionService};
+    use crate::{
+        Conditions, Context, DecryptionError, EncryptedKeyFrag, PolicyValidity,
+        ReencryptionRequest, VerificationError, HRAC,
+    };
+
+    #[test]
+    fn handle() {
+        let delegating_sk = SecretKey::random();
+        let publisher_signer = Signer::new(SecretKey::random());
+        let publisher_vk = publisher_signer.verifying_key();
+        let bob_sk = SecretKey::random();
+        let bob_vk = SecretKey::random().public_key();
+        let ursula_sk = SecretKey::random();
+        let ursula_signer = Signer::new(SecretKey::random());
+
+        let hrac = HRAC::new(&publisher_vk, &bob_vk, b"label");
+        let kfrags = generate_kfrags(
+            &delegating_sk,
+            &bob_sk.public_key(),
+            &publisher_signer,
+            1,
+            1,
+            true,
+            true,
+        );
+        let ekfrag = EncryptedKeyFrag::new(
+            &publisher_signer,
+            &ursula_sk.public_key(),
+            &hrac,
+            kfrags[0].clone(),
+            &delegating_sk.public_key(),
+            &bob_sk.public_key(),
+            &PolicyValidity::new(2000, Some(1000)),
+        )
+        .unwrap();
+        let (capsule, _ciphertext) = encrypt(&delegating_sk.public_key(), b"message").unwrap();
+        let capsules = [capsule];
+        let make_request = |hrac: &HRAC, conditions: Option<&Conditions>| {
+            ReencryptionRequest::new(
+                &capsules,
+                hrac,
+                &ekfrag,
+                &publisher_vk,
+                &bob_vk,
+                conditions,
+                Some(&Context::new("context")),
+            )
+            .unwrap()
+        };
+
+        let revoked_ekfrag = ekfrag.clone();
+        let service = ReencryptionService::new(
+            ursula_signer.clone(),
+            ursula_sk.clone(),
+            |_hrac: &HRAC, _ekfrag: &EncryptedKeyFrag| Ok::<_, String>(false),
+            |conditions: &Conditions, context: Option<&Context>| match conditions.as_ref() {
+                "allow" => Ok(context.map(|c| c.as_ref()) == Some("context")),
+                "deny" => Ok(false),
+                _ => Err("unknown condition".to_string()),
+            },
+        );
+
+        let request = make_request(&hrac, None);
+        let response = service.handle(&request, 1500).unwrap();
+
+        // The response is bound to the request it was made for.
+        let allow = Conditions::new("allow");
+        assert_eq!(
+            response
+                .clone()
+                .verify(
+                    &make_request(&hrac, Some(&allow)),
+                    &publisher_vk,
+                    &ursula_signer.verifying_key(),
+                    &delegating_sk.public_key(),
+                    &bob_sk.public_key(),
+                )
+                .unwrap_err(),
+            VerificationError::InvalidSignature
+        );
+
+        let vcfrags = response
+            .verify(
+                &request,
+                &publisher_vk,
+                &ursula_signer.verifying_key(),
+                &delegating_sk.public_key(),
+                &bob_sk.public_key(),
+            )
+            .unwrap();
+        assert_eq!(vcfrags.len(), 1);
+
+        assert!(service
+            .handle(&make_request(&hrac, Some(&allow)), 1500)
+            .is_ok());
+        assert_eq!(
+            service
+                .handle(&make_request(&hrac, Some(&Conditions::new("deny"))), 1500)
+                .unwrap_err(),
+            ReencryptionRejection::ConditionsNotSatisfied
+        );
+        assert_eq!(
+            service
+                .handle(&make_request(&hrac, Some(&Conditions::new("?"))), 1500)
+                .unwrap_err(),
+            ReencryptionRejection::ConditionEvaluationFailed("unknown condition".into())
+        );
+        assert_eq!(
+            service
+                .handle(&make_request(&hrac, None), 2500)
+                .unwrap_err(),
+            ReencryptionRejection::InvalidKeyFrag(DecryptionError::Expired)
+        );
+        let other_hrac = HRAC::new(&publisher_vk, &bob_vk, b"other label");
+        assert!(matches!(
+            service
+                .handle(&make_request(&other_hrac, None), 1500)
+                .unwrap_err(),
+            ReencryptionRejection::InvalidKeyFrag(_)
+        ));
+
+        let revoking_service = ReencryptionService::new(
+            ursula_signer,
+            ursula_sk,
+            move |_hrac: &HRAC, ekfrag: &EncryptedKeyFrag| {
+                Ok::<_, String>(ekfrag == &revoked_ekfrag)
+            },
+            |_conditions: &Conditions, _context: Option<&Context>| Ok::<_, String>(true),
+        );
+        assert_eq!(
+            revoking_service
+                .handle(&make_request(&hrac, Some(&allow)), 1500)
+                .unwrap_err(),
+            ReencryptionRejection::Revoked
+        );
+    }
+}
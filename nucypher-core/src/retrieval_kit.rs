@@ -1,12 +1,13 @@
 use alloc::boxed::Box;
 use alloc::collections::BTreeSet;
-use alloc::string::String;
+use alloc::string::{String, ToString};
 
 use serde::{Deserialize, Serialize};
 use umbral_pre::Capsule;
 
 use crate::address::Address;
-use crate::conditions::Conditions;
+use crate::canonicalization::check_default_limits;
+use crate::conditions::{Conditions, ConditionsError};
 use crate::message_kit::MessageKit;
 use crate::versioning::{
     messagepack_deserialize, messagepack_serialize, ProtocolObject, ProtocolObjectInner,
@@ -15,7 +16,7 @@ use crate::versioning::{
 /// An object encapsulating the information necessary for retrieval of cfrags from Ursulas.
 /// Contains the capsule and the checksum addresses of Ursulas from which the requester
 /// already received cfrags.
-#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct RetrievalKit {
     /// The ciphertext's capsule.
     pub capsule: Capsule,
@@ -36,17 +37,20 @@ impl RetrievalKit {
     }
 
     /// Creates a new retrieval kit recording the addresses already queried for reencryption.
+    ///
+    /// Fails if `conditions` exceed [`ConditionsLimits::DEFAULT`](crate::ConditionsLimits::DEFAULT).
     pub fn new(
         capsule: &Capsule,
         queried_addresses: impl IntoIterator<Item = Address>,
         conditions: Option<&Conditions>,
-    ) -> Self {
+    ) -> Result<Self, ConditionsError> {
+        check_default_limits(conditions)?;
         // Can store cfrags too, if we're worried about Ursulas supplying duplicate ones.
-        Self {
+        Ok(Self {
             capsule: capsule.clone(),
             queried_addresses: queried_addresses.into_iter().collect(),
             conditions: conditions.cloned(),
-        }
+        })
     }
 }
 
@@ -70,6 +74,10 @@ impl<'a> ProtocolObjectInner<'a> for RetrievalKit {
             None
         }
     }
+
+    fn validate(&self) -> Result<(), String> {
+        check_default_limits(self.conditions.as_ref()).map_err(|err| err.to_string())
+    }
 }
 
 impl<'a> ProtocolObject<'a> for RetrievalKit {}
@@ -0,0 +1,430 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use core::fmt;
+
+use umbral_pre::{Capsule, PublicKey, VerifiedCapsuleFrag};
+
+use crate::address::Address;
+use crate::canonicalization::check_default_limits;
+use crate::conditions::{Conditions, ConditionsError, Context};
+use crate::error::VerificationError;
+use crate::reencryption::{ReencryptionRequest, ReencryptionResponse};
+use crate::retrieval_kit::RetrievalKit;
+use crate::treasure_map::TreasureMap;
+
+/// An error returned by [`RetrievalPlan::add_response`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RetrievalError {
+    /// There is no pending request to the Ursula with this address.
+    UnexpectedResponse(Address),
+    /// The response of the Ursula with this address failed verification.
+    VerificationFailed {
+        /// The address of the Ursula.
+        address: Address,
+        /// The verification error.
+        error: VerificationError,
+    },
+}
+
+impl fmt::Display for RetrievalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedResponse(address) => {
+                write!(f, "no pending request to {:?}", address)
+            }
+            Self::VerificationFailed { address, .. } => {
+                write!(f, "response from {:?} failed verification", address)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CapsuleState {
+    capsule: Capsule,
+    conditions: Option<Conditions>,
+    queried_addresses: BTreeSet<Address>,
+    vcfrags: BTreeMap<Address, VerifiedCapsuleFrag>,
+    pending: usize,
+}
+
+impl CapsuleState {
+    fn can_ask(&self, address: &Address) -> bool {
+        !self.queried_addresses.contains(address)
+    }
+}
+
+/// Bob's retrieval of capsule frags for a number of message kits under the same policy.
+///
+/// The plan does not perform any network requests itself.
+/// The caller sends the requests returned by [`RetrievalPlan::next_requests`]
+/// to the corresponding Ursulas and feeds the results back
+/// with [`RetrievalPlan::add_response`] or [`RetrievalPlan::report_failure`],
+/// until the plan is complete or exhausted.
+#[derive(Debug, Clone)]
+pub struct RetrievalPlan {
+    treasure_map: TreasureMap,
+    bob_encrypting_key: PublicKey,
+    bob_verifying_key: PublicKey,
+    context: Option<Context>,
+    capsules: Vec<CapsuleState>,
+    // The indices of the capsules requested from each Ursula, and the request itself.
+    pending: BTreeMap<Address, (Vec<usize>, ReencryptionRequest)>,
+}
+
+impl RetrievalPlan {
+    /// Starts a retrieval for the given retrieval kits (one per message kit).
+    ///
+    /// The Ursulas in `RetrievalKit::queried_addresses` are not asked for the corresponding capsule.
+    ///
+    /// Fails if the conditions of any of the retrieval kits exceed
+    /// [`ConditionsLimits::DEFAULT`](crate::ConditionsLimits::DEFAULT).
+    pub fn new<'a>(
+        treasure_map: &TreasureMap,
+        retrieval_kits: impl IntoIterator<Item = &'a RetrievalKit>,
+        bob_encrypting_key: &PublicKey,
+        bob_verifying_key: &PublicKey,
+        context: Option<&Context>,
+    ) -> Result<Self, ConditionsError> {
+        let capsules = retrieval_kits
+            .into_iter()
+            .map(|kit| {
+                check_default_limits(kit.conditions.as_ref())?;
+                Ok(CapsuleState {
+                    capsule: kit.capsule.clone(),
+                    conditions: kit.conditions.clone(),
+                    queried_addresses: kit.queried_addresses.clone(),
+                    vcfrags: BTreeMap::new(),
+                    pending: 0,
+                })
+            })
+            .collect::<Result<Vec<_>, ConditionsError>>()?;
+
+        Ok(Self {
+            treasure_map: treasure_map.clone(),
+            bob_encrypting_key: *bob_encrypting_key,
+            bob_verifying_key: *bob_verifying_key,
+            context: context.cloned(),
+            capsules,
+            pending: BTreeMap::new(),
+        })
+    }
+
+    fn needs_more(&self, state: &CapsuleState) -> bool {
+        state.vcfrags.len() + state.pending < self.treasure_map.threshold as usize
+    }
+
+    /// Returns the requests to send, along with the addresses of the Ursulas to send them to.
+    ///
+    /// Only as many Ursulas are asked for each capsule as needed to reach the threshold,
+    /// assuming all the pending requests succeed.
+    /// Each Ursula has at most one pending request;
+    /// capsules with different conditions are requested from the same Ursula
+    /// in subsequent calls.
+    pub fn next_requests(&mut self) -> Vec<(Address, ReencryptionRequest)> {
+        let mut requests = Vec::new();
+
+        let addresses = self
+            .treasure_map
+            .destinations
+            .keys()
+            .filter(|address| !self.pending.contains_key(address))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for address in addresses {
+            let mut indices = Vec::<usize>::new();
+            for (index, state) in self.capsules.iter().enumerate() {
+                if !self.needs_more(state) || !state.can_ask(&address) {
+                    continue;
+                }
+                // A request can only have one set of conditions.
+                if let Some(first) = indices.first() {
+                    if self.capsules[*first].conditions != state.conditions {
+                        continue;
+                    }
+                }
+                indices.push(index);
+            }
+
+            let first = match indices.first() {
+                Some(first) => &self.capsules[*first],
+                None => continue,
+            };
+            let capsules = indices
+                .iter()
+                .map(|index| self.capsules[*index].capsule.clone())
+                .collect::<Vec<_>>();
+            let request = ReencryptionRequest::new(
+                &capsules,
+                &self.treasure_map.hrac,
+                &self.treasure_map.destinations[&address],
+                &self.treasure_map.publisher_verifying_key,
+                &self.bob_verifying_key,
+                first.conditions.as_ref(),
+                self.context.as_ref(),
+            )
+            // The conditions were checked on construction.
+            .expect("conditions are within limits");
+
+            for index in indices.iter() {
+                let state = &mut self.capsules[*index];
+                state.queried_addresses.insert(address);
+                state.pending += 1;
+            }
+            self.pending.insert(address, (indices, request.clone()));
+            requests.push((address, request));
+        }
+
+        requests
+    }
+
+    fn take_pending(&mut self, address: &Address) -> Option<(Vec<usize>, ReencryptionRequest)> {
+        let (indices, request) = self.pending.remove(address)?;
+        for index in indices.iter() {
+            self.capsules[*index].pending -= 1;
+        }
+        Some((indices, request))
+    }
+
+    /// Verifies and records the response of the Ursula with the given address.
+    ///
+    /// If the verification fails, the capsules are requested from other Ursulas
+    /// on the next call to [`RetrievalPlan::next_requests`].
+    /// The same happens to the individual capsules the Ursula refused to reencrypt,
+    /// or whose capsule frags failed verification.
+    pub fn add_response(
+        &mut self,
+        address: &Address,
+        ursula_verifying_key: &PublicKey,
+        response: ReencryptionResponse,
+    ) -> Result<(), RetrievalError> {
+        let (indices, request) = self
+            .take_pending(address)
+            .ok_or(RetrievalError::UnexpectedResponse(*address))?;
+
+        let results = response
+            .verify(
+                &request,
+                &self.treasure_map.publisher_verifying_key,
+                ursula_verifying_key,
+                &self.treasure_map.policy_encrypting_key,
+                &self.bob_encrypting_key,
+            )
+            .map_err(|error| RetrievalError::VerificationFailed {
+                address: *address,
+                error,
+            })?;
+
+        for (index, result) in indices.into_iter().zip(results.into_vec()) {
+            if let Ok(vcfrag) = result {
+                self.capsules[index].vcfrags.insert(*address, vcfrag);
+            }
+        }
+        Ok(())
+    }
+
+    /// Records that the request to the Ursula with the given address failed
+    /// (e.g. due to a network error),
+    /// so that the capsules are requested from other Ursulas
+    /// on the next call to [`RetrievalPlan::next_requests`].
+    pub fn report_failure(&mut self, address: &Address) {
+        self.take_pending(address);
+    }
+
+    /// Returns `true` if enough capsule frags were retrieved
+    /// to decrypt the message kit with the given index.
+    pub fn is_decryptable(&self, index: usize) -> bool {
+        matches!(
+            self.capsules.get(index),
+            Some(state) if state.vcfrags.len() >= self.treasure_map.threshold as usize
+        )
+    }
+
+    /// Returns `true` if all the message kits can be decrypted.
+    pub fn is_complete(&self) -> bool {
+        (0..self.capsules.len()).all(|index| self.is_decryptable(index))
+    }
+
+    /// Returns `true` if the retrieval is not complete,
+    /// but there are no pending requests and no Ursulas left to ask.
+    pub fn is_exhausted(&self) -> bool {
+        self.pending.is_empty()
+            && !self.is_complete()
+            && !self.capsules.iter().any(|state| {
+                self.needs_more(state)
+                    && self
+                        .treasure_map
+                        .destinations
+                        .keys()
+                        .any(|address| state.can_ask(address))
+            })
+    }
+
+    /// Returns the verified capsule frags retrieved so far
+    /// for the message kit with the given index.
+    pub fn verified_cfrags(&self, index: usize) -> Vec<VerifiedCapsuleFrag> {
+        self.capsules
+            .get(index)
+            .map(|state| state.vcfrags.values().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use umbral_pre::{reencrypt, SecretKey, Signer};
+
+    use super::{RetrievalError, RetrievalPlan};
+    use crate::{
+        Address, MessageKit, PolicyValidity, ReencryptionRequest, ReencryptionResponse,
+        RetrievalKit, TreasureMap, VerificationError, HRAC,
+    };
+
+    fn address(index: u8) -> Address {
+        let mut address = [0u8; Address::SIZE];
+        address[0] = index;
+        Address::new(&address)
+    }
+
+    fn respond(
+        ursula_sk: &SecretKey,
+        publisher_sk: &SecretKey,
+        request: &ReencryptionRequest,
+    ) -> ReencryptionResponse {
+        let (vkfrag, _validity) = request
+            .encrypted_kfrag
+            .decrypt(ursula_sk, &request.hrac, &publisher_sk.public_key())
+            .unwrap();
+        let vcfrags = request
+            .capsules
+            .iter()
+            .map(|capsule| reencrypt(capsule, vkfrag.clone()));
+        ReencryptionResponse::new(&Signer::new(ursula_sk.clone()), request, vcfrags).unwrap()
+    }
+
+    #[test]
+    fn retrieval() {
+        let publisher_sk = SecretKey::random();
+        let signer = Signer::new(publisher_sk.clone());
+        let delegating_sk = SecretKey::random();
+        let bob_sk = SecretKey::random();
+        let bob_verifying_key = SecretKey::random().public_key();
+        let ursula_sks = [
+            SecretKey::random(),
+            SecretKey::random(),
+            SecretKey::random(),
+        ];
+
+        let hrac = HRAC::new(&signer.verifying_key(), &bob_verifying_key, b"label");
+        let kfrags = umbral_pre::generate_kfrags(
+            &delegating_sk,
+            &bob_sk.public_key(),
+            &signer,
+            2,
+            3,
+            true,
+            true,
+        );
+        let treasure_map = TreasureMap::new(
+            &signer,
+            &hrac,
+            &delegating_sk.public_key(),
+            &bob_sk.public_key(),
+            ursula_sks
+                .iter()
+                .zip(kfrags.iter())
+                .enumerate()
+                .map(|(i, (sk, kfrag))| (address(i as u8 + 1), (sk.public_key(), kfrag.clone()))),
+            2,
+            &PolicyValidity::new(u64::MAX, None),
+        );
+
+        let message_kits = [b"first".as_ref(), b"second"]
+            .iter()
+            .map(|plaintext| MessageKit::new(&delegating_sk.public_key(), plaintext, None).unwrap())
+            .collect::<Vec<_>>();
+        let retrieval_kits = [
+            RetrievalKit::from_message_kit(&message_kits[0]),
+            RetrievalKit::new(&message_kits[1].capsule, [address(1)], None).unwrap(),
+        ];
+
+        let mut plan = RetrievalPlan::new(
+            &treasure_map,
+            &retrieval_kits,
+            &bob_sk.public_key(),
+            &bob_verifying_key,
+            None,
+        )
+        .unwrap();
+
+        // Two Ursulas are asked for each capsule, skipping the already queried one.
+        let requests = plan.next_requests();
+        let requested = requests
+            .iter()
+            .map(|(address, request)| (*address, request.capsules.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            requested,
+            [(address(1), 1), (address(2), 2), (address(3), 1)]
+        );
+        assert!(plan.next_requests().is_empty());
+
+        plan.add_response(
+            &address(1),
+            &ursula_sks[0].public_key(),
+            respond(&ursula_sks[0], &publisher_sk, &requests[0].1),
+        )
+        .unwrap();
+        assert_eq!(
+            plan.add_response(
+                &address(2),
+                &ursula_sks[0].public_key(),
+                respond(&ursula_sks[1], &publisher_sk, &requests[1].1),
+            ),
+            Err(RetrievalError::VerificationFailed {
+                address: address(2),
+                error: VerificationError::InvalidSignature
+            })
+        );
+        plan.report_failure(&address(3));
+        assert!(!plan.is_decryptable(0));
+
+        // Only the first capsule has an Ursula left to ask.
+        let requests = plan.next_requests();
+        assert_eq!(requests.len(), 1);
+        let (next_address, request) = &requests[0];
+        assert_eq!(next_address, &address(3));
+        assert_eq!(request.capsules.as_ref(), [message_kits[0].capsule.clone()]);
+        plan.add_response(
+            &address(3),
+            &ursula_sks[2].public_key(),
+            respond(&ursula_sks[2], &publisher_sk, request),
+        )
+        .unwrap();
+        assert_eq!(
+            plan.add_response(
+                &address(3),
+                &ursula_sks[2].public_key(),
+                respond(&ursula_sks[2], &publisher_sk, request),
+            ),
+            Err(RetrievalError::UnexpectedResponse(address(3)))
+        );
+
+        assert!(plan.is_decryptable(0));
+        assert!(!plan.is_decryptable(1));
+        assert!(!plan.is_complete());
+        assert!(plan.is_exhausted());
+
+        let plaintext = message_kits[0]
+            .decrypt_reencrypted(
+                &bob_sk,
+                &delegating_sk.public_key(),
+                plan.verified_cfrags(0),
+            )
+            .unwrap();
+        assert_eq!(plaintext.as_ref(), b"first");
+    }
+}
@@ -1,60 +1,117 @@
 use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
 use alloc::string::String;
+use alloc::vec::Vec;
 
 use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
 use umbral_pre::{PublicKey, Signature, Signer};
 
 use crate::address::Address;
+use crate::hrac::HRAC;
 use crate::key_frag::EncryptedKeyFrag;
 use crate::versioning::{
     messagepack_deserialize, messagepack_serialize, ProtocolObject, ProtocolObjectInner,
 };
 use crate::VerificationError;
 
+fn order_message(
+    staking_provider_address: &Address,
+    encrypted_kfrag: &EncryptedKeyFrag,
+    hrac: &HRAC,
+    issued_at: u64,
+    reason: Option<u16>,
+) -> Vec<u8> {
+    let reason_bytes = match reason {
+        None => [0u8, 0, 0],
+        Some(code) => {
+            let [hi, lo] = code.to_be_bytes();
+            [1u8, hi, lo]
+        }
+    };
+    [
+        staking_provider_address.as_ref(),
+        hrac.as_ref(),
+        &issued_at.to_be_bytes(),
+        &reason_bytes,
+        &encrypted_kfrag.to_bytes(),
+    ]
+    .concat()
+}
+
 /// Represents a string used by characters to perform a revocation on a specific Ursula.
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct RevocationOrder {
     /// The address of the Ursula that is being revoked.
     staking_provider_address: Address,
     encrypted_kfrag: EncryptedKeyFrag,
+    hrac: HRAC,
+    issued_at: u64,
+    reason: Option<u16>,
     signature: Signature,
 }
 
 impl RevocationOrder {
-    /// Create and sign a new revocation order.
+    /// Create and sign a new revocation order for the key frag of the policy `hrac`,
+    /// issued at the given time (UNIX timestamp) with an optional application-defined reason code.
     pub fn new(
         signer: &Signer,
         staking_provider_address: &Address,
         encrypted_kfrag: &EncryptedKeyFrag,
+        hrac: &HRAC,
+        issued_at: u64,
+        reason: Option<u16>,
     ) -> Self {
         Self {
             staking_provider_address: *staking_provider_address,
             encrypted_kfrag: encrypted_kfrag.clone(),
-            signature: signer.sign(
-                &[
-                    staking_provider_address.as_ref(),
-                    &encrypted_kfrag.to_bytes(),
-                ]
-                .concat(),
-            ),
+            hrac: *hrac,
+            issued_at,
+            reason,
+            signature: signer.sign(&order_message(
+                staking_provider_address,
+                encrypted_kfrag,
+                hrac,
+                issued_at,
+                reason,
+            )),
         }
     }
 
+    /// Returns the HRAC of the policy the revoked key frag belongs to.
+    pub fn hrac(&self) -> HRAC {
+        self.hrac
+    }
+
+    /// Returns the time (UNIX timestamp) the order was issued at.
+    pub fn issued_at(&self) -> u64 {
+        self.issued_at
+    }
+
+    /// Returns the reason code of the revocation, if any.
+    pub fn reason(&self) -> Option<u16> {
+        self.reason
+    }
+
     /// Verifies the revocation order against Alice's key.
     /// On success, returns the staking provider address and the encrypted keyfrag.
+    ///
+    /// The HRAC, the issuance time and the reason code are covered by the signature as well.
     pub fn verify(
         self,
         alice_verifying_key: &PublicKey,
     ) -> Result<(Address, EncryptedKeyFrag), VerificationError> {
-        let message = [
-            self.staking_provider_address.as_ref(),
-            &self.encrypted_kfrag.to_bytes(),
-        ]
-        .concat();
+        let message = order_message(
+            &self.staking_provider_address,
+            &self.encrypted_kfrag,
+            &self.hrac,
+            self.issued_at,
+            self.reason,
+        );
         if self.signature.verify(alice_verifying_key, &message) {
             Ok((self.staking_provider_address, self.encrypted_kfrag))
         } else {
-            Err(VerificationError)
+            Err(VerificationError::InvalidSignature)
         }
     }
 }
@@ -65,7 +122,7 @@ impl<'a> ProtocolObjectInner<'a> for RevocationOrder {
     }
 
     fn version() -> (u16, u16) {
-        (3, 0)
+        (4, 0)
     }
 
     fn unversioned_to_bytes(&self) -> Box<[u8]> {
@@ -82,3 +139,306 @@ impl<'a> ProtocolObjectInner<'a> for RevocationOrder {
 }
 
 impl<'a> ProtocolObject<'a> for RevocationOrder {}
+
+fn certificate_message(
+    hrac: &HRAC,
+    destinations: &BTreeMap<Address, EncryptedKeyFrag>,
+    issued_at: u64,
+) -> Vec<u8> {
+    [
+        b"RevocationCertificate".as_ref(),
+        hrac.as_ref(),
+        &issued_at.to_be_bytes(),
+        &messagepack_serialize(destinations),
+    ]
+    .concat()
+}
+
+/// A revocation of a whole policy, signed by the publisher.
+///
+/// Unlike [`RevocationOrder`], it covers all the destinations of the policy,
+/// and can be broadcast to every Ursula instead of being delivered to each of them.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct RevocationCertificate {
+    hrac: HRAC,
+    destinations: BTreeMap<Address, EncryptedKeyFrag>,
+    issued_at: u64,
+    signature: Signature,
+}
+
+impl RevocationCertificate {
+    /// Create and sign a new revocation certificate for the given policy HRAC
+    /// and all its destinations, issued at the given time (UNIX timestamp).
+    pub fn new(
+        signer: &Signer,
+        hrac: &HRAC,
+        destinations: &BTreeMap<Address, EncryptedKeyFrag>,
+        issued_at: u64,
+    ) -> Self {
+        Self {
+            hrac: *hrac,
+            destinations: destinations.clone(),
+            issued_at,
+            signature: signer.sign(&certificate_message(hrac, destinations, issued_at)),
+        }
+    }
+
+    /// Returns the HRAC of the revoked policy.
+    pub fn hrac(&self) -> HRAC {
+        self.hrac
+    }
+
+    /// Returns the time (UNIX timestamp) the certificate was issued at.
+    pub fn issued_at(&self) -> u64 {
+        self.issued_at
+    }
+
+    /// Verifies the revocation certificate against Alice's key.
+    /// On success, returns the policy HRAC and the revoked destinations.
+    ///
+    /// The issuance time is covered by the signature as well.
+    pub fn verify(
+        self,
+        alice_verifying_key: &PublicKey,
+    ) -> Result<(HRAC, BTreeMap<Address, EncryptedKeyFrag>), VerificationError> {
+        let message = certificate_message(&self.hrac, &self.destinations, self.issued_at);
+        if self.signature.verify(alice_verifying_key, &message) {
+            Ok((self.hrac, self.destinations))
+        } else {
+            Err(VerificationError::InvalidSignature)
+        }
+    }
+}
+
+impl<'a> ProtocolObjectInner<'a> for RevocationCertificate {
+    fn brand() -> [u8; 4] {
+        *b"RvCt"
+    }
+
+    fn version() -> (u16, u16) {
+        (1, 0)
+    }
+
+    fn unversioned_to_bytes(&self) -> Box<[u8]> {
+        messagepack_serialize(&self)
+    }
+
+    fn unversioned_from_bytes(minor_version: u16, bytes: &[u8]) -> Option<Result<Self, String>> {
+        if minor_version == 0 {
+            Some(messagepack_deserialize(bytes))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> ProtocolObject<'a> for RevocationCertificate {}
+
+fn receipt_message(order: &RevocationOrder, acknowledged_at: u64) -> Vec<u8> {
+    [
+        b"RevocationReceipt".as_ref(),
+        &Keccak256::digest(order.to_bytes()),
+        &acknowledged_at.to_be_bytes(),
+    ]
+    .concat()
+}
+
+/// An acknowledgement of a [`RevocationOrder`], signed by the Ursula that applied it,
+/// serving as a proof of revocation for the publisher.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct RevocationReceipt {
+    acknowledged_at: u64,
+    signature: Signature,
+}
+
+impl RevocationReceipt {
+    /// Creates and signs a receipt for the order, applied at the given time (UNIX timestamp).
+    pub fn new(signer: &Signer, order: &RevocationOrder, acknowledged_at: u64) -> Self {
+        Self {
+            acknowledged_at,
+            signature: signer.sign(&receipt_message(order, acknowledged_at)),
+        }
+    }
+
+    /// Verifies that the receipt was issued by the Ursula with the given verifying key
+    /// for the given order. On success, returns the time the order was applied at.
+    pub fn verify(
+        self,
+        ursula_verifying_key: &PublicKey,
+        order: &RevocationOrder,
+    ) -> Result<u64, VerificationError> {
+        if self.signature.verify(
+            ursula_verifying_key,
+            &receipt_message(order, self.acknowledged_at),
+        ) {
+            Ok(self.acknowledged_at)
+        } else {
+            Err(VerificationError::InvalidSignature)
+        }
+    }
+}
+
+impl<'a> ProtocolObjectInner<'a> for RevocationReceipt {
+    fn brand() -> [u8; 4] {
+        *b"RvRc"
+    }
+
+    fn version() -> (u16, u16) {
+        (1, 0)
+    }
+
+    fn unversioned_to_bytes(&self) -> Box<[u8]> {
+        messagepack_serialize(&self)
+    }
+
+    fn unversioned_from_bytes(minor_version: u16, bytes: &[u8]) -> Option<Result<Self, String>> {
+        if minor_version == 0 {
+            Some(messagepack_deserialize(bytes))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> ProtocolObject<'a> for RevocationReceipt {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+
+    use umbral_pre::{generate_kfrags, SecretKey, Signer};
+
+    use super::{RevocationCertificate, RevocationOrder, RevocationReceipt};
+    use crate::{
+        Address, EncryptedKeyFrag, PolicyValidity, ProtocolObject, VerificationError, HRAC,
+    };
+
+    fn make_ekfrag(publisher_signer: &Signer, hrac: &HRAC) -> EncryptedKeyFrag {
+        let delegating_sk = SecretKey::random();
+        let receiving_pk = SecretKey::random().public_key();
+        let kfrags = generate_kfrags(
+            &delegating_sk,
+            &receiving_pk,
+            publisher_signer,
+            1,
+            1,
+            true,
+            true,
+        );
+        EncryptedKeyFrag::new(
+            publisher_signer,
+            &SecretKey::random().public_key(),
+            hrac,
+            kfrags[0].clone(),
+            &delegating_sk.public_key(),
+            &receiving_pk,
+            &PolicyValidity::new(2000, None),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn order() {
+        let publisher_signer = Signer::new(SecretKey::random());
+        let hrac = HRAC::new(
+            &publisher_signer.verifying_key(),
+            &SecretKey::random().public_key(),
+            b"label",
+        );
+        let ekfrag = make_ekfrag(&publisher_signer, &hrac);
+        let address = Address::new(&[1; Address::SIZE]);
+
+        let order =
+            RevocationOrder::new(&publisher_signer, &address, &ekfrag, &hrac, 1000, Some(7));
+        let order = RevocationOrder::from_bytes(&order.to_bytes()).unwrap();
+        assert_eq!(order.hrac(), hrac);
+        assert_eq!(order.issued_at(), 1000);
+        assert_eq!(order.reason(), Some(7));
+        assert_eq!(
+            order
+                .clone()
+                .verify(&SecretKey::random().public_key())
+                .unwrap_err(),
+            VerificationError::InvalidSignature
+        );
+        assert_eq!(
+            order.verify(&publisher_signer.verifying_key()).unwrap(),
+            (address, ekfrag)
+        );
+    }
+
+    #[test]
+    fn certificate() {
+        let publisher_signer = Signer::new(SecretKey::random());
+        let hrac = HRAC::new(
+            &publisher_signer.verifying_key(),
+            &SecretKey::random().public_key(),
+            b"label",
+        );
+        let destinations = BTreeMap::from([
+            (
+                Address::new(&[1; Address::SIZE]),
+                make_ekfrag(&publisher_signer, &hrac),
+            ),
+            (
+                Address::new(&[2; Address::SIZE]),
+                make_ekfrag(&publisher_signer, &hrac),
+            ),
+        ]);
+
+        let certificate = RevocationCertificate::new(&publisher_signer, &hrac, &destinations, 1000);
+        let certificate = RevocationCertificate::from_bytes(&certificate.to_bytes()).unwrap();
+        assert_eq!(certificate.hrac(), hrac);
+        assert_eq!(certificate.issued_at(), 1000);
+        assert_eq!(
+            certificate
+                .clone()
+                .verify(&SecretKey::random().public_key())
+                .unwrap_err(),
+            VerificationError::InvalidSignature
+        );
+        assert_eq!(
+            certificate
+                .verify(&publisher_signer.verifying_key())
+                .unwrap(),
+            (hrac, destinations)
+        );
+    }
+
+    #[test]
+    fn receipt() {
+        let publisher_signer = Signer::new(SecretKey::random());
+        let ursula_signer = Signer::new(SecretKey::random());
+        let hrac = HRAC::new(
+            &publisher_signer.verifying_key(),
+            &SecretKey::random().public_key(),
+            b"label",
+        );
+        let ekfrag = make_ekfrag(&publisher_signer, &hrac);
+        let address = Address::new(&[1; Address::SIZE]);
+        let order = RevocationOrder::new(&publisher_signer, &address, &ekfrag, &hrac, 1000, None);
+
+        let receipt = RevocationReceipt::new(&ursula_signer, &order, 1100);
+        let receipt = RevocationReceipt::from_bytes(&receipt.to_bytes()).unwrap();
+        assert_eq!(
+            receipt
+                .clone()
+                .verify(&ursula_signer.verifying_key(), &order),
+            Ok(1100)
+        );
+        assert_eq!(
+            receipt
+                .clone()
+                .verify(&SecretKey::random().public_key(), &order),
+            Err(VerificationError::InvalidSignature)
+        );
+
+        // A receipt does not carry over to another order
+        let other_order =
+            RevocationOrder::new(&publisher_signer, &address, &ekfrag, &hrac, 1001, None);
+        assert_eq!(
+            receipt.verify(&ursula_signer.verifying_key(), &other_order),
+            Err(VerificationError::InvalidSignature)
+        );
+    }
+}
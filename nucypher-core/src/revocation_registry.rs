@@ -0,0 +1,726 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use umbral_pre::{serde_bytes, PublicKey, SecretKey};
+
+use crate::address::Address;
+use crate::hrac::HRAC;
+use crate::key_frag::{DecryptionError, EncryptedKeyFrag};
+use crate::reencryption_service::RevocationChecker;
+use crate::revocation_order::{RevocationCertificate, RevocationOrder};
+use crate::versioning::{
+    messagepack_deserialize, messagepack_serialize, ProtocolObject, ProtocolObjectInner,
+};
+use crate::VerificationError;
+
+/// A digest identifying an [`EncryptedKeyFrag`] in a [`RevocationRegistry`].
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct KeyFragDigest(#[serde(with = "serde_bytes::as_hex")] [u8; KeyFragDigest::SIZE]);
+
+impl KeyFragDigest {
+    /// The size of the digest in bytes.
+    pub const SIZE: usize = 32;
+
+    /// Calculates the digest of an encrypted key frag.
+    pub fn new(encrypted_kfrag: &EncryptedKeyFrag) -> Self {
+        Self(Keccak256::digest(encrypted_kfrag.to_bytes()).into())
+    }
+}
+
+impl From<[u8; KeyFragDigest::SIZE]> for KeyFragDigest {
+    fn from(bytes: [u8; KeyFragDigest::SIZE]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl AsRef<[u8]> for KeyFragDigest {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+/// An error returned by [`revoke`] and [`revoke_policy`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum RevocationError {
+    /// The order or certificate is not signed by the publisher.
+    InvalidOrder(VerificationError),
+    /// The order targets a different Ursula.
+    WrongUrsula {
+        /// The address of this Ursula.
+        expected: Address,
+        /// The address the order targets.
+        received: Address,
+    },
+    /// The certificate does not include a destination with this address.
+    NotADestination(Address),
+    /// The key frag in the order is not authorized for this Ursula
+    /// by the publisher under the given HRAC.
+    InvalidKeyFrag(DecryptionError),
+    /// The order or certificate is issued later than the current time.
+    IssuedInFuture {
+        /// The issuance time of the order (UNIX timestamp).
+        issued_at: u64,
+        /// The current time (UNIX timestamp).
+        now: u64,
+    },
+    /// The key frag was already revoked by an order or certificate issued at the same time
+    /// or later, so this one is either a replay or superseded.
+    Stale {
+        /// The issuance time of the order (UNIX timestamp).
+        issued_at: u64,
+        /// The issuance time of the recorded revocation (UNIX timestamp).
+        revoked_at: u64,
+    },
+}
+
+impl fmt::Display for RevocationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidOrder(_) => write!(f, "invalid revocation order"),
+            Self::WrongUrsula { expected, received } => write!(
+                f,
+                "the order targets {:?} instead of {:?}",
+                received, expected
+            ),
+            Self::NotADestination(address) => {
+                write!(f, "{:?} is not a destination of the policy", address)
+            }
+            Self::InvalidKeyFrag(_) => write!(f, "invalid key frag"),
+            Self::IssuedInFuture { issued_at, now } => write!(
+                f,
+                "the order is issued at {}, later than the current time {}",
+                issued_at, now
+            ),
+            Self::Stale {
+                issued_at,
+                revoked_at,
+            } => write!(
+                f,
+                "the order is issued at {}, but the key frag is already revoked at {}",
+                issued_at, revoked_at
+            ),
+        }
+    }
+}
+
+/// A storage of revoked key frags, keyed by the policy HRAC and the key frag digest.
+///
+/// Orders and certificates are verified by [`revoke`] and [`revoke_policy`]
+/// before anything is recorded here.
+pub trait RevocationRegistry {
+    /// Records the key frag as revoked by an order or certificate issued at `issued_at`
+    /// (UNIX timestamp), replacing the previously recorded time, if any.
+    fn insert(&mut self, hrac: &HRAC, kfrag_digest: &KeyFragDigest, issued_at: u64);
+
+    /// Returns the issuance time of the latest recorded revocation of the key frag, if any.
+    fn revoked_at(&self, hrac: &HRAC, kfrag_digest: &KeyFragDigest) -> Option<u64>;
+}
+
+/// Records the key frag as revoked at `issued_at`, rejecting orders and certificates
+/// issued after `now` or not later than the recorded revocation.
+fn insert_fresh(
+    registry: &mut impl RevocationRegistry,
+    hrac: &HRAC,
+    kfrag_digest: &KeyFragDigest,
+    issued_at: u64,
+    now: u64,
+) -> Result<(), RevocationError> {
+    if issued_at > now {
+        return Err(RevocationError::IssuedInFuture { issued_at, now });
+    }
+    if let Some(revoked_at) = registry.revoked_at(hrac, kfrag_digest) {
+        if issued_at <= revoked_at {
+            return Err(RevocationError::Stale {
+                issued_at,
+                revoked_at,
+            });
+        }
+    }
+    registry.insert(hrac, kfrag_digest, issued_at);
+    Ok(())
+}
+
+/// Verifies a revocation order received by an Ursula at `now` (UNIX timestamp),
+/// and records the key frag as revoked in the registry.
+///
+/// The order must be signed by the publisher, target the Ursula's staking provider address,
+/// and contain a key frag the publisher authorized for her (decryptable with
+/// `decrypting_key`) under the order's HRAC.
+/// It must not be issued after `now`, and must be issued later than any order
+/// or certificate already applied to this key frag, so that a replayed order is rejected.
+pub fn revoke(
+    registry: &mut impl RevocationRegistry,
+    order: &RevocationOrder,
+    publisher_verifying_key: &PublicKey,
+    staking_provider_address: &Address,
+    decrypting_key: &SecretKey,
+    now: u64,
+) -> Result<(), RevocationError> {
+    let hrac = order.hrac();
+    let (address, encrypted_kfrag) = order
+        .clone()
+        .verify(publisher_verifying_key)
+        .map_err(RevocationError::InvalidOrder)?;
+    if &address != staking_provider_address {
+        return Err(RevocationError::WrongUrsula {
+            expected: *staking_provider_address,
+            received: address,
+        });
+    }
+    encrypted_kfrag
+        .decrypt(decrypting_key, &hrac, publisher_verifying_key)
+        .map_err(RevocationError::InvalidKeyFrag)?;
+    insert_fresh(
+        registry,
+        &hrac,
+        &KeyFragDigest::new(&encrypted_kfrag),
+        order.issued_at(),
+        now,
+    )
+}
+
+/// Verifies a policy-wide revocation certificate received by an Ursula
+/// at `now` (UNIX timestamp), and records her key frag as revoked in the registry.
+///
+/// The certificate must be signed by the publisher, and include a destination
+/// with the Ursula's staking provider address and a key frag the publisher authorized
+/// for her (decryptable with `decrypting_key`) under the certificate's HRAC.
+/// The issuance time is checked the same way as in [`revoke`].
+pub fn revoke_policy(
+    registry: &mut impl RevocationRegistry,
+    certificate: &RevocationCertificate,
+    publisher_verifying_key: &PublicKey,
+    staking_provider_address: &Address,
+    decrypting_key: &SecretKey,
+    now: u64,
+) -> Result<(), RevocationError> {
+    let issued_at = certificate.issued_at();
+    let (hrac, destinations) = certificate
+        .clone()
+        .verify(publisher_verifying_key)
+        .map_err(RevocationError::InvalidOrder)?;
+    let encrypted_kfrag = destinations
+        .get(staking_provider_address)
+        .ok_or(RevocationError::NotADestination(*staking_provider_address))?;
+    encrypted_kfrag
+        .decrypt(decrypting_key, &hrac, publisher_verifying_key)
+        .map_err(RevocationError::InvalidKeyFrag)?;
+    insert_fresh(
+        registry,
+        &hrac,
+        &KeyFragDigest::new(encrypted_kfrag),
+        issued_at,
+        now,
+    )
+}
+
+/// Returns `true` if the key frag was revoked in the registry.
+pub fn is_kfrag_revoked(
+    registry: &impl RevocationRegistry,
+    hrac: &HRAC,
+    encrypted_kfrag: &EncryptedKeyFrag,
+) -> bool {
+    registry
+        .revoked_at(hrac, &KeyFragDigest::new(encrypted_kfrag))
+        .is_some()
+}
+
+/// A revocation registry kept in memory.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct InMemoryRevocationRegistry {
+    revoked: BTreeMap<(HRAC, KeyFragDigest), u64>,
+}
+
+impl InMemoryRevocationRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of revoked key frags.
+    pub fn len(&self) -> usize {
+        self.revoked.len()
+    }
+
+    /// Returns `true` if no key frags were revoked.
+    pub fn is_empty(&self) -> bool {
+        self.revoked.is_empty()
+    }
+
+    /// Makes a serializable snapshot of the registry.
+    pub fn snapshot(&self) -> RevocationRegistrySnapshot {
+        RevocationRegistrySnapshot {
+            revoked: self.revoked.clone(),
+        }
+    }
+}
+
+impl From<RevocationRegistrySnapshot> for InMemoryRevocationRegistry {
+    fn from(snapshot: RevocationRegistrySnapshot) -> Self {
+        Self {
+            revoked: snapshot.revoked,
+        }
+    }
+}
+
+impl RevocationRegistry for InMemoryRevocationRegistry {
+    fn insert(&mut self, hrac: &HRAC, kfrag_digest: &KeyFragDigest, issued_at: u64) {
+        self.revoked.insert((*hrac, *kfrag_digest), issued_at);
+    }
+
+    fn revoked_at(&self, hrac: &HRAC, kfrag_digest: &KeyFragDigest) -> Option<u64> {
+        self.revoked.get(&(*hrac, *kfrag_digest)).copied()
+    }
+}
+
+impl RevocationChecker for InMemoryRevocationRegistry {
+    fn is_revoked(&self, hrac: &HRAC, encrypted_kfrag: &EncryptedKeyFrag) -> Result<bool, String> {
+        Ok(is_kfrag_revoked(self, hrac, encrypted_kfrag))
+    }
+}
+
+/// A serialized state of an [`InMemoryRevocationRegistry`],
+/// to be persisted by an Ursula between restarts.
+///
+/// Convert it back into the registry to check or record revocations.
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RevocationRegistrySnapshot {
+    revoked: BTreeMap<(HRAC, KeyFragDigest), u64>,
+}
+
+impl<'a> ProtocolObjectInner<'a> for RevocationRegistrySnapshot {
+    fn brand() -> [u8; 4] {
+        *b"RvRg"
+    }
+
+    fn version() -> (u16, u16) {
+        (1, 0)
+    }
+
+    fn unversioned_to_bytes(&self) -> Box<[u8]> {
+        messagepack_serialize(&self)
+    }
+
+    fn unversioned_from_bytes(minor_version: u16, bytes: &[u8]) -> Option<Result<Self, String>> {
+        if minor_version == 0 {
+            Some(messagepack_deserialize(bytes))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> ProtocolObject<'a> for RevocationRegistrySnapshot {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+    use alloc::string::String;
+
+    use umbral_pre::{encrypt, generate_kfrags, SecretKey, Signer};
+
+    use super::{
+        is_kfrag_revoked, InMemoryRevocationRegistry, KeyFragDigest, RevocationError,
+        RevocationRegistry, RevocationRegistrySnapshot,
+    };
+    use crate::{
+        Address, Conditions, Context, EncryptedKeyFrag, PolicyValidity, ProtocolObject,
+        ReencryptionRejection, ReencryptionRequest, ReencryptionService, RevocationCertificate,
+        RevocationOrder, VerificationError, HRAC,
+    };
+
+    struct Policy {
+        publisher_signer: Signer,
+        delegating_sk: SecretKey,
+        bob_sk: SecretKey,
+        hrac: HRAC,
+        ursula_sk: SecretKey,
+        ursula_address: Address,
+        ekfrag: EncryptedKeyFrag,
+        other_ursula_sk: SecretKey,
+        other_ursula_address: Address,
+        other_ekfrag: EncryptedKeyFrag,
+    }
+
+    impl Policy {
+        fn new() -> Self {
+            let delegating_sk = SecretKey::random();
+            let publisher_signer = Signer::new(SecretKey::random());
+            let bob_sk = SecretKey::random();
+            let hrac = HRAC::new(
+                &publisher_signer.verifying_key(),
+                &bob_sk.public_key(),
+                b"label",
+            );
+            let kfrags = generate_kfrags(
+                &delegating_sk,
+                &bob_sk.public_key(),
+                &publisher_signer,
+                2,
+                2,
+                true,
+                true,
+            );
+            let make_ekfrag = |ursula_sk: &SecretKey, index: usize| {
+                EncryptedKeyFrag::new(
+                    &publisher_signer,
+                    &ursula_sk.public_key(),
+                    &hrac,
+                    kfrags[index].clone(),
+                    &delegating_sk.public_key(),
+                    &bob_sk.public_key(),
+                    &PolicyValidity::new(2000, None),
+                )
+                .unwrap()
+            };
+            let ursula_sk = SecretKey::random();
+            let other_ursula_sk = SecretKey::random();
+            let ekfrag = make_ekfrag(&ursula_sk, 0);
+            let other_ekfrag = make_ekfrag(&other_ursula_sk, 1);
+            Self {
+                publisher_signer,
+                delegating_sk,
+                bob_sk,
+                hrac,
+                ursula_sk,
+                ursula_address: Address::new(&[1; Address::SIZE]),
+                ekfrag,
+                other_ursula_sk,
+                other_ursula_address: Address::new(&[2; Address::SIZE]),
+                other_ekfrag,
+            }
+        }
+
+        fn order(
+            &self,
+            signer: &Signer,
+            address: &Address,
+            ekfrag: &EncryptedKeyFrag,
+            hrac: &HRAC,
+        ) -> RevocationOrder {
+            RevocationOrder::new(signer, address, ekfrag, hrac, 1000, None)
+        }
+
+        fn revoke(
+            &self,
+            registry: &mut InMemoryRevocationRegistry,
+            order: &RevocationOrder,
+        ) -> Result<(), RevocationError> {
+            super::revoke(
+                registry,
+                order,
+                &self.publisher_signer.verifying_key(),
+                &self.ursula_address,
+                &self.ursula_sk,
+                1000,
+            )
+        }
+    }
+
+    #[test]
+    fn revoke() {
+        let policy = Policy::new();
+        let mut registry = InMemoryRevocationRegistry::new();
+        let order = policy.order(
+            &policy.publisher_signer,
+            &policy.ursula_address,
+            &policy.ekfrag,
+            &policy.hrac,
+        );
+        policy.revoke(&mut registry, &order).unwrap();
+        assert_eq!(registry.len(), 1);
+        assert!(is_kfrag_revoked(&registry, &policy.hrac, &policy.ekfrag));
+        assert!(!is_kfrag_revoked(
+            &registry,
+            &policy.hrac,
+            &policy.other_ekfrag
+        ));
+        assert_eq!(
+            registry.revoked_at(&policy.hrac, &KeyFragDigest::new(&policy.ekfrag)),
+            Some(1000)
+        );
+    }
+
+    #[test]
+    fn revoke_replayed() {
+        let policy = Policy::new();
+        let mut registry = InMemoryRevocationRegistry::new();
+        let order = policy.order(
+            &policy.publisher_signer,
+            &policy.ursula_address,
+            &policy.ekfrag,
+            &policy.hrac,
+        );
+        policy.revoke(&mut registry, &order).unwrap();
+        assert_eq!(
+            policy.revoke(&mut registry, &order),
+            Err(RevocationError::Stale {
+                issued_at: 1000,
+                revoked_at: 1000
+            })
+        );
+
+        // A newer order supersedes the recorded one
+        let newer_order = RevocationOrder::new(
+            &policy.publisher_signer,
+            &policy.ursula_address,
+            &policy.ekfrag,
+            &policy.hrac,
+            1001,
+            None,
+        );
+        let publisher_vk = policy.publisher_signer.verifying_key();
+        super::revoke(
+            &mut registry,
+            &newer_order,
+            &publisher_vk,
+            &policy.ursula_address,
+            &policy.ursula_sk,
+            1001,
+        )
+        .unwrap();
+        assert_eq!(
+            registry.revoked_at(&policy.hrac, &KeyFragDigest::new(&policy.ekfrag)),
+            Some(1001)
+        );
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn revoke_issued_in_future() {
+        let policy = Policy::new();
+        let mut registry = InMemoryRevocationRegistry::new();
+        let order = policy.order(
+            &policy.publisher_signer,
+            &policy.ursula_address,
+            &policy.ekfrag,
+            &policy.hrac,
+        );
+        assert_eq!(
+            super::revoke(
+                &mut registry,
+                &order,
+                &policy.publisher_signer.verifying_key(),
+                &policy.ursula_address,
+                &policy.ursula_sk,
+                999,
+            ),
+            Err(RevocationError::IssuedInFuture {
+                issued_at: 1000,
+                now: 999
+            })
+        );
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn revoke_foreign_signature() {
+        let policy = Policy::new();
+        let mut registry = InMemoryRevocationRegistry::new();
+        let order = policy.order(
+            &Signer::new(SecretKey::random()),
+            &policy.ursula_address,
+            &policy.ekfrag,
+            &policy.hrac,
+        );
+        assert_eq!(
+            policy.revoke(&mut registry, &order),
+            Err(RevocationError::InvalidOrder(
+                VerificationError::InvalidSignature
+            ))
+        );
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn revoke_other_ursula() {
+        let policy = Policy::new();
+        let mut registry = InMemoryRevocationRegistry::new();
+        let order = policy.order(
+            &policy.publisher_signer,
+            &policy.other_ursula_address,
+            &policy.other_ekfrag,
+            &policy.hrac,
+        );
+        assert_eq!(
+            policy.revoke(&mut registry, &order),
+            Err(RevocationError::WrongUrsula {
+                expected: policy.ursula_address,
+                received: policy.other_ursula_address
+            })
+        );
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn revoke_foreign_kfrag() {
+        let policy = Policy::new();
+        let mut registry = InMemoryRevocationRegistry::new();
+
+        // Addressed to this Ursula, but with a key frag that is not hers
+        let order = policy.order(
+            &policy.publisher_signer,
+            &policy.ursula_address,
+            &policy.other_ekfrag,
+            &policy.hrac,
+        );
+        assert!(matches!(
+            policy.revoke(&mut registry, &order),
+            Err(RevocationError::InvalidKeyFrag(_))
+        ));
+
+        // Claims the key frag belongs to another policy
+        let other_hrac = HRAC::new(
+            &policy.publisher_signer.verifying_key(),
+            &policy.bob_sk.public_key(),
+            b"other label",
+        );
+        let order = policy.order(
+            &policy.publisher_signer,
+            &policy.ursula_address,
+            &policy.ekfrag,
+            &other_hrac,
+        );
+        assert!(matches!(
+            policy.revoke(&mut registry, &order),
+            Err(RevocationError::InvalidKeyFrag(_))
+        ));
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn revoke_policy() {
+        let policy = Policy::new();
+        let publisher_vk = policy.publisher_signer.verifying_key();
+        let destinations = BTreeMap::from([
+            (policy.ursula_address, policy.ekfrag.clone()),
+            (policy.other_ursula_address, policy.other_ekfrag.clone()),
+        ]);
+        let certificate =
+            RevocationCertificate::new(&policy.publisher_signer, &policy.hrac, &destinations, 1000);
+
+        let mut registry = InMemoryRevocationRegistry::new();
+        let stranger_address = Address::new(&[3; Address::SIZE]);
+        assert_eq!(
+            super::revoke_policy(
+                &mut registry,
+                &certificate,
+                &publisher_vk,
+                &stranger_address,
+                &policy.ursula_sk,
+                1000,
+            ),
+            Err(RevocationError::NotADestination(stranger_address))
+        );
+        assert!(matches!(
+            super::revoke_policy(
+                &mut registry,
+                &certificate,
+                &publisher_vk,
+                &policy.other_ursula_address,
+                &policy.ursula_sk,
+                1000,
+            ),
+            Err(RevocationError::InvalidKeyFrag(_))
+        ));
+        assert!(registry.is_empty());
+
+        super::revoke_policy(
+            &mut registry,
+            &certificate,
+            &publisher_vk,
+            &policy.other_ursula_address,
+            &policy.other_ursula_sk,
+            1000,
+        )
+        .unwrap();
+        assert!(is_kfrag_revoked(
+            &registry,
+            &policy.hrac,
+            &policy.other_ekfrag
+        ));
+        assert!(!is_kfrag_revoked(&registry, &policy.hrac, &policy.ekfrag));
+
+        // The certificate cannot be replayed
+        assert_eq!(
+            super::revoke_policy(
+                &mut registry,
+                &certificate,
+                &publisher_vk,
+                &policy.other_ursula_address,
+                &policy.other_ursula_sk,
+                1100,
+            ),
+            Err(RevocationError::Stale {
+                issued_at: 1000,
+                revoked_at: 1000
+            })
+        );
+    }
+
+    #[test]
+    fn snapshot() {
+        let policy = Policy::new();
+        let mut registry = InMemoryRevocationRegistry::new();
+        let order = policy.order(
+            &policy.publisher_signer,
+            &policy.ursula_address,
+            &policy.ekfrag,
+            &policy.hrac,
+        );
+        policy.revoke(&mut registry, &order).unwrap();
+
+        let snapshot = registry.snapshot();
+        let restored = RevocationRegistrySnapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+        assert_eq!(restored, snapshot);
+        let restored = InMemoryRevocationRegistry::from(restored);
+        assert_eq!(restored, registry);
+        assert_eq!(
+            restored.revoked_at(&policy.hrac, &KeyFragDigest::new(&policy.ekfrag)),
+            Some(1000)
+        );
+    }
+
+    #[test]
+    fn service_rejects_revoked() {
+        let policy = Policy::new();
+        let (capsule, _ciphertext) =
+            encrypt(&policy.delegating_sk.public_key(), b"message").unwrap();
+        let request = ReencryptionRequest::new(
+            &[capsule],
+            &policy.hrac,
+            &policy.ekfrag,
+            &policy.publisher_signer.verifying_key(),
+            &policy.bob_sk.public_key(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let mut service = ReencryptionService::new(
+            Signer::new(SecretKey::random()),
+            policy.ursula_sk.clone(),
+            InMemoryRevocationRegistry::new(),
+            |_conditions: &Conditions, _context: Option<&Context>| Ok::<_, String>(true),
+        );
+        assert!(service.handle(&request, 1000).is_ok());
+
+        let order = policy.order(
+            &policy.publisher_signer,
+            &policy.ursula_address,
+            &policy.ekfrag,
+            &policy.hrac,
+        );
+        policy
+            .revoke(service.revocation_checker_mut(), &order)
+            .unwrap();
+        assert_eq!(
+            service.handle(&request, 1000).unwrap_err(),
+            ReencryptionRejection::Revoked
+        );
+    }
+}
@@ -0,0 +1,558 @@
+//! Sign-In-With-Ethereum (EIP-4361) authentication of context parameters.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+use sha3::Digest;
+use umbral_pre::{PublicKey, RecoverableSignature};
+
+use crate::address::Address;
+use crate::node_metadata::encode_defunct;
+
+/// The authentication scheme identifier for EIP-4361 messages.
+pub const EIP4361_SCHEME: &str = "EIP4361";
+
+const PREAMBLE_SUFFIX: &str = " wants you to sign in with your Ethereum account:";
+
+/// An error returned when parsing or verifying a SIWE authentication.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SiweError {
+    /// The message does not follow the EIP-4361 format.
+    MalformedMessage(String),
+    /// The authentication scheme is not supported.
+    UnsupportedScheme(String),
+    /// The signature could not be parsed, or the signer could not be recovered.
+    InvalidSignature(String),
+    /// The address recovered from the signature differs from the claimed one.
+    SignerMismatch,
+    /// The address in the message differs from the claimed one.
+    AddressMismatch,
+    /// The domain in the message differs from the expected one.
+    DomainMismatch {
+        /// The expected domain.
+        expected: String,
+        /// The domain in the message.
+        received: String,
+    },
+    /// The nonce in the message differs from the expected one.
+    NonceMismatch,
+    /// The message was issued after the current time.
+    IssuedInFuture,
+    /// The message is not valid yet (its `Not Before` time is after the current time).
+    NotYetValid,
+    /// The message has expired.
+    Expired,
+}
+
+impl fmt::Display for SiweError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MalformedMessage(err) => write!(f, "malformed SIWE message: {}", err),
+            Self::UnsupportedScheme(scheme) => {
+                write!(f, "unsupported authentication scheme: {}", scheme)
+            }
+            Self::InvalidSignature(err) => write!(f, "invalid signature: {}", err),
+            Self::SignerMismatch => write!(f, "signer does not match the claimed address"),
+            Self::AddressMismatch => {
+                write!(
+                    f,
+                    "address in the message does not match the claimed address"
+                )
+            }
+            Self::DomainMismatch { expected, received } => write!(
+                f,
+                "domain mismatch: expected {}, got {}",
+                expected, received
+            ),
+            Self::NonceMismatch => write!(f, "nonce mismatch"),
+            Self::IssuedInFuture => write!(f, "message is issued in the future"),
+            Self::NotYetValid => write!(f, "message is not valid yet"),
+            Self::Expired => write!(f, "message has expired"),
+        }
+    }
+}
+
+fn malformed(reason: &str) -> SiweError {
+    SiweError::MalformedMessage(reason.into())
+}
+
+/// Parses a `0x`-prefixed hex-encoded Ethereum address.
+pub(crate) fn address_from_hex(address: &str) -> Option<Address> {
+    let mut bytes = [0u8; Address::SIZE];
+    hex::decode_to_slice(address.strip_prefix("0x")?, &mut bytes).ok()?;
+    Some(Address::new(&bytes))
+}
+
+/// Encodes an Ethereum address as a `0x`-prefixed hex string.
+pub(crate) fn address_to_hex(address: &Address) -> String {
+    format!("0x{}", hex::encode(address))
+}
+
+fn parse_number(digits: &str) -> Option<i64> {
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+// Parses a fixed-width numeric field of an RFC 3339 timestamp.
+fn parse_digits(digits: &str, width: usize) -> Option<i64> {
+    if digits.len() != width {
+        return None;
+    }
+    parse_number(digits)
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Returns the number of days since 1970-01-01 for a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Parses an RFC 3339 timestamp (e.g. `2023-03-01T12:00:00.000Z`) into a UNIX timestamp.
+fn parse_rfc3339(timestamp: &str) -> Option<u64> {
+    let (date, time) = timestamp.split_once(['T', 't'])?;
+
+    let mut date_parts = date.splitn(3, '-');
+    let year = parse_digits(date_parts.next()?, 4)?;
+    let month = parse_digits(date_parts.next()?, 2)?;
+    let day = parse_digits(date_parts.next()?, 2)?;
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return None;
+    }
+
+    let (time, offset_seconds) = if let Some(time) = time.strip_suffix(['Z', 'z']) {
+        (time, 0)
+    } else {
+        let sign_pos = time.rfind(['+', '-'])?;
+        let (time, offset) = time.split_at(sign_pos);
+        let sign = if offset.starts_with('+') { 1 } else { -1 };
+        let (hours, minutes) = offset[1..].split_once(':')?;
+        (
+            time,
+            sign * (parse_digits(hours, 2)? * 3600 + parse_digits(minutes, 2)? * 60),
+        )
+    };
+
+    // Fractional seconds do not affect a timestamp with a 1 second resolution.
+    let time = time.split('.').next()?;
+    let mut time_parts = time.splitn(3, ':');
+    let hours = parse_digits(time_parts.next()?, 2)?;
+    let minutes = parse_digits(time_parts.next()?, 2)?;
+    let seconds = parse_digits(time_parts.next()?, 2)?;
+    if hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+
+    let timestamp =
+        days_from_civil(year, month, day) * 86400 + hours * 3600 + minutes * 60 + seconds
+            - offset_seconds;
+    u64::try_from(timestamp).ok()
+}
+
+/// A parsed EIP-4361 (Sign-In-With-Ethereum) message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiweMessage {
+    /// The domain requesting the signing.
+    pub domain: String,
+    /// The address performing the signing.
+    pub address: Address,
+    /// The human-readable assertion the user signs.
+    pub statement: Option<String>,
+    /// The subject of the signing.
+    pub uri: String,
+    /// The message version (must be `1`).
+    pub version: String,
+    /// The chain ID the session is bound to.
+    pub chain_id: u64,
+    /// A randomized token used to prevent replay attacks.
+    pub nonce: String,
+    /// The issuance time (UNIX timestamp).
+    pub issued_at: u64,
+    /// The expiration time (UNIX timestamp).
+    pub expiration_time: Option<u64>,
+    /// The time when the message becomes valid (UNIX timestamp).
+    pub not_before: Option<u64>,
+    /// A system-specific identifier.
+    pub request_id: Option<String>,
+    /// Resources the user wishes to have resolved as part of the authentication.
+    pub resources: Vec<String>,
+}
+
+impl SiweMessage {
+    /// Parses an EIP-4361 message.
+    pub fn parse(message: &str) -> Result<Self, SiweError> {
+        let mut lines = message.split('\n').peekable();
+
+        let preamble = lines.next().ok_or_else(|| malformed("empty message"))?;
+        let domain = preamble
+            .strip_suffix(PREAMBLE_SUFFIX)
+            .ok_or_else(|| malformed("invalid preamble"))?;
+        // The scheme is optional and is not a part of the domain.
+        let domain = domain
+            .split_once("://")
+            .map_or(domain, |(_, domain)| domain);
+
+        let address_line = lines.next().ok_or_else(|| malformed("missing address"))?;
+        let address = address_from_hex(address_line).ok_or_else(|| malformed("invalid address"))?;
+
+        // An empty line, an optional statement followed by an empty line, then the fields.
+        if lines.next() != Some("") {
+            return Err(malformed("expected an empty line after the address"));
+        }
+        let statement = match lines.next() {
+            Some("") => None,
+            Some(statement) => {
+                if lines.next() != Some("") {
+                    return Err(malformed("expected an empty line after the statement"));
+                }
+                Some(statement.to_string())
+            }
+            None => return Err(malformed("unexpected end of message")),
+        };
+
+        let mut field = |name: &str, required: bool| -> Result<Option<String>, SiweError> {
+            let prefix = format!("{}: ", name);
+            match lines
+                .peek()
+                .and_then(|line| line.strip_prefix(prefix.as_str()))
+            {
+                Some(value) => {
+                    let value = value.to_string();
+                    lines.next();
+                    Ok(Some(value))
+                }
+                None if required => Err(SiweError::MalformedMessage(format!(
+                    "missing field `{}`",
+                    name
+                ))),
+                None => Ok(None),
+            }
+        };
+
+        let uri = field("URI", true)?.unwrap_or_default();
+        let version = field("Version", true)?.unwrap_or_default();
+        let chain_id = field("Chain ID", true)?.unwrap_or_default();
+        let nonce = field("Nonce", true)?.unwrap_or_default();
+        let issued_at = field("Issued At", true)?.unwrap_or_default();
+        let expiration_time = field("Expiration Time", false)?;
+        let not_before = field("Not Before", false)?;
+        let request_id = field("Request ID", false)?;
+
+        let mut resources = Vec::new();
+        if lines.peek() == Some(&"Resources:") {
+            lines.next();
+            while let Some(resource) = lines.peek().and_then(|line| line.strip_prefix("- ")) {
+                resources.push(resource.to_string());
+                lines.next();
+            }
+        }
+
+        if lines.any(|line| !line.is_empty()) {
+            return Err(malformed("unexpected trailing lines"));
+        }
+
+        if version != "1" {
+            return Err(malformed("unsupported version"));
+        }
+        if nonce.len() < 8 || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(malformed(
+                "nonce must be at least 8 alphanumeric characters",
+            ));
+        }
+        let parse_time = |time: &str| parse_rfc3339(time).ok_or_else(|| malformed("invalid time"));
+
+        Ok(Self {
+            domain: domain.into(),
+            address,
+            statement,
+            uri,
+            version,
+            chain_id: parse_number(&chain_id)
+                .and_then(|id| u64::try_from(id).ok())
+                .ok_or_else(|| malformed("invalid chain ID"))?,
+            nonce,
+            issued_at: parse_time(&issued_at)?,
+            expiration_time: expiration_time.as_deref().map(parse_time).transpose()?,
+            not_before: not_before.as_deref().map(parse_time).transpose()?,
+            request_id,
+            resources,
+        })
+    }
+}
+
+/// The checks applied to a SIWE message in addition to the signature verification.
+#[derive(Debug, Clone, Copy)]
+pub struct SiweVerificationParameters<'a> {
+    /// The current time (UNIX timestamp).
+    pub now: u64,
+    /// The domain the message must be issued for.
+    pub domain: &'a str,
+    /// The nonce the message must contain, if the verifier issued one.
+    pub nonce: Option<&'a str>,
+}
+
+/// A context entry authenticating an Ethereum address with a signed EIP-4361 message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiweAuthentication {
+    /// The claimed address (`0x`-prefixed hex).
+    pub address: String,
+    /// The authentication scheme (must be `EIP4361`).
+    pub scheme: String,
+    /// The signed EIP-4361 message.
+    pub typed_data: String,
+    /// The `0x`-prefixed hex-encoded signature (`r || s || v`, 65 bytes).
+    pub signature: String,
+}
+
+impl SiweAuthentication {
+    /// Creates a new authentication entry from a SIWE message and its signature.
+    pub fn new(message: &str, signature: &RecoverableSignature) -> Result<Self, SiweError> {
+        let parsed = SiweMessage::parse(message)?;
+        Ok(Self {
+            address: address_to_hex(&parsed.address),
+            scheme: EIP4361_SCHEME.into(),
+            typed_data: message.into(),
+            signature: format!("0x{}", hex::encode(signature.to_be_bytes())),
+        })
+    }
+
+    fn recover_signer(&self) -> Result<Address, SiweError> {
+        let mut signature_bytes = self
+            .signature
+            .strip_prefix("0x")
+            .and_then(|signature| hex::decode(signature).ok())
+            .ok_or_else(|| {
+                SiweError::InvalidSignature("expected a 0x-prefixed hex string".into())
+            })?;
+
+        // Wallets produce `v` in the Ethereum format (27 or 28).
+        if let Some(v) = signature_bytes.last_mut() {
+            if *v >= 27 {
+                *v -= 27;
+            }
+        }
+
+        let signature = RecoverableSignature::try_from_be_bytes(&signature_bytes)
+            .map_err(SiweError::InvalidSignature)?;
+        let digest = encode_defunct(self.typed_data.as_bytes()).finalize();
+        let key = PublicKey::recover_from_prehash(&digest, &signature)
+            .map_err(SiweError::InvalidSignature)?;
+        Ok(Address::from_public_key(&key))
+    }
+
+    /// Verifies the message and the signature, returning the authenticated address on success.
+    pub fn verify(&self, params: &SiweVerificationParameters<'_>) -> Result<Address, SiweError> {
+        if self.scheme != EIP4361_SCHEME {
+            return Err(SiweError::UnsupportedScheme(self.scheme.clone()));
+        }
+
+        let message = SiweMessage::parse(&self.typed_data)?;
+        let claimed_address = address_from_hex(&self.address).ok_or(SiweError::AddressMismatch)?;
+        if message.address != claimed_address {
+            return Err(SiweError::AddressMismatch);
+        }
+
+        if message.domain != params.domain {
+            return Err(SiweError::DomainMismatch {
+                expected: params.domain.into(),
+                received: message.domain,
+            });
+        }
+        if let Some(nonce) = params.nonce {
+            if message.nonce != nonce {
+                return Err(SiweError::NonceMismatch);
+            }
+        }
+        if message.issued_at > params.now {
+            return Err(SiweError::IssuedInFuture);
+        }
+        if matches!(message.not_before, Some(not_before) if not_before > params.now) {
+            return Err(SiweError::NotYetValid);
+        }
+        if matches!(message.expiration_time, Some(expiration) if expiration <= params.now) {
+            return Err(SiweError::Expired);
+        }
+
+        if self.recover_signer()? != claimed_address {
+            return Err(SiweError::SignerMismatch);
+        }
+
+        Ok(claimed_address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::{String, ToString};
+
+    use k256::ecdsa::SigningKey;
+    use serde_json::json;
+    use sha3::Digest;
+    use umbral_pre::{PublicKey, RecoverableSignature};
+
+    use super::{
+        address_to_hex, parse_rfc3339, SiweAuthentication, SiweError, SiweMessage,
+        SiweVerificationParameters,
+    };
+    use crate::address::Address;
+    use crate::node_metadata::encode_defunct;
+    use crate::{Context, ContextError, USER_ADDRESS_CONTEXT_VARIABLE};
+
+    fn make_message(address: &Address) -> String {
+        format!(
+            "localhost:3000 wants you to sign in with your Ethereum account:\n\
+            {}\n\
+            \n\
+            Sign in to decrypt the data.\n\
+            \n\
+            URI: http://localhost:3000\n\
+            Version: 1\n\
+            Chain ID: 1\n\
+            Nonce: 32891756ab\n\
+            Issued At: 2023-03-01T12:00:00.000Z\n\
+            Expiration Time: 2023-03-02T12:00:00Z\n\
+            Resources:\n\
+            - ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/",
+            address_to_hex(address)
+        )
+    }
+
+    fn make_signing_key(key_bytes: &[u8; 32]) -> (SigningKey, Address) {
+        let signing_key = SigningKey::from_bytes(key_bytes).unwrap();
+        let public_key = PublicKey::try_from_compressed_bytes(
+            signing_key
+                .verifying_key()
+                .to_encoded_point(true)
+                .as_bytes(),
+        )
+        .unwrap();
+        (signing_key, Address::from_public_key(&public_key))
+    }
+
+    fn sign(signing_key: &SigningKey, message: &str) -> RecoverableSignature {
+        let digest = encode_defunct(message.as_bytes()).finalize();
+        let (signature, recovery_id) = signing_key.sign_prehash_recoverable(&digest).unwrap();
+        let bytes = [signature.to_bytes().as_slice(), &[recovery_id.to_byte()]].concat();
+        RecoverableSignature::try_from_be_bytes(&bytes).unwrap()
+    }
+
+    #[test]
+    fn rfc3339() {
+        assert_eq!(parse_rfc3339("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_rfc3339("2023-03-01T12:00:00.000Z"), Some(1677672000));
+        assert_eq!(parse_rfc3339("2023-03-01T14:00:00+02:00"), Some(1677672000));
+        assert_eq!(parse_rfc3339("2023-13-01T14:00:00Z"), None);
+    }
+
+    #[test]
+    fn rfc3339_rejects_nonexistent_days() {
+        assert_eq!(parse_rfc3339("2024-02-29T00:00:00Z"), Some(1709164800));
+        assert_eq!(parse_rfc3339("2023-02-29T00:00:00Z"), None);
+        assert_eq!(parse_rfc3339("2023-02-31T00:00:00Z"), None);
+        assert_eq!(parse_rfc3339("2023-04-31T00:00:00Z"), None);
+        assert_eq!(parse_rfc3339("2100-02-29T00:00:00Z"), None);
+    }
+
+    #[test]
+    fn rfc3339_rejects_out_of_range_fields() {
+        // Would overflow the seconds computation if the year was unbounded.
+        assert_eq!(parse_rfc3339("9223372036854775807-01-01T00:00:00Z"), None);
+        assert_eq!(parse_rfc3339("99999-01-01T00:00:00Z"), None);
+        assert_eq!(parse_rfc3339("2023-3-01T12:00:00Z"), None);
+        assert_eq!(parse_rfc3339("2023-03-01T12:00:00+9999999999999:00"), None);
+        assert_eq!(parse_rfc3339("9999-12-31T23:59:60Z"), Some(253402300800));
+    }
+
+    #[test]
+    fn verify_authentication() {
+        let (signing_key, address) = make_signing_key(b"01234567890123456789012345678901");
+        let message = make_message(&address);
+
+        let parsed = SiweMessage::parse(&message).unwrap();
+        assert_eq!(parsed.address, address);
+        assert_eq!(
+            parsed.statement.as_deref(),
+            Some("Sign in to decrypt the data.")
+        );
+        assert_eq!(parsed.resources.len(), 1);
+
+        let auth = SiweAuthentication::new(&message, &sign(&signing_key, &message)).unwrap();
+        let params = SiweVerificationParameters {
+            now: 1677672000 + 3600,
+            domain: "localhost:3000",
+            nonce: Some("32891756ab"),
+        };
+        assert_eq!(auth.verify(&params), Ok(address));
+
+        let expired = SiweVerificationParameters {
+            now: 1677672000 + 86400,
+            ..params
+        };
+        assert_eq!(auth.verify(&expired), Err(SiweError::Expired));
+
+        let other_domain = SiweVerificationParameters {
+            domain: "example.com",
+            ..params
+        };
+        assert!(matches!(
+            auth.verify(&other_domain),
+            Err(SiweError::DomainMismatch { .. })
+        ));
+
+        // Signed by a different key
+        let forged = SiweAuthentication::new(
+            &message,
+            &sign(
+                &make_signing_key(b"10987654321098765432109876543210").0,
+                &message,
+            ),
+        )
+        .unwrap();
+        assert_eq!(forged.verify(&params), Err(SiweError::SignerMismatch));
+
+        // The authentication entry is replaced by the verified address in the context
+        let context = Context::new(
+            &json!({ USER_ADDRESS_CONTEXT_VARIABLE: auth, ":tokenId": 1 }).to_string(),
+        );
+        let authenticated = context.parse().unwrap().authenticate(&params).unwrap();
+        assert_eq!(
+            authenticated.get(USER_ADDRESS_CONTEXT_VARIABLE),
+            Some(&json!(address_to_hex(&address)))
+        );
+        assert_eq!(
+            context.parse().unwrap().authenticate(&expired),
+            Err(ContextError::Authentication(SiweError::Expired))
+        );
+
+        let unauthenticated = Context::new(
+            &json!({ USER_ADDRESS_CONTEXT_VARIABLE: address_to_hex(&address) }).to_string(),
+        );
+        assert_eq!(
+            unauthenticated.parse().unwrap().authenticate(&params),
+            Err(ContextError::Unauthenticated)
+        );
+    }
+}
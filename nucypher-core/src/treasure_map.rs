@@ -2,21 +2,24 @@ use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
 use alloc::format;
 use alloc::string::String;
+use alloc::vec;
 use alloc::vec::Vec;
 
 use serde::{Deserialize, Serialize};
 use umbral_pre::{
     decrypt_original, encrypt, serde_bytes, Capsule, EncryptionError, PublicKey, SecretKey,
-    Signature, Signer, VerifiedKeyFrag,
+    SecretKeyFactory, Signature, Signer, VerifiedKeyFrag,
 };
+use zeroize::Zeroizing;
 
 use crate::address::Address;
 use crate::hrac::HRAC;
-use crate::key_frag::{DecryptionError, EncryptedKeyFrag};
+use crate::key_frag::{keys_signed, DecryptionError, EncryptedKeyFrag, PolicyValidity};
+use crate::treasure_map_builder::{TreasureMapError, TreasureMapProblem};
 use crate::versioning::{
     messagepack_deserialize, messagepack_serialize, ProtocolObject, ProtocolObjectInner,
 };
-use crate::RevocationOrder;
+use crate::{RevocationCertificate, RevocationOrder};
 
 /// A structure containing `KeyFrag` objects encrypted for Ursulas chosen for this policy.
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
@@ -31,20 +34,39 @@ pub struct TreasureMap {
     pub policy_encrypting_key: PublicKey,
     /// Publisher's verifying key.
     pub publisher_verifying_key: PublicKey,
+    /// The number of amendments made to the map since its creation.
+    pub revision: u32,
+}
+
+/// The result of [`TreasureMap::amend`].
+#[derive(PartialEq, Debug, Clone)]
+pub struct TreasureMapAmendment {
+    /// The amended treasure map.
+    pub treasure_map: TreasureMap,
+    /// Revocation orders for the removed destinations.
+    pub revocation_orders: Vec<RevocationOrder>,
 }
 
 impl TreasureMap {
-    /// Create a new treasure map for a collection of ursulas and kfrags.
+    /// Create a new treasure map for a collection of ursulas and kfrags,
+    /// authorized for use during the `validity` period.
+    ///
+    /// The kfrags must be generated for `policy_encrypting_key` as the delegating key
+    /// and `receiving_key` (Bob's encrypting key), with both keys signed into them,
+    /// since Ursulas will verify them.
     ///
     /// Panics if `threshold` is set to 0,
     /// the number of assigned keyfrags is less than `threshold`,
-    /// or if the addresses in `assigned_kfrags` repeat.
+    /// if the addresses in `assigned_kfrags` repeat,
+    /// or if the keys are not signed into the kfrags.
     pub fn new(
         signer: &Signer,
         hrac: &HRAC,
         policy_encrypting_key: &PublicKey,
+        receiving_key: &PublicKey,
         assigned_kfrags: impl IntoIterator<Item = (Address, (PublicKey, VerifiedKeyFrag))>,
         threshold: u8,
+        validity: &PolicyValidity,
     ) -> Self {
         // Panic here since violation of theis condition indicates a bug on the caller's side.
         assert!(threshold != 0, "threshold must be non-zero");
@@ -53,8 +75,17 @@ impl TreasureMap {
         let mut destinations = BTreeMap::new();
         for (ursula_address, (ursula_encrypting_key, verified_kfrag)) in assigned_kfrags.into_iter()
         {
-            let encrypted_kfrag =
-                EncryptedKeyFrag::new(signer, &ursula_encrypting_key, hrac, verified_kfrag);
+            let encrypted_kfrag = EncryptedKeyFrag::new(
+                signer,
+                &ursula_encrypting_key,
+                hrac,
+                verified_kfrag,
+                policy_encrypting_key,
+                receiving_key,
+                validity,
+            )
+            // Panic here since violation of this condition indicates a bug on the caller's side.
+            .unwrap_or_else(|err| panic!("{} for {:?}", err, ursula_address));
             if destinations
                 .insert(ursula_address, encrypted_kfrag)
                 .is_some()
@@ -80,21 +111,188 @@ impl TreasureMap {
             destinations,
             policy_encrypting_key: *policy_encrypting_key,
             publisher_verifying_key: signer.verifying_key(),
+            revision: 0,
+        }
+    }
+
+    /// Replaces some of the destinations with new Ursulas,
+    /// returning a map with the next revision number
+    /// along with the revocation orders for the removed destinations.
+    ///
+    /// `replacements` maps the addresses of the removed Ursulas
+    /// to the addresses and encrypting keys of the new ones and the key frags assigned to them.
+    /// Since key frags from different `generate_kfrags()` calls cannot be combined,
+    /// these must be unused key frags from the same generation as the ones already in the map
+    /// (or the key frags of the removed Ursulas themselves).
+    /// They are verified against the publisher's key, the policy encrypting key
+    /// and `receiving_key`.
+    ///
+    /// `signer` must be the publisher of the map.
+    /// The revocation orders are marked as issued at `issued_at` (UNIX timestamp).
+    pub fn amend(
+        &self,
+        signer: &Signer,
+        receiving_key: &PublicKey,
+        replacements: impl IntoIterator<Item = (Address, (Address, PublicKey, VerifiedKeyFrag))>,
+        validity: &PolicyValidity,
+        issued_at: u64,
+    ) -> Result<TreasureMapAmendment, TreasureMapError> {
+        let replacements = replacements.into_iter().collect::<Vec<_>>();
+        let mut problems = Vec::new();
+
+        if signer.verifying_key() != self.publisher_verifying_key {
+            problems.push(TreasureMapProblem::PublisherMismatch);
+        }
+
+        for (i, (removed, (address, encrypting_key, kfrag))) in replacements.iter().enumerate() {
+            let previous = &replacements[..i];
+
+            if !self.destinations.contains_key(removed) {
+                problems.push(TreasureMapProblem::UnknownDestination(*removed));
+            } else if previous.iter().any(|(other, _)| other == removed) {
+                problems.push(TreasureMapProblem::DuplicateAddress(*removed));
+            }
+
+            if address.as_ref().iter().all(|byte| *byte == 0) {
+                problems.push(TreasureMapProblem::ZeroAddress);
+            }
+            let remains = self.destinations.contains_key(address)
+                && !replacements.iter().any(|(other, _)| other == address);
+            if remains || previous.iter().any(|(_, (other, _, _))| other == address) {
+                problems.push(TreasureMapProblem::DuplicateAddress(*address));
+            }
+
+            if encrypting_key == &self.policy_encrypting_key {
+                problems.push(TreasureMapProblem::PolicyKeyAsEncryptingKey(*address));
+            } else if encrypting_key == receiving_key {
+                problems.push(TreasureMapProblem::ReceivingKeyAsEncryptingKey(*address));
+            } else if previous
+                .iter()
+                .any(|(_, (_, other, _))| other == encrypting_key)
+            {
+                problems.push(TreasureMapProblem::DuplicateEncryptingKey(*address));
+            }
+
+            if previous.iter().any(|(_, (_, _, other))| other == kfrag) {
+                problems.push(TreasureMapProblem::DuplicateKeyFrag(*address));
+            }
+            let kfrag = kfrag.clone().unverify();
+            if !keys_signed(&kfrag, &self.publisher_verifying_key) {
+                problems.push(TreasureMapProblem::UnsignedKeyFrag(*address));
+            } else if kfrag
+                .verify(
+                    &self.publisher_verifying_key,
+                    Some(&self.policy_encrypting_key),
+                    Some(receiving_key),
+                )
+                .is_err()
+            {
+                problems.push(TreasureMapProblem::KeyFragMismatch(*address));
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(TreasureMapError { problems });
+        }
+
+        let mut destinations = self.destinations.clone();
+        let mut revocation_orders = Vec::new();
+        for (removed, _) in replacements.iter() {
+            if let Some(ekfrag) = destinations.remove(removed) {
+                revocation_orders.push(RevocationOrder::new(
+                    signer, removed, &ekfrag, &self.hrac, issued_at, None,
+                ));
+            }
+        }
+        for (_, (address, encrypting_key, kfrag)) in replacements.into_iter() {
+            let encrypted_kfrag = EncryptedKeyFrag::new(
+                signer,
+                &encrypting_key,
+                &self.hrac,
+                kfrag,
+                &self.policy_encrypting_key,
+                receiving_key,
+                validity,
+            )
+            .map_err(|_| TreasureMapError {
+                problems: vec![TreasureMapProblem::UnsignedKeyFrag(address)],
+            })?;
+            destinations.insert(address, encrypted_kfrag);
         }
+
+        Ok(TreasureMapAmendment {
+            treasure_map: Self {
+                destinations,
+                revision: self.revision + 1,
+                ..self.clone()
+            },
+            revocation_orders,
+        })
+    }
+
+    fn check_publisher(&self, signer: &Signer) -> Result<(), TreasureMapError> {
+        if signer.verifying_key() != self.publisher_verifying_key {
+            return Err(TreasureMapError {
+                problems: vec![TreasureMapProblem::PublisherMismatch],
+            });
+        }
+        Ok(())
     }
 
     /// Encrypts the treasure map for Bob.
-    pub fn encrypt(&self, signer: &Signer, recipient_key: &PublicKey) -> EncryptedTreasureMap {
-        EncryptedTreasureMap::new(signer, recipient_key, self)
+    ///
+    /// Fails if `signer` is not the publisher of the treasure map.
+    pub fn encrypt(
+        &self,
+        signer: &Signer,
+        recipient_key: &PublicKey,
+    ) -> Result<EncryptedTreasureMap, TreasureMapError> {
+        self.check_publisher(signer)?;
+        Ok(EncryptedTreasureMap::new(signer, recipient_key, self))
     }
 
-    /// Makes revocation orders for all destinations in the treasure map.
-    pub fn make_revocation_orders(&self, signer: &Signer) -> Vec<RevocationOrder> {
+    /// Encrypts the treasure map once for a group of Bobs.
+    ///
+    /// Fails if `signer` is not the publisher of the treasure map,
+    /// or if `recipient_keys` is empty.
+    pub fn encrypt_for_many(
+        &self,
+        signer: &Signer,
+        recipient_keys: &[PublicKey],
+    ) -> Result<MultiRecipientEncryptedTreasureMap, TreasureMapError> {
+        self.check_publisher(signer)?;
+        if recipient_keys.is_empty() {
+            return Err(TreasureMapError {
+                problems: vec![TreasureMapProblem::NoRecipients],
+            });
+        }
+        Ok(MultiRecipientEncryptedTreasureMap::new(
+            signer,
+            recipient_keys,
+            self,
+        ))
+    }
+
+    /// Makes revocation orders for all destinations in the treasure map,
+    /// issued at the given time (UNIX timestamp).
+    pub fn make_revocation_orders(&self, signer: &Signer, issued_at: u64) -> Vec<RevocationOrder> {
         self.destinations
             .iter()
-            .map(|(address, ekfrag)| RevocationOrder::new(signer, address, ekfrag))
+            .map(|(address, ekfrag)| {
+                RevocationOrder::new(signer, address, ekfrag, &self.hrac, issued_at, None)
+            })
             .collect()
     }
+
+    /// Makes a single revocation certificate covering all destinations in the treasure map,
+    /// issued at the given time (UNIX timestamp).
+    pub fn make_revocation_certificate(
+        &self,
+        signer: &Signer,
+        issued_at: u64,
+    ) -> RevocationCertificate {
+        RevocationCertificate::new(signer, &self.hrac, &self.destinations, issued_at)
+    }
 }
 
 impl<'a> ProtocolObjectInner<'a> for TreasureMap {
@@ -103,7 +301,7 @@ impl<'a> ProtocolObjectInner<'a> for TreasureMap {
     }
 
     fn version() -> (u16, u16) {
-        (3, 0)
+        (4, 0)
     }
 
     fn unversioned_to_bytes(&self) -> Box<[u8]> {
@@ -117,6 +315,20 @@ impl<'a> ProtocolObjectInner<'a> for TreasureMap {
             None
         }
     }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.threshold == 0 {
+            return Err("threshold must be non-zero".into());
+        }
+        if self.destinations.len() < self.threshold as usize {
+            return Err(format!(
+                "threshold ({}) cannot be larger than the number of destinations ({})",
+                self.threshold,
+                self.destinations.len()
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl<'a> ProtocolObject<'a> for TreasureMap {}
@@ -148,12 +360,15 @@ impl AuthorizedTreasureMap {
         self,
         recipient_key: &PublicKey,
         publisher_verifying_key: &PublicKey,
-    ) -> Option<TreasureMap> {
+    ) -> Result<TreasureMap, DecryptionError> {
+        if &self.treasure_map.publisher_verifying_key != publisher_verifying_key {
+            return Err(DecryptionError::PublisherMismatch);
+        }
         let message = Self::message_to_sign(recipient_key, &self.treasure_map);
         if !self.signature.verify(publisher_verifying_key, &message) {
-            return None;
+            return Err(DecryptionError::VerificationFailed);
         }
-        Some(self.treasure_map)
+        Ok(self.treasure_map)
     }
 }
 
@@ -163,7 +378,7 @@ impl<'a> ProtocolObjectInner<'a> for AuthorizedTreasureMap {
     }
 
     fn version() -> (u16, u16) {
-        (3, 0)
+        (4, 0)
     }
 
     fn unversioned_to_bytes(&self) -> Box<[u8]> {
@@ -177,6 +392,10 @@ impl<'a> ProtocolObjectInner<'a> for AuthorizedTreasureMap {
             None
         }
     }
+
+    fn validate(&self) -> Result<(), String> {
+        self.treasure_map.validate()
+    }
 }
 
 impl<'a> ProtocolObject<'a> for AuthorizedTreasureMap {}
@@ -195,18 +414,8 @@ impl EncryptedTreasureMap {
         // Most probably it is an overkill, unless it can be used somehow
         // for Ursula-to-Ursula "baton passing".
 
-        // TODO: `publisher` here can be different from the one in TreasureMap, it seems.
-        // Do we ever cross-check them? Do we want to enforce them to be the same?
-
         let authorized_tmap = AuthorizedTreasureMap::new(signer, recipient_key, treasure_map);
-        let (capsule, ciphertext) = match encrypt(recipient_key, &authorized_tmap.to_bytes()) {
-            Ok(result) => result,
-            Err(err) => match err {
-                // For now this is the only error that can happen during encryption,
-                // and there's really no point in propagating it.
-                EncryptionError::PlaintextTooLarge => panic!("encryption failed - out of memory?"),
-            },
-        };
+        let (capsule, ciphertext) = encrypt_or_panic(recipient_key, &authorized_tmap.to_bytes());
         Self {
             capsule,
             ciphertext,
@@ -214,6 +423,10 @@ impl EncryptedTreasureMap {
     }
 
     /// Decrypts and verifies the treasure map.
+    ///
+    /// Fails with [`DecryptionError::PublisherMismatch`] if `publisher_verifying_key`
+    /// is not the publisher's key embedded in the treasure map,
+    /// and with [`DecryptionError::VerificationFailed`] if the map was not signed with it.
     pub fn decrypt(
         &self,
         sk: &SecretKey,
@@ -223,9 +436,7 @@ impl EncryptedTreasureMap {
             .map_err(DecryptionError::DecryptionFailed)?;
         let auth_tmap = AuthorizedTreasureMap::from_bytes(&auth_tmap_bytes)
             .map_err(DecryptionError::DeserializationFailed)?;
-        auth_tmap
-            .verify(&sk.public_key(), publisher_verifying_key)
-            .ok_or(DecryptionError::VerificationFailed)
+        auth_tmap.verify(&sk.public_key(), publisher_verifying_key)
     }
 }
 
@@ -252,3 +463,435 @@ impl<'a> ProtocolObjectInner<'a> for EncryptedTreasureMap {
 }
 
 impl<'a> ProtocolObject<'a> for EncryptedTreasureMap {}
+
+fn encrypt_or_panic(recipient_key: &PublicKey, plaintext: &[u8]) -> (Capsule, Box<[u8]>) {
+    match encrypt(recipient_key, plaintext) {
+        Ok(result) => result,
+        Err(err) => match err {
+            // For now this is the only error that can happen during encryption,
+            // and there's really no point in propagating it.
+            EncryptionError::PlaintextTooLarge => panic!("encryption failed - out of memory?"),
+        },
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+struct MultiAuthorizedTreasureMap {
+    signature: Signature,
+    recipient_keys: Vec<PublicKey>,
+    treasure_map: TreasureMap,
+}
+
+impl MultiAuthorizedTreasureMap {
+    fn message_to_sign(recipient_keys: &[PublicKey], treasure_map: &TreasureMap) -> Vec<u8> {
+        let mut message = (recipient_keys.len() as u32).to_be_bytes().to_vec();
+        for recipient_key in recipient_keys {
+            message.extend(recipient_key.to_compressed_bytes().iter());
+        }
+        message.extend(treasure_map.to_bytes().iter());
+        message
+    }
+
+    fn new(signer: &Signer, recipient_keys: &[PublicKey], treasure_map: &TreasureMap) -> Self {
+        let message = Self::message_to_sign(recipient_keys, treasure_map);
+        let signature = signer.sign(&message);
+
+        Self {
+            signature,
+            recipient_keys: recipient_keys.to_vec(),
+            treasure_map: treasure_map.clone(),
+        }
+    }
+
+    fn verify(
+        self,
+        recipient_key: &PublicKey,
+        publisher_verifying_key: &PublicKey,
+    ) -> Result<TreasureMap, DecryptionError> {
+        if !self.recipient_keys.contains(recipient_key) {
+            return Err(DecryptionError::NotARecipient);
+        }
+        if &self.treasure_map.publisher_verifying_key != publisher_verifying_key {
+            return Err(DecryptionError::PublisherMismatch);
+        }
+        let message = Self::message_to_sign(&self.recipient_keys, &self.treasure_map);
+        if !self.signature.verify(publisher_verifying_key, &message) {
+            return Err(DecryptionError::VerificationFailed);
+        }
+        Ok(self.treasure_map)
+    }
+}
+
+impl<'a> ProtocolObjectInner<'a> for MultiAuthorizedTreasureMap {
+    fn brand() -> [u8; 4] {
+        *b"MAMp"
+    }
+
+    fn version() -> (u16, u16) {
+        (1, 0)
+    }
+
+    fn unversioned_to_bytes(&self) -> Box<[u8]> {
+        messagepack_serialize(&self)
+    }
+
+    fn unversioned_from_bytes(minor_version: u16, bytes: &[u8]) -> Option<Result<Self, String>> {
+        if minor_version == 0 {
+            Some(messagepack_deserialize(bytes))
+        } else {
+            None
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.recipient_keys.is_empty() {
+            return Err("at least one recipient key is required".into());
+        }
+        self.treasure_map.validate()
+    }
+}
+
+impl<'a> ProtocolObject<'a> for MultiAuthorizedTreasureMap {}
+
+/// The seed of the secret key of the encrypted body of a [`MultiRecipientEncryptedTreasureMap`]
+/// encrypted for one of the recipients.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+struct KeyWrap {
+    capsule: Capsule,
+    #[serde(with = "serde_bytes::as_base64")]
+    ciphertext: Box<[u8]>,
+}
+
+/// A treasure map encrypted for a group of Bobs.
+///
+/// The treasure map is encrypted once with a one-time key,
+/// and that key is encrypted separately for each recipient.
+/// The recipient keys are not revealed to the holders of the encrypted map.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct MultiRecipientEncryptedTreasureMap {
+    capsule: Capsule,
+    #[serde(with = "serde_bytes::as_base64")]
+    ciphertext: Box<[u8]>,
+    key_wraps: Vec<KeyWrap>,
+}
+
+const BODY_KEY_LABEL: &[u8] = b"MultiRecipientEncryptedTreasureMap/body";
+
+// The body key is derived from a seed, since `SecretKey` can only be restored from bytes
+// already held in a `SecretBox`, and a seed can be used directly.
+fn derive_body_key(seed: &[u8]) -> Option<SecretKey> {
+    SecretKeyFactory::from_secure_randomness(seed)
+        .ok()
+        .map(|factory| factory.make_key(BODY_KEY_LABEL))
+}
+
+impl MultiRecipientEncryptedTreasureMap {
+    fn new(signer: &Signer, recipient_keys: &[PublicKey], treasure_map: &TreasureMap) -> Self {
+        // The secret is held in a `SecretBox` and zeroized on drop.
+        let secret = SecretKeyFactory::random().make_secret(BODY_KEY_LABEL);
+        let seed = &secret.as_secret()[..SecretKeyFactory::seed_size()];
+        let body_sk = derive_body_key(seed).expect("the seed has the correct size");
+
+        let authorized_tmap = MultiAuthorizedTreasureMap::new(signer, recipient_keys, treasure_map);
+        let (capsule, ciphertext) =
+            encrypt_or_panic(&body_sk.public_key(), &authorized_tmap.to_bytes());
+
+        let key_wraps = recipient_keys
+            .iter()
+            .map(|recipient_key| {
+                let (capsule, ciphertext) = encrypt_or_panic(recipient_key, seed);
+                KeyWrap {
+                    capsule,
+                    ciphertext,
+                }
+            })
+            .collect();
+
+        Self {
+            capsule,
+            ciphertext,
+            key_wraps,
+        }
+    }
+
+    /// Returns the number of recipients the treasure map is encrypted for.
+    pub fn recipients_count(&self) -> usize {
+        self.key_wraps.len()
+    }
+
+    /// Decrypts the treasure map with the secret key of one of the recipients,
+    /// and verifies the publisher's signature.
+    ///
+    /// The publisher's key is checked in the same way as in [`EncryptedTreasureMap::decrypt`].
+    pub fn decrypt(
+        &self,
+        sk: &SecretKey,
+        publisher_verifying_key: &PublicKey,
+    ) -> Result<TreasureMap, DecryptionError> {
+        let seed = self
+            .key_wraps
+            .iter()
+            .find_map(|wrap| decrypt_original(sk, &wrap.capsule, &wrap.ciphertext).ok())
+            .map(Zeroizing::new)
+            .ok_or(DecryptionError::NotARecipient)?;
+        let body_sk = derive_body_key(&seed).ok_or(DecryptionError::VerificationFailed)?;
+
+        let auth_tmap_bytes = decrypt_original(&body_sk, &self.capsule, &self.ciphertext)
+            .map_err(DecryptionError::DecryptionFailed)?;
+        let auth_tmap = MultiAuthorizedTreasureMap::from_bytes(&auth_tmap_bytes)
+            .map_err(DecryptionError::DeserializationFailed)?;
+        auth_tmap.verify(&sk.public_key(), publisher_verifying_key)
+    }
+}
+
+impl<'a> ProtocolObjectInner<'a> for MultiRecipientEncryptedTreasureMap {
+    fn brand() -> [u8; 4] {
+        *b"MEMp"
+    }
+
+    fn version() -> (u16, u16) {
+        (1, 0)
+    }
+
+    fn unversioned_to_bytes(&self) -> Box<[u8]> {
+        messagepack_serialize(&self)
+    }
+
+    fn unversioned_from_bytes(minor_version: u16, bytes: &[u8]) -> Option<Result<Self, String>> {
+        if minor_version == 0 {
+            Some(messagepack_deserialize(bytes))
+        } else {
+            None
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.key_wraps.is_empty() {
+            return Err("at least one key wrap is required".into());
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ProtocolObject<'a> for MultiRecipientEncryptedTreasureMap {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use umbral_pre::{generate_kfrags, PublicKey, SecretKey, Signer, VerifiedKeyFrag};
+
+    use super::{
+        EncryptedTreasureMap, MultiAuthorizedTreasureMap, MultiRecipientEncryptedTreasureMap,
+        TreasureMap,
+    };
+    use crate::{
+        Address, DecryptionError, DeserializationError, PolicyValidity, ProtocolObject,
+        TreasureMapProblem, HRAC,
+    };
+
+    fn address(index: u8) -> Address {
+        let mut address = [0u8; Address::SIZE];
+        address[0] = index;
+        Address::new(&address)
+    }
+
+    /// Makes a treasure map with 2-of-3 kfrags assigned, returning a spare kfrag too.
+    fn make_treasure_map(
+        signer: &Signer,
+        delegating_sk: &SecretKey,
+        receiving_pk: &PublicKey,
+    ) -> (TreasureMap, Vec<VerifiedKeyFrag>) {
+        let hrac = HRAC::new(&signer.verifying_key(), receiving_pk, b"label");
+        let kfrags = generate_kfrags(delegating_sk, receiving_pk, signer, 2, 4, true, true);
+        let assigned_kfrags = kfrags[..3].iter().enumerate().map(|(i, kfrag)| {
+            (
+                address(i as u8 + 1),
+                (SecretKey::random().public_key(), kfrag.clone()),
+            )
+        });
+        let treasure_map = TreasureMap::new(
+            signer,
+            &hrac,
+            &delegating_sk.public_key(),
+            receiving_pk,
+            assigned_kfrags,
+            2,
+            &PolicyValidity::new(2000, None),
+        );
+        (treasure_map, kfrags.into_vec())
+    }
+
+    #[test]
+    fn invariants_checked_on_deserialization() {
+        let signer = Signer::new(SecretKey::random());
+        let receiving_pk = SecretKey::random().public_key();
+        let (treasure_map, _kfrags) =
+            make_treasure_map(&signer, &SecretKey::random(), &receiving_pk);
+        assert_eq!(
+            TreasureMap::from_bytes(&treasure_map.to_bytes()).unwrap(),
+            treasure_map
+        );
+
+        for threshold in [0, 4] {
+            let mut invalid_map = treasure_map.clone();
+            invalid_map.threshold = threshold;
+            assert!(matches!(
+                TreasureMap::from_bytes(&invalid_map.to_bytes()),
+                Err(DeserializationError::InvalidObject { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn amend() {
+        let signer = Signer::new(SecretKey::random());
+        let delegating_sk = SecretKey::random();
+        let receiving_pk = SecretKey::random().public_key();
+        let (treasure_map, kfrags) = make_treasure_map(&signer, &delegating_sk, &receiving_pk);
+        let validity = PolicyValidity::new(2000, None);
+
+        let new_ursula_sk = SecretKey::random();
+        let amendment = treasure_map
+            .amend(
+                &signer,
+                &receiving_pk,
+                [(
+                    address(2),
+                    (address(4), new_ursula_sk.public_key(), kfrags[3].clone()),
+                )],
+                &validity,
+                1000,
+            )
+            .unwrap();
+
+        let amended = amendment.treasure_map;
+        assert_eq!(amended.revision, 1);
+        assert!(!amended.destinations.contains_key(&address(2)));
+        let (verified_kfrag, _validity) = amended.destinations[&address(4)]
+            .decrypt(&new_ursula_sk, &amended.hrac, &signer.verifying_key())
+            .unwrap();
+        assert_eq!(verified_kfrag, kfrags[3]);
+
+        assert_eq!(amendment.revocation_orders.len(), 1);
+        assert_eq!(amendment.revocation_orders[0].hrac(), treasure_map.hrac);
+        assert_eq!(amendment.revocation_orders[0].issued_at(), 1000);
+        let (revoked_address, revoked_ekfrag) = amendment.revocation_orders[0]
+            .clone()
+            .verify(&signer.verifying_key())
+            .unwrap();
+        assert_eq!(revoked_address, address(2));
+        assert_eq!(&revoked_ekfrag, &treasure_map.destinations[&address(2)]);
+
+        let err = treasure_map
+            .amend(
+                &Signer::new(SecretKey::random()),
+                &receiving_pk,
+                [
+                    (
+                        address(5),
+                        (address(3), new_ursula_sk.public_key(), kfrags[0].clone()),
+                    ),
+                    (address(1), (address(4), receiving_pk, kfrags[0].clone())),
+                ],
+                &validity,
+                1000,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.problems,
+            [
+                TreasureMapProblem::PublisherMismatch,
+                TreasureMapProblem::UnknownDestination(address(5)),
+                TreasureMapProblem::DuplicateAddress(address(3)),
+                TreasureMapProblem::ReceivingKeyAsEncryptingKey(address(4)),
+                TreasureMapProblem::DuplicateKeyFrag(address(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn multi_recipient() {
+        let publisher_sk = SecretKey::random();
+        let signer = Signer::new(publisher_sk.clone());
+        let bob_sks = [
+            SecretKey::random(),
+            SecretKey::random(),
+            SecretKey::random(),
+        ];
+        let bob_pks = bob_sks.iter().map(|sk| sk.public_key()).collect::<Vec<_>>();
+        let (treasure_map, _kfrags) = make_treasure_map(&signer, &SecretKey::random(), &bob_pks[0]);
+
+        assert_eq!(
+            treasure_map
+                .encrypt_for_many(&signer, &[])
+                .unwrap_err()
+                .problems,
+            [TreasureMapProblem::NoRecipients]
+        );
+        let encrypted = treasure_map.encrypt_for_many(&signer, &bob_pks).unwrap();
+        let encrypted =
+            MultiRecipientEncryptedTreasureMap::from_bytes(&encrypted.to_bytes()).unwrap();
+        assert_eq!(encrypted.recipients_count(), 3);
+
+        for bob_sk in bob_sks.iter() {
+            assert_eq!(
+                encrypted
+                    .decrypt(bob_sk, &publisher_sk.public_key())
+                    .unwrap(),
+                treasure_map
+            );
+        }
+
+        assert_eq!(
+            encrypted.decrypt(&SecretKey::random(), &publisher_sk.public_key()),
+            Err(DecryptionError::NotARecipient)
+        );
+        assert_eq!(
+            encrypted.decrypt(&bob_sks[1], &SecretKey::random().public_key()),
+            Err(DecryptionError::PublisherMismatch)
+        );
+
+        // A key that can unwrap the body but is not in the signed recipient list.
+        let authorized = MultiAuthorizedTreasureMap::new(&signer, &bob_pks[..2], &treasure_map);
+        assert_eq!(
+            authorized.verify(&bob_pks[2], &publisher_sk.public_key()),
+            Err(DecryptionError::NotARecipient)
+        );
+    }
+
+    #[test]
+    fn publisher_consistency() {
+        let publisher_sk = SecretKey::random();
+        let signer = Signer::new(publisher_sk.clone());
+        let other_signer = Signer::new(SecretKey::random());
+        let bob_sk = SecretKey::random();
+        let (treasure_map, _kfrags) =
+            make_treasure_map(&signer, &SecretKey::random(), &bob_sk.public_key());
+
+        let err = treasure_map
+            .encrypt(&other_signer, &bob_sk.public_key())
+            .unwrap_err();
+        assert_eq!(err.problems, [TreasureMapProblem::PublisherMismatch]);
+        assert!(treasure_map
+            .encrypt_for_many(&other_signer, &[bob_sk.public_key()])
+            .is_err());
+
+        let encrypted = treasure_map.encrypt(&signer, &bob_sk.public_key()).unwrap();
+        assert_eq!(
+            encrypted.decrypt(&bob_sk, &other_signer.verifying_key()),
+            Err(DecryptionError::PublisherMismatch)
+        );
+
+        // Bypass the check on construction to simulate a map signed by someone else.
+        let forged = EncryptedTreasureMap::new(&other_signer, &bob_sk.public_key(), &treasure_map);
+        assert_eq!(
+            forged.decrypt(&bob_sk, &publisher_sk.public_key()),
+            Err(DecryptionError::VerificationFailed)
+        );
+        assert_eq!(
+            forged.decrypt(&bob_sk, &other_signer.verifying_key()),
+            Err(DecryptionError::PublisherMismatch)
+        );
+    }
+}
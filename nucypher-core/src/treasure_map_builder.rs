@@ -0,0 +1,333 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use umbral_pre::{PublicKey, Signer, VerifiedKeyFrag};
+
+use crate::address::Address;
+use crate::hrac::HRAC;
+use crate::key_frag::{keys_signed, PolicyValidity};
+use crate::treasure_map::TreasureMap;
+
+/// A single problem found when validating the contents of a [`TreasureMapBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreasureMapProblem {
+    /// The threshold is zero.
+    ZeroThreshold,
+    /// The threshold is larger than the number of distinct destination addresses.
+    ThresholdExceedsShares {
+        /// The requested threshold.
+        threshold: u8,
+        /// The number of distinct destination addresses.
+        shares: usize,
+    },
+    /// A destination has an all-zero address.
+    ZeroAddress,
+    /// The address was added more than once.
+    DuplicateAddress(Address),
+    /// The Ursula at this address has the same encrypting key as another destination.
+    DuplicateEncryptingKey(Address),
+    /// The Ursula at this address has the policy encrypting key as her encrypting key.
+    PolicyKeyAsEncryptingKey(Address),
+    /// The Ursula at this address has the receiving key as her encrypting key.
+    ReceivingKeyAsEncryptingKey(Address),
+    /// The key frag assigned to this address is already assigned to another destination.
+    DuplicateKeyFrag(Address),
+    /// The key frag assigned to this address was not generated by the publisher
+    /// for the policy encrypting key and the receiving key.
+    KeyFragMismatch(Address),
+    /// The key frag assigned to this address was generated without signing
+    /// the policy encrypting key and the receiving key into it.
+    UnsignedKeyFrag(Address),
+    /// The address to be replaced is not a destination of the treasure map.
+    UnknownDestination(Address),
+    /// The signer is not the publisher of the treasure map.
+    PublisherMismatch,
+    /// No recipient keys were given to encrypt the treasure map for.
+    NoRecipients,
+}
+
+impl fmt::Display for TreasureMapProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroThreshold => write!(f, "threshold must be non-zero"),
+            Self::ThresholdExceedsShares { threshold, shares } => write!(
+                f,
+                "threshold {} is larger than the number of destinations {}",
+                threshold, shares
+            ),
+            Self::ZeroAddress => write!(f, "zero address"),
+            Self::DuplicateAddress(address) => write!(f, "duplicate address {:?}", address),
+            Self::DuplicateEncryptingKey(address) => {
+                write!(f, "duplicate encrypting key at {:?}", address)
+            }
+            Self::PolicyKeyAsEncryptingKey(address) => {
+                write!(f, "policy key used as the encrypting key at {:?}", address)
+            }
+            Self::ReceivingKeyAsEncryptingKey(address) => {
+                write!(
+                    f,
+                    "receiving key used as the encrypting key at {:?}",
+                    address
+                )
+            }
+            Self::DuplicateKeyFrag(address) => write!(f, "duplicate key frag at {:?}", address),
+            Self::KeyFragMismatch(address) => {
+                write!(f, "key frag at {:?} does not belong to the policy", address)
+            }
+            Self::UnsignedKeyFrag(address) => {
+                write!(f, "key frag at {:?} does not have the keys signed", address)
+            }
+            Self::UnknownDestination(address) => {
+                write!(f, "{:?} is not a destination of the treasure map", address)
+            }
+            Self::PublisherMismatch => write!(f, "the signer is not the publisher"),
+            Self::NoRecipients => write!(f, "at least one recipient key is required"),
+        }
+    }
+}
+
+/// An error returned by [`TreasureMapBuilder::build`] and [`TreasureMap::amend`],
+/// listing every problem found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreasureMapError {
+    /// The problems found, in the order of checking.
+    pub problems: Vec<TreasureMapProblem>,
+}
+
+impl fmt::Display for TreasureMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid treasure map:")?;
+        for problem in self.problems.iter() {
+            write!(f, " {};", problem)?;
+        }
+        Ok(())
+    }
+}
+
+/// A fallible alternative to [`TreasureMap::new`]
+/// accepting the destinations one by one.
+#[derive(Debug, Clone)]
+pub struct TreasureMapBuilder {
+    hrac: HRAC,
+    policy_encrypting_key: PublicKey,
+    receiving_key: PublicKey,
+    threshold: u8,
+    validity: PolicyValidity,
+    verify_kfrags: bool,
+    destinations: Vec<(Address, PublicKey, VerifiedKeyFrag)>,
+}
+
+impl TreasureMapBuilder {
+    /// Starts a treasure map for the policy identified by `hrac`.
+    pub fn new(
+        hrac: &HRAC,
+        policy_encrypting_key: &PublicKey,
+        receiving_key: &PublicKey,
+        threshold: u8,
+        validity: &PolicyValidity,
+    ) -> Self {
+        Self {
+            hrac: *hrac,
+            policy_encrypting_key: *policy_encrypting_key,
+            receiving_key: *receiving_key,
+            threshold,
+            validity: *validity,
+            verify_kfrags: false,
+            destinations: Vec::new(),
+        }
+    }
+
+    /// If set, each key frag is verified against the publisher's key,
+    /// the policy encrypting key and the receiving key before encryption.
+    ///
+    /// Key frags without the keys signed into them are rejected regardless of this setting.
+    pub fn verify_kfrags(&mut self, verify: bool) -> &mut Self {
+        self.verify_kfrags = verify;
+        self
+    }
+
+    /// Assigns a key frag to the Ursula with the given address and encrypting key.
+    pub fn add_destination(
+        &mut self,
+        address: &Address,
+        ursula_encrypting_key: &PublicKey,
+        verified_kfrag: VerifiedKeyFrag,
+    ) -> &mut Self {
+        self.destinations
+            .push((*address, *ursula_encrypting_key, verified_kfrag));
+        self
+    }
+
+    fn problems(&self, publisher_verifying_key: &PublicKey) -> Vec<TreasureMapProblem> {
+        let mut problems = Vec::new();
+
+        if self.threshold == 0 {
+            problems.push(TreasureMapProblem::ZeroThreshold);
+        } else {
+            let shares = self
+                .destinations
+                .iter()
+                .enumerate()
+                .filter(|(i, (address, _, _))| {
+                    !self.destinations[..*i]
+                        .iter()
+                        .any(|(other, _, _)| other == address)
+                })
+                .count();
+            if self.threshold as usize > shares {
+                problems.push(TreasureMapProblem::ThresholdExceedsShares {
+                    threshold: self.threshold,
+                    shares,
+                });
+            }
+        }
+
+        for (i, (address, encrypting_key, kfrag)) in self.destinations.iter().enumerate() {
+            let previous = &self.destinations[..i];
+
+            if address.as_ref().iter().all(|byte| *byte == 0) {
+                problems.push(TreasureMapProblem::ZeroAddress);
+            }
+            if previous.iter().any(|(other, _, _)| other == address) {
+                problems.push(TreasureMapProblem::DuplicateAddress(*address));
+            }
+
+            if encrypting_key == &self.policy_encrypting_key {
+                problems.push(TreasureMapProblem::PolicyKeyAsEncryptingKey(*address));
+            } else if encrypting_key == &self.receiving_key {
+                problems.push(TreasureMapProblem::ReceivingKeyAsEncryptingKey(*address));
+            } else if previous.iter().any(|(_, other, _)| other == encrypting_key) {
+                problems.push(TreasureMapProblem::DuplicateEncryptingKey(*address));
+            }
+
+            if previous.iter().any(|(_, _, other)| other == kfrag) {
+                problems.push(TreasureMapProblem::DuplicateKeyFrag(*address));
+            }
+            let kfrag = kfrag.clone().unverify();
+            if !keys_signed(&kfrag, publisher_verifying_key) {
+                problems.push(TreasureMapProblem::UnsignedKeyFrag(*address));
+            } else if self.verify_kfrags
+                && kfrag
+                    .verify(
+                        publisher_verifying_key,
+                        Some(&self.policy_encrypting_key),
+                        Some(&self.receiving_key),
+                    )
+                    .is_err()
+            {
+                problems.push(TreasureMapProblem::KeyFragMismatch(*address));
+            }
+        }
+
+        problems
+    }
+
+    /// Validates the destinations and creates a treasure map signed by `signer`.
+    pub fn build(&self, signer: &Signer) -> Result<TreasureMap, TreasureMapError> {
+        let problems = self.problems(&signer.verifying_key());
+        if !problems.is_empty() {
+            return Err(TreasureMapError { problems });
+        }
+
+        Ok(TreasureMap::new(
+            signer,
+            &self.hrac,
+            &self.policy_encrypting_key,
+            &self.receiving_key,
+            self.destinations
+                .iter()
+                .map(|(address, key, kfrag)| (*address, (*key, kfrag.clone()))),
+            self.threshold,
+            &self.validity,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use umbral_pre::{generate_kfrags, SecretKey, Signer};
+
+    use super::{TreasureMapBuilder, TreasureMapProblem};
+    use crate::{Address, PolicyValidity, HRAC};
+
+    #[test]
+    fn validation() {
+        let delegating_sk = SecretKey::random();
+        let receiving_pk = SecretKey::random().public_key();
+        let signer = Signer::new(delegating_sk.clone());
+        let hrac = HRAC::new(&signer.verifying_key(), &receiving_pk, b"label");
+        let kfrags = generate_kfrags(&delegating_sk, &receiving_pk, &signer, 2, 3, true, true);
+        let address1 = Address::new(b"00000000000000000001");
+        let address2 = Address::new(b"00000000000000000002");
+        let ursula_pk = SecretKey::random().public_key();
+
+        let mut builder = TreasureMapBuilder::new(
+            &hrac,
+            &delegating_sk.public_key(),
+            &receiving_pk,
+            2,
+            &PolicyValidity::new(2000, None),
+        );
+        builder
+            .add_destination(&address1, &ursula_pk, kfrags[0].clone())
+            .add_destination(
+                &address2,
+                &SecretKey::random().public_key(),
+                kfrags[1].clone(),
+            );
+        let treasure_map = builder.build(&signer).unwrap();
+        assert_eq!(treasure_map.destinations.len(), 2);
+
+        let foreign_kfrags = generate_kfrags(
+            &SecretKey::random(),
+            &receiving_pk,
+            &signer,
+            1,
+            1,
+            true,
+            true,
+        );
+        let mut builder = TreasureMapBuilder::new(
+            &hrac,
+            &delegating_sk.public_key(),
+            &receiving_pk,
+            4,
+            &PolicyValidity::new(2000, None),
+        );
+        builder
+            .verify_kfrags(true)
+            .add_destination(&address1, &ursula_pk, kfrags[0].clone())
+            .add_destination(&address1, &ursula_pk, kfrags[0].clone())
+            .add_destination(&address2, &receiving_pk, foreign_kfrags[0].clone());
+        assert_eq!(
+            builder.build(&signer).unwrap_err().problems,
+            [
+                TreasureMapProblem::ThresholdExceedsShares {
+                    threshold: 4,
+                    shares: 2
+                },
+                TreasureMapProblem::DuplicateAddress(address1),
+                TreasureMapProblem::DuplicateEncryptingKey(address1),
+                TreasureMapProblem::DuplicateKeyFrag(address1),
+                TreasureMapProblem::ReceivingKeyAsEncryptingKey(address2),
+                TreasureMapProblem::KeyFragMismatch(address2),
+            ]
+        );
+
+        // Key frags without the keys signed are rejected even if not verified
+        let unsigned_kfrags =
+            generate_kfrags(&delegating_sk, &receiving_pk, &signer, 1, 1, false, false);
+        let mut builder = TreasureMapBuilder::new(
+            &hrac,
+            &delegating_sk.public_key(),
+            &receiving_pk,
+            1,
+            &PolicyValidity::new(2000, None),
+        );
+        builder.add_destination(&address1, &ursula_pk, unsigned_kfrags[0].clone());
+        assert_eq!(
+            builder.build(&signer).unwrap_err().problems,
+            [TreasureMapProblem::UnsignedKeyFrag(address1)]
+        );
+    }
+}
@@ -66,25 +66,45 @@ impl ProtocolObjectHeader {
     }
 }
 
-#[derive(Debug)]
+/// An error returned by [`ProtocolObject::from_bytes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DeserializationError {
+    /// The bytestring is shorter than the header.
     TooShort {
+        /// The minimum expected length.
         expected: usize,
+        /// The actual length.
         received: usize,
     },
+    /// The brand in the header does not match the object type.
     IncorrectHeader {
+        /// The brand of the object type.
         expected: [u8; 4],
+        /// The brand in the header.
         received: [u8; 4],
     },
+    /// The major version in the header differs from the supported one.
     MajorVersionMismatch {
+        /// The supported major version.
         expected: u16,
+        /// The major version in the header.
         received: u16,
     },
+    /// The minor version in the header is newer than the supported one.
     UnsupportedMinorVersion {
+        /// The latest supported minor version.
         expected: u16,
+        /// The minor version in the header.
         received: u16,
     },
+    /// The payload after the header could not be deserialized.
     BadPayload {
+        /// The description of the problem.
+        error_msg: String,
+    },
+    /// The payload was deserialized, but violates the invariants of the object.
+    InvalidObject {
+        /// The description of the problem.
         error_msg: String,
     },
 }
@@ -115,6 +135,7 @@ impl fmt::Display for DeserializationError {
             Self::BadPayload { error_msg } => {
                 write!(f, "payload deserialization failed: {}", error_msg)
             }
+            Self::InvalidObject { error_msg } => write!(f, "invalid object: {}", error_msg),
         }
     }
 }
@@ -136,6 +157,12 @@ pub trait ProtocolObjectInner<'a>: Serialize + Deserialize<'a> {
     fn unversioned_to_bytes(&self) -> Box<[u8]>;
 
     fn unversioned_from_bytes(minor_version: u16, bytes: &'a [u8]) -> Option<Result<Self, String>>;
+
+    /// Checks the invariants enforced by the object's constructor.
+    /// Called on every successful deserialization.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
 }
 
 /// This is a versioned protocol object.
@@ -203,6 +230,10 @@ pub trait ProtocolObject<'a>: ProtocolObjectInner<'a> {
             None => panic!("minor version {} is not supported", header.minor_version),
         };
 
-        result.map_err(|msg| DeserializationError::BadPayload { error_msg: msg })
+        let object = result.map_err(|msg| DeserializationError::BadPayload { error_msg: msg })?;
+        object
+            .validate()
+            .map_err(|msg| DeserializationError::InvalidObject { error_msg: msg })?;
+        Ok(object)
     }
 }